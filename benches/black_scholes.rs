@@ -1,6 +1,5 @@
 use binominal_tree_model::black_scholes::black_value;
 use binominal_tree_model::instruments::OptionType;
-use std::hint::black_box;
 
 use criterion::{Criterion, criterion_group, criterion_main};
 use rayon::iter::IntoParallelIterator;
@@ -22,10 +21,10 @@ fn criterion_benchmark_bs(c: &mut Criterion) {
     let mut group = c.benchmark_group("Black Scholes Option benches");
 
     group.bench_function("american call value 100000 parallel", |b| {
-        b.iter(|| black_box(bs_american_call_value_100000_par()))
+        b.iter(bs_american_call_value_100000_par)
     });
     group.bench_function("american call value 100000 non-parallel", |b| {
-        b.iter(|| black_box(bs_american_call_value_100000()))
+        b.iter(bs_american_call_value_100000)
     });
     group.finish();
 }
@@ -1,4 +1,12 @@
+use binominal_tree_model::batch::{BatchRecord, batch_greeks};
+use binominal_tree_model::binomial_tree_map::array::ArrayBinomialTreeMap;
+use binominal_tree_model::binomial_tree_map::dynamic::DynamicBinomialTreeMap;
+use binominal_tree_model::binomial_tree_map::r#static::StaticBinomialTreeMap;
 use binominal_tree_model::eval_binomial_tree_with_steps;
+use binominal_tree_model::instruments::{AmericanOption, Exercise, StrikeContract};
+use binominal_tree_model::model::{
+    CoxRossRubenstein, Expiry, Greeks, Spot, Value, Workspace, border_truncation, leaf_smoothing,
+};
 use std::hint::black_box;
 
 use criterion::{Criterion, criterion_group, criterion_main};
@@ -7,29 +15,30 @@ use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use binominal_tree_model::instruments::OptionType;
 use std::time::Duration;
 
-fn european_call_value_100() {
+fn european_call_value_100() -> Value {
     eval_binomial_tree_with_steps!(100, EuropeanOption, Call, 95.0, 100.0, 0.5, 0.3, 0.05, 0.0)
-        .value();
+        .value()
 }
 
-fn american_call_value_100() {
+fn american_call_value_100() -> Value {
     eval_binomial_tree_with_steps!(100, AmericanOption, Call, 95.0, 100.0, 0.5, 0.3, 0.05, 0.0)
-        .value();
+        .value()
 }
 
-fn european_call_greeks_100() {
+#[allow(dead_code)]
+fn european_call_greeks_100() -> Greeks {
     eval_binomial_tree_with_steps!(100, EuropeanOption, Call, 95.0, 100.0, 0.5, 0.3, 0.05, 0.0)
-        .greeks();
+        .greeks()
 }
 
-fn american_call_greeks_100() {
+fn american_call_greeks_100() -> Greeks {
     eval_binomial_tree_with_steps!(100, AmericanOption, Call, 95.0, 100.0, 0.5, 0.3, 0.05, 0.0)
-        .greeks();
+        .greeks()
 }
 
-fn american_call_greeks_50() {
+fn american_call_greeks_50() -> Greeks {
     eval_binomial_tree_with_steps!(50, AmericanOption, Call, 95.0, 100.0, 0.5, 0.3, 0.05, 0.0)
-        .greeks();
+        .greeks()
 }
 
 fn american_call_greeks_30_1000() {
@@ -49,6 +58,99 @@ fn american_call_greeks_30_1000() {
     });
 }
 
+fn american_call_greeks_30_1000_workspace() {
+    (1..1000).into_par_iter().for_each(|_| {
+        Workspace::<StaticBinomialTreeMap>::with_thread_local(|workspace| {
+            let model: CoxRossRubenstein<
+                StaticBinomialTreeMap,
+                leaf_smoothing::Black,
+                border_truncation::Black,
+            > = CoxRossRubenstein::new(
+                StaticBinomialTreeMap::with_capacity(30),
+                Spot(100.0),
+                30,
+                Expiry(0.5),
+                0.3,
+                0.05,
+                0.0,
+            );
+            let eval = model.eval_in(AmericanOption::new(OptionType::Call, 95.0, 0.5), workspace);
+            eval.greeks();
+            eval.recycle(workspace);
+        })
+    });
+}
+
+/// American call greeks on a 100-step tree stored in the given map.
+macro_rules! american_call_greeks_100_on {
+    ($stack:ty, $tree_map:expr) => {{
+        let model: CoxRossRubenstein<$stack, leaf_smoothing::Black, border_truncation::Black> =
+            CoxRossRubenstein::new($tree_map, Spot(100.0), 100, Expiry(0.5), 0.3, 0.05, 0.0);
+        model
+            .eval(AmericanOption::new(OptionType::Call, 95.0, 0.5))
+            .greeks();
+    }};
+}
+
+fn american_call_greeks_100_static() {
+    american_call_greeks_100_on!(
+        StaticBinomialTreeMap,
+        StaticBinomialTreeMap::with_capacity(100)
+    )
+}
+
+fn american_call_greeks_100_dynamic() {
+    american_call_greeks_100_on!(DynamicBinomialTreeMap, DynamicBinomialTreeMap::new(100))
+}
+
+fn american_call_greeks_100_array() {
+    american_call_greeks_100_on!(ArrayBinomialTreeMap, ArrayBinomialTreeMap::new(100))
+}
+
+fn american_call_greeks_30_1000_batch(records: &[BatchRecord]) {
+    batch_greeks(records);
+}
+
+/// American call value on a 2000-step array map, splitting levels of `min_width` nodes.
+fn american_call_value_2000_array(min_width: usize) {
+    let model: CoxRossRubenstein<ArrayBinomialTreeMap, leaf_smoothing::Black> =
+        CoxRossRubenstein::new(
+            ArrayBinomialTreeMap::new(2000),
+            Spot(100.0),
+            2000,
+            Expiry(0.5),
+            0.3,
+            0.05,
+            0.0,
+        )
+        .with_parallel_levels(min_width);
+    model
+        .eval(AmericanOption::new(OptionType::Call, 95.0, 0.5))
+        .value();
+}
+
+fn criterion_benchmark_maps(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Tree map benches");
+
+    group.bench_function("american call greeks 100 steps, static map", |b| {
+        b.iter(american_call_greeks_100_static)
+    });
+    group.bench_function("american call greeks 100 steps, dynamic map", |b| {
+        b.iter(american_call_greeks_100_dynamic)
+    });
+    group.bench_function("american call greeks 100 steps, array map", |b| {
+        b.iter(american_call_greeks_100_array)
+    });
+    group.bench_function("american call value 2000 steps, array map", |b| {
+        b.iter(|| american_call_value_2000_array(usize::MAX))
+    });
+    group.bench_function(
+        "american call value 2000 steps, array map, parallel levels",
+        |b| b.iter(|| american_call_value_2000_array(500)),
+    );
+    group.finish();
+}
+
 fn criterion_benchmark_method(c: &mut Criterion) {
     let mut group = c.benchmark_group("Option benches");
 
@@ -66,8 +168,29 @@ fn criterion_benchmark_method(c: &mut Criterion) {
         b.iter(|| black_box(american_call_greeks_50()))
     });
     group.bench_function("american call greeks 1000 times 30 steps", |b| {
-        b.iter(|| black_box(american_call_greeks_30_1000()))
+        b.iter(american_call_greeks_30_1000)
+    });
+    let records = vec![
+        BatchRecord {
+            exercise: Exercise::American,
+            option_type: OptionType::Call,
+            strike: 95.0,
+            spot: 100.0,
+            expiry: 0.5,
+            volatility: 0.3,
+            interest_rate: 0.05,
+            dividends: 0.0,
+            steps: 30,
+        };
+        1000
+    ];
+    group.bench_function("american call greeks 1000 times 30 steps, batch", |b| {
+        b.iter(|| american_call_greeks_30_1000_batch(&records))
     });
+    group.bench_function(
+        "american call greeks 1000 times 30 steps, thread-local workspace",
+        |b| b.iter(american_call_greeks_30_1000_workspace),
+    );
     group.finish();
 }
 
@@ -80,7 +203,7 @@ fn alternate_measurement() -> Criterion {
 criterion_group!(
     name = static_binomial_tree;
     config = alternate_measurement();
-    targets = criterion_benchmark_method
+    targets = criterion_benchmark_method, criterion_benchmark_maps
 );
 
 criterion_main!(static_binomial_tree);
@@ -0,0 +1,152 @@
+//! Barone-Adesi and Whaley (1987) quadratic approximation of American options.
+//!
+//! The early exercise premium solves an approximation of the Black-Scholes equation
+//! with a power of the spot, matched to the intrinsic value at the critical price,
+//! which is found by Newton iteration.
+
+use crate::Float;
+use crate::analytical::finite_difference_greeks;
+use crate::analytical::normal_distribution::Normal;
+use crate::black_scholes::{BlackGreeks, black_value};
+use crate::instruments::OptionType;
+
+const MAX_ITERATIONS: usize = 100;
+/// Relative to the strike
+const TOLERANCE: Float = 1e-6;
+
+/// Value of an American call or put.
+pub fn american_value(
+    option_type: OptionType,
+    spot: Float,
+    strike: Float,
+    vol: Float,
+    rate: Float,
+    dividends: Float,
+    expiry: Float,
+) -> Float {
+    let european = |spot| black_value(option_type, spot, strike, vol, rate, dividends, expiry);
+    let never_exercised = match option_type {
+        OptionType::Call => dividends <= 0.0,
+        OptionType::Put => rate <= 0.0,
+    };
+    if never_exercised {
+        return european(spot);
+    }
+
+    let n = Normal::new();
+    let eta: Float = match option_type {
+        OptionType::Put => -1.0,
+        OptionType::Call => 1.0,
+    };
+    let carry = rate - dividends;
+    let vol_sqrt_expiry = vol * expiry.sqrt();
+    let carry_discount = ((carry - rate) * expiry).exp();
+    let d1 = |price: Float| {
+        ((price / strike).ln() + (carry + vol.powi(2) / 2.0) * expiry) / vol_sqrt_expiry
+    };
+
+    // Exponent of the early exercise premium, positive for calls and negative for puts
+    let m = 2.0 * rate / vol.powi(2);
+    let nn = 2.0 * carry / vol.powi(2);
+    let exponent = |m: Float| (-(nn - 1.0) + eta * ((nn - 1.0).powi(2) + 4.0 * m).sqrt()) / 2.0;
+    let q = exponent(m / (1.0 - (-rate * expiry).exp()));
+
+    // Seed of Barone-Adesi and Whaley, from the critical price of the perpetual option
+    let perpetual_critical = strike / (1.0 - 1.0 / exponent(m));
+    let h =
+        -(carry * expiry + 2.0 * eta * vol_sqrt_expiry) * strike / (perpetual_critical - strike);
+    let mut critical = perpetual_critical - (perpetual_critical - strike) * h.exp();
+
+    // Newton iteration for the price where exercising is worth the premium
+    for _ in 0..MAX_ITERATIONS {
+        let d1 = d1(critical);
+        let rhs =
+            european(critical) + eta * (1.0 - carry_discount * n.cdf(eta * d1)) * critical / q;
+        let lhs = eta * (critical - strike);
+        if (lhs - rhs).abs() / strike < TOLERANCE {
+            break;
+        }
+        let slope = eta * carry_discount * n.cdf(eta * d1) * (1.0 - 1.0 / q)
+            + (eta - carry_discount * n.pdf(d1) / vol_sqrt_expiry) / q;
+        critical = (strike + eta * (rhs - slope * critical)) / (1.0 - eta * slope);
+    }
+
+    if eta * (spot - critical) < 0.0 {
+        let premium = eta * critical / q * (1.0 - carry_discount * n.cdf(eta * d1(critical)));
+        european(spot) + premium * (spot / critical).powf(q)
+    } else {
+        option_type.payoff(strike, spot)
+    }
+}
+
+/// Greeks of an American call or put, by finite differences of [`american_value`], in
+/// the [`BlackGreeks`] of the European closed form.
+pub fn american_greeks(
+    option_type: OptionType,
+    spot: Float,
+    strike: Float,
+    vol: Float,
+    rate: Float,
+    dividends: Float,
+    expiry: Float,
+) -> BlackGreeks {
+    finite_difference_greeks(
+        |spot, vol, rate, dividends, expiry| {
+            american_value(option_type, spot, strike, vol, rate, dividends, expiry)
+        },
+        spot,
+        vol,
+        rate,
+        dividends,
+        expiry,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::black_scholes::black_greeks;
+
+    #[test]
+    fn test_american_value() {
+        // Table 4 of Barone-Adesi and Whaley (1987), r = 0.08, b = -0.04, volatility 0.2
+        let rate = 0.08;
+        let dividends = 0.12;
+        for (spot, call, put) in [
+            (80.0, 0.03, 20.42),
+            (90.0, 0.59, 11.25),
+            (100.0, 3.52, 4.40),
+            (110.0, 10.31, 1.12),
+            (120.0, 20.0, 0.18),
+        ] {
+            let value =
+                |option_type| american_value(option_type, spot, 100.0, 0.2, rate, dividends, 0.25);
+            assert!((value(OptionType::Call) - call).abs() < 6e-3);
+            assert!((value(OptionType::Put) - put).abs() < 6e-3);
+        }
+
+        // Calls on assets without dividends are never exercised early
+        assert_eq!(
+            american_value(OptionType::Call, 100.0, 95.0, 0.3, 0.05, 0.0, 0.5),
+            black_value(OptionType::Call, 100.0, 95.0, 0.3, 0.05, 0.0, 0.5)
+        );
+        // Deep in the money puts are exercised at once
+        assert_eq!(
+            american_value(OptionType::Put, 40.0, 100.0, 0.2, 0.08, 0.0, 1.0),
+            60.0
+        );
+    }
+
+    #[test]
+    fn test_american_greeks() {
+        let greeks = american_greeks(OptionType::Put, 100.0, 105.0, 0.3, 0.05, 0.02, 0.5);
+        let european = black_greeks(OptionType::Put, 100.0, 105.0, 0.3, 0.05, 0.02, 0.5);
+
+        // Early exercise makes the put more sensitive to the spot and less to the rate
+        assert!(greeks.value > european.value);
+        assert!(greeks.delta < european.delta && greeks.delta > -1.0);
+        assert!(greeks.gamma > european.gamma);
+        assert!(greeks.rho < 0.0 && greeks.rho > european.rho);
+        assert!(greeks.vega > 0.0 && greeks.theta < 0.0 && greeks.psi > 0.0);
+    }
+}
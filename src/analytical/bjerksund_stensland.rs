@@ -0,0 +1,241 @@
+//! Bjerksund and Stensland (2002) approximation of American options.
+//!
+//! Exercise is approximated by a flat exercise boundary over the first 61.8% of the life
+//! of the option and another one over the rest, which prices the call as a sum of
+//! barrier-like terms with univariate and bivariate normal probabilities. Puts are
+//! priced as calls with spot and strike, and rate and dividends, swapped.
+
+use crate::Float;
+use crate::analytical::finite_difference_greeks;
+use crate::analytical::normal_distribution::Normal;
+use crate::black_scholes::{BlackGreeks, black_value};
+use crate::instruments::OptionType;
+
+/// Value of an American call or put.
+pub fn american_value(
+    option_type: OptionType,
+    spot: Float,
+    strike: Float,
+    vol: Float,
+    rate: Float,
+    dividends: Float,
+    expiry: Float,
+) -> Float {
+    match option_type {
+        OptionType::Call => call_value(spot, strike, vol, rate, dividends, expiry),
+        OptionType::Put => call_value(strike, spot, vol, dividends, rate, expiry),
+    }
+}
+
+/// Greeks of an American call or put, by finite differences of [`american_value`], in
+/// the [`BlackGreeks`] of the European closed form.
+pub fn american_greeks(
+    option_type: OptionType,
+    spot: Float,
+    strike: Float,
+    vol: Float,
+    rate: Float,
+    dividends: Float,
+    expiry: Float,
+) -> BlackGreeks {
+    finite_difference_greeks(
+        |spot, vol, rate, dividends, expiry| {
+            american_value(option_type, spot, strike, vol, rate, dividends, expiry)
+        },
+        spot,
+        vol,
+        rate,
+        dividends,
+        expiry,
+    )
+}
+
+fn call_value(
+    spot: Float,
+    strike: Float,
+    vol: Float,
+    rate: Float,
+    dividends: Float,
+    expiry: Float,
+) -> Float {
+    if dividends <= 0.0 {
+        return black_value(OptionType::Call, spot, strike, vol, rate, dividends, expiry);
+    }
+
+    let carry = rate - dividends;
+    let variance = vol.powi(2);
+    let beta = (0.5 - carry / variance)
+        + ((carry / variance - 0.5).powi(2) + 2.0 * rate / variance).sqrt();
+
+    // Exercise boundaries of the perpetual option and at expiry
+    let boundary_perpetual = beta / (beta - 1.0) * strike;
+    let boundary_expiry = strike.max(rate / dividends * strike);
+    let boundary = |time: Float| {
+        let h = -(carry * time + 2.0 * vol * time.sqrt()) * strike.powi(2)
+            / ((boundary_perpetual - boundary_expiry) * boundary_expiry);
+        boundary_expiry + (boundary_perpetual - boundary_expiry) * (1.0 - h.exp())
+    };
+    // Golden section split of the life of the option
+    let first_period = 0.5 * ((5.0 as Float).sqrt() - 1.0) * expiry;
+    let first_boundary = boundary(first_period);
+    let second_boundary = boundary(expiry);
+    if spot >= second_boundary {
+        return spot - strike;
+    }
+
+    let market = Market {
+        normal: Normal::new(),
+        spot,
+        vol,
+        rate,
+        carry,
+    };
+    let phi = |gamma, time, h, i| market.phi(gamma, time, h, i);
+    let psi = |gamma, h, i2, i1| market.psi(gamma, expiry, h, i2, i1, first_period);
+
+    // `a(I) S^beta`, with `a(I) = (I - X) I^-beta`, without the large powers
+    let a1 = (first_boundary - strike) * (spot / first_boundary).powf(beta);
+    let a2 = (second_boundary - strike) * (spot / second_boundary).powf(beta);
+    let (i1, i2, t1) = (first_boundary, second_boundary, first_period);
+
+    a2 - a2 * phi(beta, t1, i2, i2) + spot * phi(1.0, t1, i2, i2)
+        - spot * phi(1.0, t1, i1, i2)
+        - strike * phi(0.0, t1, i2, i2)
+        + strike * phi(0.0, t1, i1, i2)
+        + a1 * phi(beta, t1, i1, i2)
+        - a1 * psi(beta, i1, i2, i1)
+        + spot * psi(1.0, i1, i2, i1)
+        - spot * psi(1.0, strike, i2, i1)
+        - strike * psi(0.0, i1, i2, i1)
+        + strike * psi(0.0, strike, i2, i1)
+}
+
+struct Market {
+    normal: Normal,
+    spot: Float,
+    vol: Float,
+    rate: Float,
+    carry: Float,
+}
+
+impl Market {
+    /// Drift of `S^gamma` under the risk-neutral measure, less the interest rate.
+    fn lambda(&self, gamma: Float) -> Float {
+        -self.rate + gamma * self.carry + 0.5 * gamma * (gamma - 1.0) * self.vol.powi(2)
+    }
+
+    fn kappa(&self, gamma: Float) -> Float {
+        2.0 * self.carry / self.vol.powi(2) + 2.0 * gamma - 1.0
+    }
+
+    /// Discounted `S^gamma` paid at `time` if the spot stays below `i` and ends below `h`,
+    /// divided by the current `S^gamma`.
+    fn phi(&self, gamma: Float, time: Float, h: Float, i: Float) -> Float {
+        let spot = self.spot;
+        let vol_sqrt_time = self.vol * time.sqrt();
+        let d = -((spot / h).ln() + (self.carry + (gamma - 0.5) * self.vol.powi(2)) * time)
+            / vol_sqrt_time;
+
+        (self.lambda(gamma) * time).exp()
+            * (self.normal.cdf(d)
+                - (i / spot).powf(self.kappa(gamma))
+                    * self.normal.cdf(d - 2.0 * (i / spot).ln() / vol_sqrt_time))
+    }
+
+    /// As [`Self::phi`], for the barrier `i1` up to `t1` and `i2` from `t1` to `t2`.
+    fn psi(&self, gamma: Float, t2: Float, h: Float, i2: Float, i1: Float, t1: Float) -> Float {
+        let spot = self.spot;
+        let n = &self.normal;
+        let drift = self.carry + (gamma - 0.5) * self.vol.powi(2);
+        let (vol_sqrt_t1, vol_sqrt_t2) = (self.vol * t1.sqrt(), self.vol * t2.sqrt());
+
+        let e = |x: Float, sign: Float| (x.ln() + sign * drift * t1) / vol_sqrt_t1;
+        let e1 = e(spot / i1, 1.0);
+        let e2 = e(i2.powi(2) / (spot * i1), 1.0);
+        let e3 = e(spot / i1, -1.0);
+        let e4 = e(i2.powi(2) / (spot * i1), -1.0);
+
+        let f = |x: Float| (x.ln() + drift * t2) / vol_sqrt_t2;
+        let f1 = f(spot / h);
+        let f2 = f(i2.powi(2) / (spot * h));
+        let f3 = f(i1.powi(2) / (spot * h));
+        let f4 = f(spot * i1.powi(2) / (h * i2.powi(2)));
+
+        let rho = (t1 / t2).sqrt();
+        let kappa = self.kappa(gamma);
+
+        (self.lambda(gamma) * t2).exp()
+            * (n.bivariate_cdf(-e1, -f1, rho)
+                - (i2 / spot).powf(kappa) * n.bivariate_cdf(-e2, -f2, rho)
+                - (i1 / spot).powf(kappa) * n.bivariate_cdf(-e3, -f3, -rho)
+                + (i1 / i2).powf(kappa) * n.bivariate_cdf(-e4, -f4, -rho))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analytical::barone_adesi_whaley;
+
+    #[test]
+    fn test_american_value() {
+        // Haug (2007), r = 0.04, b = -0.04, volatility 0.35
+        let call = american_value(OptionType::Call, 42.0, 40.0, 0.35, 0.04, 0.08, 0.75);
+        assert!((call - 5.2869).abs() < 1e-3);
+        // The transformed call, with spot and strike and rate and dividends swapped
+        let put = american_value(OptionType::Put, 40.0, 42.0, 0.35, 0.08, 0.04, 0.75);
+        assert_eq!(put, call);
+
+        // Near the values of Barone-Adesi and Whaley, and above the European ones
+        for (option_type, spot, strike, rate, dividends, expiry) in [
+            (OptionType::Put, 100.0, 105.0, 0.05, 0.02, 0.5),
+            (OptionType::Call, 100.0, 95.0, 0.05, 0.08, 0.5),
+            (OptionType::Put, 90.0, 100.0, 0.08, 0.0, 1.0),
+        ] {
+            let value = american_value(option_type, spot, strike, 0.3, rate, dividends, expiry);
+            let baw = barone_adesi_whaley::american_value(
+                option_type,
+                spot,
+                strike,
+                0.3,
+                rate,
+                dividends,
+                expiry,
+            );
+            let european = black_value(option_type, spot, strike, 0.3, rate, dividends, expiry);
+            assert!((value - baw).abs() < 5e-2);
+            assert!(value > european);
+        }
+
+        // Calls on assets without dividends are never exercised early
+        assert_eq!(
+            american_value(OptionType::Call, 100.0, 95.0, 0.3, 0.05, 0.0, 0.5),
+            black_value(OptionType::Call, 100.0, 95.0, 0.3, 0.05, 0.0, 0.5)
+        );
+        // Deep in the money puts are exercised at once
+        assert_eq!(
+            american_value(OptionType::Put, 40.0, 100.0, 0.2, 0.08, 0.0, 1.0),
+            60.0
+        );
+    }
+
+    #[test]
+    fn test_american_greeks() {
+        let greeks = american_greeks(OptionType::Put, 100.0, 105.0, 0.3, 0.05, 0.02, 0.5);
+        let baw = barone_adesi_whaley::american_greeks(
+            OptionType::Put,
+            100.0,
+            105.0,
+            0.3,
+            0.05,
+            0.02,
+            0.5,
+        );
+
+        assert!((greeks.delta - baw.delta).abs() < 1e-2);
+        assert!((greeks.gamma - baw.gamma).abs() < 1e-3);
+        assert!((greeks.vega - baw.vega).abs() < 0.5);
+        assert!((greeks.rho - baw.rho).abs() < 1.5);
+        assert!(greeks.theta < 0.0 && greeks.psi > 0.0);
+    }
+}
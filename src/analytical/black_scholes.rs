@@ -1,33 +1,293 @@
+use crate::Float;
 use crate::analytical::normal_distribution::Normal;
-use crate::instruments::OptionType;
+use crate::instruments::{Barrier, BarrierKind, OptionType};
 
 pub fn black_value(
     option_type: OptionType,
-    spot: f32,
-    strike: f32,
-    vol: f32,
-    rate: f32,
-    dividends: f32,
-    expiry: f32,
-) -> f32 {
+    spot: Float,
+    strike: Float,
+    vol: Float,
+    rate: Float,
+    dividends: Float,
+    expiry: Float,
+) -> Float {
     let n = Normal::new();
 
-    let d1 = (((spot / strike).ln() + expiry * (rate - dividends + vol.powi(2) / 2.0))
-        / (vol * expiry.sqrt())) as f64;
-    let d2 = d1 - (vol * expiry.sqrt()) as f64;
+    let d1 = ((spot / strike).ln() + expiry * (rate - dividends + vol.powi(2) / 2.0))
+        / (vol * expiry.sqrt());
+    let d2 = d1 - vol * expiry.sqrt();
 
     match option_type {
         OptionType::Put => {
-            strike * (-rate * expiry).exp() * (n.cdf(-d2) as f32)
-                - spot * (-dividends * expiry).exp() * (n.cdf(-d1) as f32)
+            strike * (-rate * expiry).exp() * n.cdf(-d2)
+                - spot * (-dividends * expiry).exp() * n.cdf(-d1)
         }
         OptionType::Call => {
-            spot * (-dividends * expiry).exp() * (n.cdf(d1) as f32)
-                - strike * (-rate * expiry).exp() * (n.cdf(d2) as f32)
+            spot * (-dividends * expiry).exp() * n.cdf(d1)
+                - strike * (-rate * expiry).exp() * n.cdf(d2)
         }
     }
 }
 
+/// Sensitivities of a call or put: closed-form for European options, see [`black_greeks`],
+/// and by finite differences for the American approximations, see
+/// [`barone_adesi_whaley::american_greeks`](crate::analytical::barone_adesi_whaley::american_greeks).
+///
+/// Theta and charm are derivatives in calendar time, as the theta of the trees.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BlackGreeks {
+    pub value: Float,
+    /// dV/dS
+    pub delta: Float,
+    /// d²V/dS²
+    pub gamma: Float,
+    /// dV/dσ
+    pub vega: Float,
+    /// dV/dt
+    pub theta: Float,
+    /// dV/dr
+    pub rho: Float,
+    /// dV/dq
+    pub psi: Float,
+    /// d²V/dSdσ
+    pub vanna: Float,
+    /// d²V/dσ²
+    pub volga: Float,
+    /// d²V/dSdt
+    pub charm: Float,
+}
+
+/// Value and sensitivities of a European call or put, see [`black_value`].
+pub fn black_greeks(
+    option_type: OptionType,
+    spot: Float,
+    strike: Float,
+    vol: Float,
+    rate: Float,
+    dividends: Float,
+    expiry: Float,
+) -> BlackGreeks {
+    let n = Normal::new();
+    let eta: Float = match option_type {
+        OptionType::Put => -1.0,
+        OptionType::Call => 1.0,
+    };
+
+    let vol_sqrt_expiry = vol * expiry.sqrt();
+    let carry = rate - dividends;
+    let d1 = ((spot / strike).ln() + expiry * (carry + vol.powi(2) / 2.0)) / vol_sqrt_expiry;
+    let d2 = d1 - vol_sqrt_expiry;
+
+    let dividend_discount = (-dividends * expiry).exp();
+    let discount = (-rate * expiry).exp();
+    // Discounted probability and density terms of the asset and the strike leg
+    let asset = spot * dividend_discount * n.cdf(eta * d1);
+    let cash = strike * discount * n.cdf(eta * d2);
+    let density = dividend_discount * n.pdf(d1);
+    let vega = spot * density * expiry.sqrt();
+
+    BlackGreeks {
+        value: eta * (asset - cash),
+        delta: eta * dividend_discount * n.cdf(eta * d1),
+        gamma: density / (spot * vol_sqrt_expiry),
+        vega,
+        theta: -spot * density * vol / (2.0 * expiry.sqrt()) - eta * rate * cash
+            + eta * dividends * asset,
+        rho: eta * expiry * cash,
+        psi: -eta * expiry * asset,
+        vanna: -density * d2 / vol,
+        volga: vega * d1 * d2 / vol,
+        charm: eta * dividends * dividend_discount * n.cdf(eta * d1)
+            - density * (2.0 * carry * expiry - d2 * vol_sqrt_expiry)
+                / (2.0 * expiry * vol_sqrt_expiry),
+    }
+}
+
+/// Cash-or-nothing option paying 1 if it expires in the money.
+pub fn cash_or_nothing_value(
+    option_type: OptionType,
+    spot: Float,
+    strike: Float,
+    vol: Float,
+    rate: Float,
+    dividends: Float,
+    expiry: Float,
+) -> Float {
+    let n = Normal::new();
+
+    let d2 = ((spot / strike).ln() + expiry * (rate - dividends - vol.powi(2) / 2.0))
+        / (vol * expiry.sqrt());
+
+    match option_type {
+        OptionType::Put => (-rate * expiry).exp() * n.cdf(-d2),
+        OptionType::Call => (-rate * expiry).exp() * n.cdf(d2),
+    }
+}
+
+/// Asset-or-nothing option paying the asset if it expires in the money.
+pub fn asset_or_nothing_value(
+    option_type: OptionType,
+    spot: Float,
+    strike: Float,
+    vol: Float,
+    rate: Float,
+    dividends: Float,
+    expiry: Float,
+) -> Float {
+    let n = Normal::new();
+
+    let d1 = ((spot / strike).ln() + expiry * (rate - dividends + vol.powi(2) / 2.0))
+        / (vol * expiry.sqrt());
+
+    match option_type {
+        OptionType::Put => spot * (-dividends * expiry).exp() * n.cdf(-d1),
+        OptionType::Call => spot * (-dividends * expiry).exp() * n.cdf(d1),
+    }
+}
+
+/// One-touch option paying 1 as soon as the spot touches the barrier.
+///
+/// Calls pay on touching the barrier from below, puts from above.
+pub fn one_touch_value(
+    option_type: OptionType,
+    spot: Float,
+    barrier: Float,
+    vol: Float,
+    rate: Float,
+    dividends: Float,
+    expiry: Float,
+) -> Float {
+    let eta: Float = match option_type {
+        OptionType::Put => 1.0,
+        OptionType::Call => -1.0,
+    };
+    if eta * (spot - barrier) <= 0.0 {
+        return 1.0;
+    }
+
+    let n = Normal::new();
+
+    let vol_sqrt_expiry = vol * expiry.sqrt();
+    let mu = (rate - dividends - vol.powi(2) / 2.0) / vol.powi(2);
+    let lambda = (mu.powi(2) + 2.0 * rate / vol.powi(2)).sqrt();
+    let z = (barrier / spot).ln() / vol_sqrt_expiry + lambda * vol_sqrt_expiry;
+
+    (barrier / spot).powf(mu + lambda) * n.cdf(eta * z)
+        + (barrier / spot).powf(mu - lambda) * n.cdf(eta * z - 2.0 * eta * lambda * vol_sqrt_expiry)
+}
+
+/// European call or put with a continuously monitored knock-in or knock-out barrier
+/// (Reiner and Rubinstein, 1991).
+///
+/// The rebate of knock-out options is paid on knocking out, that of knock-in options at
+/// expiry if they never knock in, see [`Barrier`].
+#[allow(clippy::too_many_arguments)] // The parameters of `black_value` and the barrier
+pub fn barrier_value(
+    option_type: OptionType,
+    spot: Float,
+    strike: Float,
+    barrier: &Barrier,
+    vol: Float,
+    rate: Float,
+    dividends: Float,
+    expiry: Float,
+) -> Float {
+    if barrier.is_crossed(spot) {
+        return if barrier.is_knock_in() {
+            black_value(option_type, spot, strike, vol, rate, dividends, expiry)
+        } else {
+            barrier.rebate
+        };
+    }
+
+    let n = Normal::new();
+    let phi: Float = match option_type {
+        OptionType::Put => -1.0,
+        OptionType::Call => 1.0,
+    };
+    let (eta, touch_type): (Float, _) = if barrier.is_up() {
+        (-1.0, OptionType::Call)
+    } else {
+        (1.0, OptionType::Put)
+    };
+
+    let level = barrier.level;
+    let vol_sqrt_expiry = vol * expiry.sqrt();
+    let mu = (rate - dividends - vol.powi(2) / 2.0) / vol.powi(2);
+    let shift = (1.0 + mu) * vol_sqrt_expiry;
+    let x1 = (spot / strike).ln() / vol_sqrt_expiry + shift;
+    let x2 = (spot / level).ln() / vol_sqrt_expiry + shift;
+    let y1 = (level.powi(2) / (spot * strike)).ln() / vol_sqrt_expiry + shift;
+    let y2 = (level / spot).ln() / vol_sqrt_expiry + shift;
+
+    let asset = spot * (-dividends * expiry).exp();
+    let cash = strike * (-rate * expiry).exp();
+    let ratio = level / spot;
+    // The terms of the closed forms, in the notation of Haug (2007)
+    let vanilla =
+        |x: Float| phi * asset * n.cdf(phi * x) - phi * cash * n.cdf(phi * (x - vol_sqrt_expiry));
+    let reflected = |y: Float| {
+        phi * asset * ratio.powf(2.0 * (mu + 1.0)) * n.cdf(eta * y)
+            - phi * cash * ratio.powf(2.0 * mu) * n.cdf(eta * (y - vol_sqrt_expiry))
+    };
+    let (a, b, c, d) = (vanilla(x1), vanilla(x2), reflected(y1), reflected(y2));
+    let e = barrier.rebate
+        * (-rate * expiry).exp()
+        * (n.cdf(eta * (x2 - vol_sqrt_expiry))
+            - ratio.powf(2.0 * mu) * n.cdf(eta * (y2 - vol_sqrt_expiry)));
+    let f = barrier.rebate * one_touch_value(touch_type, spot, level, vol, rate, dividends, expiry);
+
+    let strike_beyond_barrier = strike > level;
+    match (barrier.kind, option_type, strike_beyond_barrier) {
+        (BarrierKind::DownAndIn, OptionType::Call, true) => c + e,
+        (BarrierKind::DownAndIn, OptionType::Call, false) => a - b + d + e,
+        (BarrierKind::UpAndIn, OptionType::Call, true) => a + e,
+        (BarrierKind::UpAndIn, OptionType::Call, false) => b - c + d + e,
+        (BarrierKind::DownAndIn, OptionType::Put, true) => b - c + d + e,
+        (BarrierKind::DownAndIn, OptionType::Put, false) => a + e,
+        (BarrierKind::UpAndIn, OptionType::Put, true) => a - b + d + e,
+        (BarrierKind::UpAndIn, OptionType::Put, false) => c + e,
+        (BarrierKind::DownAndOut, OptionType::Call, true) => a - c + f,
+        (BarrierKind::DownAndOut, OptionType::Call, false) => b - d + f,
+        (BarrierKind::UpAndOut, OptionType::Call, true) => f,
+        (BarrierKind::UpAndOut, OptionType::Call, false) => a - b + c - d + f,
+        (BarrierKind::DownAndOut, OptionType::Put, true) => a - b + c - d + f,
+        (BarrierKind::DownAndOut, OptionType::Put, false) => f,
+        (BarrierKind::UpAndOut, OptionType::Put, true) => b - d + f,
+        (BarrierKind::UpAndOut, OptionType::Put, false) => a - c + f,
+    }
+}
+
+/// European option paying `payoff` of the price at expiry.
+///
+/// Integrates the payoff against the lognormal price distribution with the midpoint rule,
+/// over eight standard deviations either side of the mean.
+pub fn payoff_value(
+    payoff: impl Fn(Float) -> Float,
+    spot: Float,
+    vol: Float,
+    rate: Float,
+    dividends: Float,
+    expiry: Float,
+) -> Float {
+    const POINTS: usize = 256;
+    const WIDTH: Float = 8.0;
+
+    let drift = (rate - dividends - vol.powi(2) / 2.0) * expiry;
+    let vol_sqrt_expiry = vol * expiry.sqrt();
+    let step = 2.0 * WIDTH / POINTS as Float;
+
+    let (value, weight) = (0..POINTS)
+        .map(|k| -WIDTH + (k as Float + 0.5) * step)
+        .fold((0.0, 0.0), |(value, weight), z| {
+            let density = (-z * z / 2.0).exp();
+            let price = spot * (drift + vol_sqrt_expiry * z).exp();
+            (value + density * payoff(price), weight + density)
+        });
+
+    (-rate * expiry).exp() * value / weight
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -35,12 +295,204 @@ mod tests {
     #[test]
     fn test_black_call() {
         let val = black_value(OptionType::Call, 100.0, 95.0, 0.3, 0.05, 0.0, 0.5);
-        assert_eq!(val, 12.327911);
+        assert_reference_eq!(val, 12.327911);
     }
 
     #[test]
     fn test_black_put() {
         let val = black_value(OptionType::Put, 95.0, 100.0, 0.3, 0.05, 0.0, 0.5);
-        assert_eq!(val, 9.459187);
+        assert_reference_eq!(val, 9.459187);
+    }
+
+    #[test]
+    fn test_digital_parity() {
+        for option_type in [OptionType::Call, OptionType::Put] {
+            let cash = cash_or_nothing_value(option_type, 100.0, 95.0, 0.3, 0.05, 0.02, 0.5);
+            let asset = asset_or_nothing_value(option_type, 100.0, 95.0, 0.3, 0.05, 0.02, 0.5);
+            let vanilla = black_value(option_type, 100.0, 95.0, 0.3, 0.05, 0.02, 0.5);
+
+            let value = match option_type {
+                OptionType::Call => asset - 95.0 * cash,
+                OptionType::Put => 95.0 * cash - asset,
+            };
+            assert!((value - vanilla).abs() < 1e-4);
+        }
+
+        let call = cash_or_nothing_value(OptionType::Call, 100.0, 95.0, 0.3, 0.05, 0.02, 0.5);
+        let put = cash_or_nothing_value(OptionType::Put, 100.0, 95.0, 0.3, 0.05, 0.02, 0.5);
+        assert!((call + put - (-0.05 * 0.5 as Float).exp()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_one_touch() {
+        // Touched already
+        assert_eq!(
+            one_touch_value(OptionType::Call, 100.0, 95.0, 0.3, 0.05, 0.0, 0.5),
+            1.0
+        );
+
+        // Touching is about twice as likely as expiring beyond the barrier
+        let touch = one_touch_value(OptionType::Call, 100.0, 110.0, 0.3, 0.0, 0.0, 0.5);
+        let digital = cash_or_nothing_value(OptionType::Call, 100.0, 110.0, 0.3, 0.0, 0.0, 0.5);
+        assert!(touch > 1.8 * digital && touch < 2.2 * digital);
+
+        let touch = one_touch_value(OptionType::Put, 100.0, 90.0, 0.3, 0.05, 0.0, 0.5);
+        assert!(touch > 0.0 && touch < 1.0);
+    }
+
+    #[test]
+    fn test_barrier_value() {
+        use crate::instruments::Monitoring;
+
+        let value = |option_type, kind, strike, level| {
+            let barrier = Barrier {
+                kind,
+                level,
+                rebate: 3.0,
+                monitoring: Monitoring::Continuous,
+            };
+            barrier_value(option_type, 100.0, strike, &barrier, 0.25, 0.08, 0.04, 0.5)
+        };
+        // Haug (2007), table 4-13, with a volatility of 25%
+        let (call, put) = (OptionType::Call, OptionType::Put);
+        let cases = [
+            (
+                call,
+                BarrierKind::DownAndOut,
+                95.0,
+                [9.0246, 6.7924, 4.8759],
+            ),
+            (call, BarrierKind::DownAndOut, 100.0, [3.0, 3.0, 3.0]),
+            (call, BarrierKind::UpAndOut, 105.0, [2.6789, 2.3580, 2.3453]),
+            (call, BarrierKind::DownAndIn, 95.0, [7.7627, 4.0109, 2.0576]),
+            (
+                call,
+                BarrierKind::DownAndIn,
+                100.0,
+                [13.8333, 7.8494, 3.9795],
+            ),
+            (call, BarrierKind::UpAndIn, 105.0, [14.1112, 8.4482, 4.5910]),
+            (put, BarrierKind::DownAndOut, 95.0, [2.2798, 2.2947, 2.6252]),
+            (put, BarrierKind::UpAndOut, 105.0, [3.7760, 5.4932, 7.5187]),
+            (put, BarrierKind::DownAndIn, 95.0, [2.9586, 6.5677, 11.9752]),
+            (put, BarrierKind::UpAndIn, 105.0, [1.4653, 3.3721, 7.0846]),
+        ];
+        for (option_type, kind, level, expected) in cases {
+            for (strike, expected) in [90.0, 100.0, 110.0].into_iter().zip(expected) {
+                let value = value(option_type, kind, strike, level);
+                assert!(
+                    (value - expected).abs() < 1e-3,
+                    "{option_type:?} {kind:?} {strike} {level}: {value} != {expected}"
+                );
+            }
+        }
+
+        // Without rebate, knocking in and knocking out add up to the vanilla option
+        for (option_type, (out, knock_in), level) in [
+            (
+                call,
+                (BarrierKind::DownAndOut, BarrierKind::DownAndIn),
+                95.0,
+            ),
+            (put, (BarrierKind::UpAndOut, BarrierKind::UpAndIn), 105.0),
+        ] {
+            for strike in [90.0, 100.0, 110.0] {
+                let value = |kind| {
+                    let barrier = Barrier {
+                        kind,
+                        level,
+                        rebate: 0.0,
+                        monitoring: Monitoring::Continuous,
+                    };
+                    barrier_value(option_type, 100.0, strike, &barrier, 0.25, 0.08, 0.04, 0.5)
+                };
+                let vanilla = black_value(option_type, 100.0, strike, 0.25, 0.08, 0.04, 0.5);
+                assert!((value(out) + value(knock_in) - vanilla).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn test_payoff_value() {
+        let call = |price: Float| (price - 95.0).max(0.0);
+        let value = payoff_value(call, 100.0, 0.3, 0.05, 0.02, 0.5);
+        let vanilla = black_value(OptionType::Call, 100.0, 95.0, 0.3, 0.05, 0.02, 0.5);
+        assert!((value - vanilla).abs() < 1e-3);
+
+        // Forward
+        let value = payoff_value(|price| price, 100.0, 0.3, 0.05, 0.02, 0.5);
+        assert!((value - 100.0 * (-0.02 * 0.5 as Float).exp()).abs() < 1e-3);
+
+        // No time left
+        assert!((payoff_value(call, 100.0, 0.3, 0.05, 0.02, 0.0) - 5.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_black_greeks() {
+        let (spot, vol, rate, dividends, expiry) = (100.0, 0.3, 0.05, 0.02, 0.5);
+        for (option_type, strike) in [
+            (OptionType::Call, 95.0),
+            (OptionType::Put, 105.0),
+            (OptionType::Put, 80.0),
+        ] {
+            let greeks = black_greeks(option_type, spot, strike, vol, rate, dividends, expiry);
+            let value = |spot, vol, rate, dividends, expiry| {
+                black_value(option_type, spot, strike, vol, rate, dividends, expiry)
+            };
+            let delta = |spot, vol, expiry| {
+                black_greeks(option_type, spot, strike, vol, rate, dividends, expiry).delta
+            };
+            let vega =
+                |vol| black_greeks(option_type, spot, strike, vol, rate, dividends, expiry).vega;
+            let central =
+                |f: &dyn Fn(Float) -> Float, x: Float, h: Float| (f(x + h) - f(x - h)) / (2.0 * h);
+            let close = |analytic: Float, numeric: Float| {
+                assert!(
+                    (analytic - numeric).abs() < 2e-3 * (1.0 + analytic.abs()),
+                    "{option_type:?} {strike}: {analytic} vs {numeric}"
+                )
+            };
+
+            close(greeks.value, value(spot, vol, rate, dividends, expiry));
+            close(
+                greeks.delta,
+                central(&|s| value(s, vol, rate, dividends, expiry), spot, 0.5),
+            );
+            close(greeks.gamma, central(&|s| delta(s, vol, expiry), spot, 0.5));
+            close(
+                greeks.vega,
+                central(&|v| value(spot, v, rate, dividends, expiry), vol, 0.01),
+            );
+            close(
+                greeks.theta,
+                -central(&|t| value(spot, vol, rate, dividends, t), expiry, 0.01),
+            );
+            close(
+                greeks.rho,
+                central(&|r| value(spot, vol, r, dividends, expiry), rate, 0.005),
+            );
+            close(
+                greeks.psi,
+                central(&|q| value(spot, vol, rate, q, expiry), dividends, 0.005),
+            );
+            close(
+                greeks.vanna,
+                central(&|v| delta(spot, v, expiry), vol, 0.002),
+            );
+            close(greeks.volga, central(&vega, vol, 0.002));
+            close(
+                greeks.charm,
+                -central(&|t| delta(spot, vol, t), expiry, 0.01),
+            );
+        }
+
+        // Put-call parity
+        let call = black_greeks(OptionType::Call, spot, 100.0, vol, rate, dividends, expiry);
+        let put = black_greeks(OptionType::Put, spot, 100.0, vol, rate, dividends, expiry);
+        assert!((call.delta - put.delta - (-dividends * expiry as Float).exp()).abs() < 1e-6);
+        assert_eq!(call.gamma, put.gamma);
+        assert_eq!(call.vega, put.vega);
+        assert_eq!(call.vanna, put.vanna);
+        assert_eq!(call.volga, put.volga);
     }
 }
@@ -1,2 +1,122 @@
+pub mod barone_adesi_whaley;
+pub mod bjerksund_stensland;
 pub mod black_scholes;
-mod normal_distribution;
+pub(crate) mod normal_distribution;
+
+use crate::Float;
+use crate::black_scholes::BlackGreeks;
+use crate::instruments::OptionType;
+use crate::model::Bumps;
+
+/// Analytic approximation of American calls and puts.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AmericanApproximation {
+    /// Barone-Adesi and Whaley (1987), see [`barone_adesi_whaley`]
+    BaroneAdesiWhaley,
+    /// Bjerksund and Stensland (2002), see [`bjerksund_stensland`]
+    BjerksundStensland,
+}
+
+impl AmericanApproximation {
+    /// The `american_value` function of the approximation.
+    pub fn american_value(
+        self,
+    ) -> fn(OptionType, Float, Float, Float, Float, Float, Float) -> Float {
+        match self {
+            AmericanApproximation::BaroneAdesiWhaley => barone_adesi_whaley::american_value,
+            AmericanApproximation::BjerksundStensland => bjerksund_stensland::american_value,
+        }
+    }
+}
+
+/// Greeks of `value(spot, vol, rate, dividends, expiry)` by central differences, in the
+/// [`BlackGreeks`] of the closed-form values.
+///
+/// Spot and expiry are bumped by one percent, the other parameters by the default
+/// [`Bumps`] of the trees. Theta and charm are derivatives in calendar time, as for the
+/// trees.
+pub(crate) fn finite_difference_greeks(
+    value: impl Fn(Float, Float, Float, Float, Float) -> Float,
+    spot: Float,
+    vol: Float,
+    rate: Float,
+    dividends: Float,
+    expiry: Float,
+) -> BlackGreeks {
+    let bumps = Bumps::default();
+    let difference = bumps.difference;
+    let center = value(spot, vol, rate, dividends, expiry);
+
+    let h = 0.01 * spot;
+    let up = value(spot + h, vol, rate, dividends, expiry);
+    let down = value(spot - h, vol, rate, dividends, expiry);
+    let delta_at = |vol, expiry| {
+        (value(spot + h, vol, rate, dividends, expiry)
+            - value(spot - h, vol, rate, dividends, expiry))
+            / (2.0 * h)
+    };
+    let (k, e) = (bumps.volatility, 0.01 * expiry);
+
+    BlackGreeks {
+        value: center,
+        delta: (up - down) / (2.0 * h),
+        gamma: (up - 2.0 * center + down) / h.powi(2),
+        vega: difference.derivative(vol, k, center, |vol| {
+            value(spot, vol, rate, dividends, expiry)
+        }),
+        theta: -difference.derivative(expiry, e, center, |expiry| {
+            value(spot, vol, rate, dividends, expiry)
+        }),
+        rho: difference.derivative(rate, bumps.interest_rate, center, |rate| {
+            value(spot, vol, rate, dividends, expiry)
+        }),
+        psi: difference.derivative(dividends, bumps.dividends, center, |dividends| {
+            value(spot, vol, rate, dividends, expiry)
+        }),
+        vanna: (delta_at(vol + k, expiry) - delta_at(vol - k, expiry)) / (2.0 * k),
+        volga: (value(spot, vol + k, rate, dividends, expiry) - 2.0 * center
+            + value(spot, vol - k, rate, dividends, expiry))
+            / k.powi(2),
+        charm: -(delta_at(vol, expiry + e) - delta_at(vol, expiry - e)) / (2.0 * e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::black_scholes::{black_greeks, black_value};
+
+    #[test]
+    fn test_finite_difference_greeks() {
+        for option_type in [OptionType::Call, OptionType::Put] {
+            let greeks = finite_difference_greeks(
+                |spot, vol, rate, dividends, expiry| {
+                    black_value(option_type, spot, 105.0, vol, rate, dividends, expiry)
+                },
+                100.0,
+                0.3,
+                0.05,
+                0.02,
+                0.5,
+            );
+            let black = black_greeks(option_type, 100.0, 105.0, 0.3, 0.05, 0.02, 0.5);
+
+            // Single precision rounding shows in the second differences in the volatility
+            let pairs = [
+                (greeks.value, black.value, 2e-3),
+                (greeks.delta, black.delta, 2e-3),
+                (greeks.gamma, black.gamma, 2e-3),
+                (greeks.vega, black.vega, 2e-3),
+                (greeks.theta, black.theta, 2e-3),
+                (greeks.rho, black.rho, 2e-3),
+                (greeks.psi, black.psi, 2e-3),
+                (greeks.vanna, black.vanna, 2e-3),
+                (greeks.volga, black.volga, 5e-2),
+                (greeks.charm, black.charm, 2e-3),
+            ];
+            for (difference, closed_form, tolerance) in pairs {
+                assert!((difference - closed_form).abs() < tolerance * closed_form.abs().max(1.0));
+            }
+        }
+    }
+}
@@ -1,4 +1,6 @@
-use statrs::distribution::ContinuousCDF;
+use crate::Float;
+use statrs::distribution::{Continuous, ContinuousCDF};
+use std::f64::consts::PI;
 
 pub(crate) struct Normal {
     normal: statrs::distribution::Normal,
@@ -11,7 +13,179 @@ impl Normal {
         }
     }
 
-    pub(crate) fn cdf(&self, x: f64) -> f64 {
-        self.normal.cdf(x)
+    #[allow(clippy::useless_conversion)] // Identity conversion with the `f64` feature
+    pub(crate) fn cdf(&self, x: Float) -> Float {
+        self.normal.cdf(x.into()) as Float
+    }
+
+    #[allow(clippy::useless_conversion)] // Identity conversion with the `f64` feature
+    pub(crate) fn pdf(&self, x: Float) -> Float {
+        self.normal.pdf(x.into()) as Float
+    }
+
+    /// Probability that two standard normal variables with correlation `rho` are below `x`
+    /// and `y`.
+    ///
+    /// Genz's (2004) algorithm, evaluated in double precision whatever [`Float`] is.
+    #[allow(clippy::useless_conversion)] // Identity conversion with the `f64` feature
+    pub(crate) fn bivariate_cdf(&self, x: Float, y: Float, rho: Float) -> Float {
+        self.bivariate_cdf_f64(x.into(), y.into(), rho.into()) as Float
+    }
+
+    fn bivariate_cdf_f64(&self, x: f64, y: f64, rho: f64) -> f64 {
+        let (nodes, weights) = gauss_legendre(rho);
+        let (h, mut k) = (-x, -y);
+        let mut hk = h * k;
+
+        if rho.abs() < 0.925 {
+            let hs = (h * h + k * k) / 2.0;
+            let asr = rho.asin();
+            let sum: f64 = nodes
+                .iter()
+                .zip(weights)
+                .flat_map(|(node, weight)| [(*node, weight), (-node, weight)])
+                .map(|(node, weight)| {
+                    let sn = (asr * (node + 1.0) / 2.0).sin();
+                    weight * ((sn * hk - hs) / (1.0 - sn * sn)).exp()
+                })
+                .sum();
+            return sum * asr / (4.0 * PI) + self.normal.cdf(-h) * self.normal.cdf(-k);
+        }
+
+        if rho < 0.0 {
+            k = -k;
+            hk = -hk;
+        }
+        let mut bvn = 0.0;
+        if rho.abs() < 1.0 {
+            let a_squared = (1.0 - rho) * (1.0 + rho);
+            let mut a = a_squared.sqrt();
+            let b_squared = (h - k).powi(2);
+            let c = (4.0 - hk) / 8.0;
+            let d = (12.0 - hk) / 16.0;
+            bvn = a
+                * (-(b_squared / a_squared + hk) / 2.0).exp()
+                * (1.0 - c * (b_squared - a_squared) * (1.0 - d * b_squared / 5.0) / 3.0
+                    + c * d * a_squared * a_squared / 5.0);
+            if hk > -160.0 {
+                let b = b_squared.sqrt();
+                bvn -= (-hk / 2.0).exp()
+                    * (2.0 * PI).sqrt()
+                    * self.normal.cdf(-b / a)
+                    * b
+                    * (1.0 - c * b_squared * (1.0 - d * b_squared / 5.0) / 3.0);
+            }
+            a /= 2.0;
+            for (node, weight) in nodes.iter().zip(weights) {
+                let xs = (a * (node + 1.0)).powi(2);
+                let rs = (1.0 - xs).sqrt();
+                bvn += a
+                    * weight
+                    * ((-b_squared / (2.0 * xs) - hk / (1.0 + rs)).exp() / rs
+                        - (-(b_squared / xs + hk) / 2.0).exp() * (1.0 + c * xs * (1.0 + d * xs)));
+
+                let xs = a_squared * (1.0 - node).powi(2) / 4.0;
+                let rs = (1.0 - xs).sqrt();
+                bvn += a
+                    * weight
+                    * (-(b_squared / xs + hk) / 2.0).exp()
+                    * ((-hk * (1.0 - rs) / (2.0 * (1.0 + rs))).exp() / rs
+                        - (1.0 + c * xs * (1.0 + d * xs)));
+            }
+            bvn = -bvn / (2.0 * PI);
+        }
+
+        if rho > 0.0 {
+            bvn + self.normal.cdf(-h.max(k))
+        } else {
+            -bvn + (self.normal.cdf(-h) - self.normal.cdf(-k)).max(0.0)
+        }
+    }
+}
+
+/// Negative Gauss-Legendre nodes and their weights, with more nodes for stronger correlation.
+fn gauss_legendre(rho: f64) -> (&'static [f64], &'static [f64]) {
+    const NODES_3: [f64; 3] = [-0.9324695142031522, -0.6612093864662647, -0.238619186083197];
+    const WEIGHTS_3: [f64; 3] = [0.1713244923791705, 0.3607615730481384, 0.4679139345726904];
+    const NODES_6: [f64; 6] = [
+        -0.9815606342467191,
+        -0.904117256370475,
+        -0.769902674194305,
+        -0.5873179542866171,
+        -0.3678314989981802,
+        -0.1252334085114692,
+    ];
+    const WEIGHTS_6: [f64; 6] = [
+        0.04717533638651177,
+        0.1069393259953183,
+        0.1600783285433464,
+        0.2031674267230659,
+        0.2334925365383547,
+        0.2491470458134029,
+    ];
+    const NODES_10: [f64; 10] = [
+        -0.9931285991850949,
+        -0.9639719272779138,
+        -0.912234428251326,
+        -0.8391169718222188,
+        -0.7463319064601508,
+        -0.636053680726515,
+        -0.5108670019508271,
+        -0.3737060887154196,
+        -0.2277858511416451,
+        -0.07652652113349732,
+    ];
+    const WEIGHTS_10: [f64; 10] = [
+        0.01761400713915212,
+        0.04060142980038694,
+        0.06267204833410905,
+        0.08327674157670475,
+        0.1019301198172404,
+        0.1181945319615184,
+        0.1316886384491766,
+        0.1420961093183821,
+        0.1491729864726037,
+        0.1527533871307259,
+    ];
+
+    if rho.abs() < 0.3 {
+        (&NODES_3, &WEIGHTS_3)
+    } else if rho.abs() < 0.75 {
+        (&NODES_6, &WEIGHTS_6)
+    } else {
+        (&NODES_10, &WEIGHTS_10)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bivariate_cdf() {
+        let n = Normal::new();
+
+        // Closed form at the origin
+        for rho in [-0.99_f64, -0.95, -0.5, -0.1, 0.0, 0.1, 0.5, 0.8, 0.95, 0.99] {
+            let expected = 0.25 + rho.asin() / (2.0 * std::f64::consts::PI);
+            assert!((n.bivariate_cdf_f64(0.0, 0.0, rho) - expected).abs() < 1e-12);
+        }
+
+        // Independent variables
+        let expected = n.normal.cdf(0.5) * n.normal.cdf(-0.3);
+        assert!((n.bivariate_cdf_f64(0.5, -0.3, 0.0) - expected).abs() < 1e-12);
+
+        // Perfectly correlated variables
+        assert!((n.bivariate_cdf_f64(0.3, -0.2, 1.0) - n.normal.cdf(-0.2)).abs() < 1e-12);
+        let expected = n.normal.cdf(0.3) - n.normal.cdf(-0.2);
+        assert!((n.bivariate_cdf_f64(0.3, 0.2, -1.0) - expected).abs() < 1e-12);
+
+        // Symmetry and the marginals
+        for (x, y, rho) in [(0.3, -0.2, 0.96), (1.0, 0.5, -0.97), (-0.4, 0.2, 0.6)] {
+            let m = n.bivariate_cdf_f64(x, y, rho);
+            assert!((m - n.bivariate_cdf_f64(y, x, rho)).abs() < 1e-12);
+            let complement = n.bivariate_cdf_f64(x, -y, -rho);
+            assert!((m + complement - n.normal.cdf(x)).abs() < 1e-12);
+        }
     }
 }
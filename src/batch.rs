@@ -0,0 +1,299 @@
+//! Batch pricing of vanilla options, e.g. for end-of-day valuation of a whole book.
+//!
+//! Every record is priced on its own Cox-Ross-Rubinstein tree with the Black-Scholes leaf
+//! smoothing and border truncation of the `eval_binomial_tree!` macros. Records are spread
+//! over the rayon thread pool, and each thread reuses the node value maps and node stacks
+//! of its [`Workspace`] from one record to the next.
+//!
+//! Records are checked as by [`BinomialTreeModel::try_new`](crate::model::BinomialTreeModel::try_new)
+//! and [`BinomialTreeModel::try_eval`](crate::model::BinomialTreeModel::try_eval), so that a bad record gives an error in its place
+//! instead of failing the whole batch.
+
+use crate::Float;
+use crate::binomial_tree_map::array::ArrayBinomialTreeMap;
+use crate::error::Error;
+use crate::instruments::{
+    AmericanOption, BermudanOption, EuropeanOption, Exercise, OptionContract, OptionType,
+    StrikeContract,
+};
+use crate::model::{
+    CoxRossRubenstein, EvaluatedBinomialTree, Expiry, Greeks, Spot, Value, Workspace,
+    border_truncation, leaf_smoothing,
+};
+
+use rayon::prelude::*;
+
+/// Vanilla option and the market it is priced in.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatchRecord {
+    pub exercise: Exercise,
+    pub option_type: OptionType,
+    pub strike: Float,
+    pub spot: Float,
+    /// Time to expiration (years)
+    pub expiry: Float,
+    pub volatility: Float,
+    pub interest_rate: Float,
+    /// Continuous dividend yield
+    pub dividends: Float,
+    /// Number of tree steps
+    pub steps: usize,
+}
+
+/// Values of the records, in the same order.
+pub fn batch_values(records: &[BatchRecord]) -> Vec<Result<Value, Error>> {
+    batch(records, |eval| eval.value())
+}
+
+/// Greeks of the records, in the same order.
+pub fn batch_greeks(records: &[BatchRecord]) -> Vec<Result<Greeks, Error>> {
+    batch(records, |eval| eval.greeks())
+}
+
+fn batch<R: Send>(
+    records: &[BatchRecord],
+    result: impl Fn(&dyn EvaluatedBinomialTree) -> R + Sync,
+) -> Vec<Result<R, Error>> {
+    records
+        .par_iter()
+        .map(|record| {
+            let (option_type, strike, expiry) = (record.option_type, record.strike, record.expiry);
+            match &record.exercise {
+                Exercise::European => evaluate(
+                    record,
+                    EuropeanOption::new(option_type, strike, expiry),
+                    &result,
+                ),
+                Exercise::American => evaluate(
+                    record,
+                    AmericanOption::new(option_type, strike, expiry),
+                    &result,
+                ),
+                Exercise::Bermudan(exercise_times) => {
+                    let option = BermudanOption::with_exercise_times(
+                        option_type,
+                        strike,
+                        expiry,
+                        exercise_times.clone(),
+                    );
+                    evaluate(record, option, &result)
+                }
+            }
+        })
+        .collect()
+}
+
+/// Evaluates `option` on the tree of `record` with the workspace of the current thread.
+fn evaluate<T: OptionContract + Sync, R>(
+    record: &BatchRecord,
+    option: T,
+    result: impl Fn(&dyn EvaluatedBinomialTree) -> R,
+) -> Result<R, Error> {
+    Workspace::with_thread_local(|workspace| {
+        let model: CoxRossRubenstein<
+            ArrayBinomialTreeMap,
+            leaf_smoothing::Black,
+            border_truncation::Black,
+        > = CoxRossRubenstein::try_new(
+            workspace.take_stack(record.steps),
+            Spot(record.spot),
+            record.steps,
+            Expiry(record.expiry),
+            record.volatility,
+            record.interest_rate,
+            record.dividends,
+        )?;
+
+        let eval = model.try_eval_in(option, workspace)?;
+        let result = result(&eval);
+        eval.recycle(workspace);
+        Ok(result)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval_binomial_tree_with_steps;
+    use pretty_assertions::assert_eq;
+
+    fn record(exercise: Exercise, option_type: OptionType, strike: Float) -> BatchRecord {
+        BatchRecord {
+            exercise,
+            option_type,
+            strike,
+            spot: 100.0,
+            expiry: 0.5,
+            volatility: 0.3,
+            interest_rate: 0.05,
+            dividends: 0.02,
+            steps: 100,
+        }
+    }
+
+    #[test]
+    fn test_batch_values() {
+        let records = [
+            record(Exercise::American, OptionType::Call, 95.0),
+            record(Exercise::European, OptionType::Put, 105.0),
+            record(Exercise::American, OptionType::Put, 105.0),
+            record(Exercise::European, OptionType::Call, 95.0),
+        ];
+
+        assert_eq!(
+            batch_values(&records),
+            vec![
+                Ok(eval_binomial_tree_with_steps!(
+                    100,
+                    AmericanOption,
+                    Call,
+                    95.0,
+                    100.0,
+                    0.5,
+                    0.3,
+                    0.05,
+                    0.02
+                )
+                .value()),
+                Ok(eval_binomial_tree_with_steps!(
+                    100,
+                    EuropeanOption,
+                    Put,
+                    105.0,
+                    100.0,
+                    0.5,
+                    0.3,
+                    0.05,
+                    0.02
+                )
+                .value()),
+                Ok(eval_binomial_tree_with_steps!(
+                    100,
+                    AmericanOption,
+                    Put,
+                    105.0,
+                    100.0,
+                    0.5,
+                    0.3,
+                    0.05,
+                    0.02
+                )
+                .value()),
+                Ok(eval_binomial_tree_with_steps!(
+                    100,
+                    EuropeanOption,
+                    Call,
+                    95.0,
+                    100.0,
+                    0.5,
+                    0.3,
+                    0.05,
+                    0.02
+                )
+                .value()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_batch_greeks() {
+        let records: Vec<_> = (0..64)
+            .map(|k| {
+                let exercise = match k % 3 {
+                    0 => Exercise::American,
+                    1 => Exercise::European,
+                    _ => Exercise::Bermudan(vec![0.125, 0.25, 0.375]),
+                };
+                let option_type = if k % 2 == 0 {
+                    OptionType::Call
+                } else {
+                    OptionType::Put
+                };
+                BatchRecord {
+                    steps: 50 + k,
+                    ..record(exercise, option_type, 80.0 + k as Float)
+                }
+            })
+            .collect();
+
+        let greeks = batch_greeks(&records);
+        assert_eq!(greeks.len(), records.len());
+        for (record, greeks) in records.iter().zip(greeks) {
+            let greeks = greeks.unwrap();
+            let model: CoxRossRubenstein<
+                ArrayBinomialTreeMap,
+                leaf_smoothing::Black,
+                border_truncation::Black,
+            > = CoxRossRubenstein::new(
+                ArrayBinomialTreeMap::new(record.steps),
+                Spot(100.0),
+                record.steps,
+                Expiry(0.5),
+                0.3,
+                0.05,
+                0.02,
+            );
+            let (option_type, strike) = (record.option_type, record.strike);
+            let expected = match &record.exercise {
+                Exercise::European => model
+                    .eval(EuropeanOption::new(option_type, strike, 0.5))
+                    .greeks(),
+                Exercise::American => model
+                    .eval(AmericanOption::new(option_type, strike, 0.5))
+                    .greeks(),
+                Exercise::Bermudan(times) => model
+                    .eval(BermudanOption::with_exercise_times(
+                        option_type,
+                        strike,
+                        0.5,
+                        times.clone(),
+                    ))
+                    .greeks(),
+            };
+            assert_eq!(greeks, expected);
+        }
+
+        // Exercising early adds value to puts
+        let put = |exercise| record(exercise, OptionType::Put, 105.0);
+        let values = batch_values(&[
+            put(Exercise::European),
+            put(Exercise::Bermudan(vec![0.25])),
+            put(Exercise::American),
+        ]);
+        let values: Vec<_> = values.into_iter().map(|value| value.unwrap().0).collect();
+        assert!(values[0] < values[1] && values[1] < values[2]);
+    }
+
+    #[test]
+    fn test_batch_bad_record() {
+        let good = record(Exercise::American, OptionType::Put, 105.0);
+        let records = [
+            good.clone(),
+            BatchRecord {
+                strike: -105.0,
+                ..good.clone()
+            },
+            BatchRecord {
+                volatility: 0.0,
+                ..good.clone()
+            },
+            BatchRecord {
+                steps: 1,
+                ..good.clone()
+            },
+            good.clone(),
+        ];
+
+        // A bad record gives its error without failing the others
+        let values = batch_values(&records);
+        assert_eq!(values[1], Err(Error::NonPositiveStrike(-105.0)));
+        assert_eq!(values[2], Err(Error::NonPositiveVolatility(0.0)));
+        assert!(matches!(
+            values[3],
+            Err(Error::TooFewSteps { steps: 1, .. })
+        ));
+        assert_eq!(values[0], batch_values(&[good])[0]);
+        assert_eq!(values[4], values[0]);
+        assert!(values[0].is_ok());
+    }
+}
@@ -0,0 +1,129 @@
+use crate::binomial_tree_map::nodes::{NodeIndex, NodeNameTrait};
+use crate::binomial_tree_map::{
+    BinomialTreeMapImpl, BinomialTreeMapNumericType, BinomialTreeStackBuilder,
+    BinomialTreeStackImpl,
+};
+use std::ops::Deref;
+
+/// Binomial tree addressing the nodes by step and number of up moves instead of by name.
+///
+/// The node values are kept in a flat array, level after level, so looking a node up is
+/// an index computation rather than a hash.
+#[derive(Default, Debug)]
+pub struct ArrayBinomialTreeMap {
+    stack: Vec<Vec<NodeIndex>>,
+}
+
+impl ArrayBinomialTreeMap {
+    pub fn new(number_of_steps: usize) -> Self {
+        // Sorted from the highest to the lowest price, as in the other stacks
+        let stack = (0..=number_of_steps)
+            .map(|step| {
+                (0..=step)
+                    .rev()
+                    .map(|ups| NodeIndex { step, ups })
+                    .collect()
+            })
+            .collect();
+
+        Self { stack }
+    }
+}
+
+/// Node values of an [`ArrayBinomialTreeMap`], grown as the levels are set.
+#[derive(Default, Debug)]
+pub struct ArrayContainer {
+    values: Vec<BinomialTreeMapNumericType>,
+    // Border truncation leaves nodes without a value
+    is_set: Vec<bool>,
+}
+
+impl BinomialTreeMapImpl for ArrayContainer {
+    type NodeNameType = NodeIndex;
+    type NumericType = BinomialTreeMapNumericType;
+    type ValueType = BinomialTreeMapNumericType;
+
+    fn get(&self, node_name: &Self::NodeNameType) -> Option<&Self::ValueType> {
+        let position = node_name.position();
+        match self.is_set.get(position) {
+            Some(true) => Some(&self.values[position]),
+            _ => None,
+        }
+    }
+
+    fn get_next_step(&self, node_name: &Self::NodeNameType) -> Option<&Self::ValueType> {
+        // Two steps later at the same price, as in the static map
+        self.get(&node_name.up().down())
+    }
+
+    fn set(&mut self, node_name: &Self::NodeNameType, value: Self::NumericType) {
+        let position = node_name.position();
+        if position >= self.values.len() {
+            self.values.resize(position + 1, 0.0);
+            self.is_set.resize(position + 1, false);
+        }
+
+        self.values[position] = value;
+        self.is_set[position] = true;
+    }
+
+    fn clear(&mut self) {
+        self.is_set.fill(false);
+    }
+}
+
+impl BinomialTreeStackImpl for ArrayBinomialTreeMap {
+    type NodeNameContainerType = ArrayContainer;
+
+    fn iter(&self) -> impl DoubleEndedIterator +
+        ExactSizeIterator<
+            Item=&impl Deref<
+                Target=[<<Self as BinomialTreeStackImpl>::NodeNameContainerType as BinomialTreeMapImpl>::NodeNameType]
+            >
+    >{
+        self.stack.iter()
+    }
+}
+
+impl BinomialTreeStackBuilder for ArrayBinomialTreeMap {
+    fn with_steps(number_of_steps: usize) -> Self {
+        Self::new(number_of_steps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stack_structure() {
+        let tree = ArrayBinomialTreeMap::new(2);
+
+        assert_eq!(tree.stack.len(), 3);
+        assert_eq!(tree.stack[0], vec![NodeIndex::default()]);
+        assert_eq!(
+            tree.stack[2],
+            vec![
+                NodeIndex { step: 2, ups: 2 },
+                NodeIndex { step: 2, ups: 1 },
+                NodeIndex { step: 2, ups: 0 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_container() {
+        let mut container = ArrayContainer::default();
+        let node = NodeIndex::default();
+
+        container.set(&node.up().down(), 3.0);
+        container.set(&node, 1.0);
+
+        assert_eq!(container.get(&node), Some(&1.0));
+        assert_eq!(container.get(&node.down().up()), Some(&3.0));
+        assert_eq!(container.get_next_step(&node), Some(&3.0));
+        // Not set, inside and beyond the array
+        assert_eq!(container.get(&node.up()), None);
+        assert_eq!(container.get(&node.up().up().up()), None);
+    }
+}
@@ -1,7 +1,8 @@
 use crate::binomial_tree_map::capacity::{calculate_capacity, calculate_step_capacity};
 use crate::binomial_tree_map::nodes::{ALL_UPDOWNS, NodeName, NodeNameTrait};
 use crate::binomial_tree_map::{
-    BinomialTreeMapImpl, BinomialTreeMapNumericType, BinomialTreeMapValue, BinomialTreeStackImpl,
+    BinomialTreeMapImpl, BinomialTreeMapNumericType, BinomialTreeMapValue,
+    BinomialTreeStackBuilder, BinomialTreeStackImpl,
 };
 use hashbrown::HashMap;
 use itertools::Itertools;
@@ -41,7 +42,7 @@ impl DynamicBinomialTreeMap {
 
 impl BinomialTreeMapImpl for DynamicBinomialTreeMap {
     type NodeNameType = NodeName;
-    type NumericType = f32;
+    type NumericType = BinomialTreeMapNumericType;
     type ValueType = BinomialTreeMapValue<Self::NumericType>;
 
     fn get(&self, node_name: &Self::NodeNameType) -> Option<&Self::ValueType> {
@@ -59,6 +60,10 @@ impl BinomialTreeMapImpl for DynamicBinomialTreeMap {
             .set(value)
             .unwrap();
     }
+
+    fn clear(&mut self) {
+        self.map.clear();
+    }
 }
 
 impl BinomialTreeStackImpl for DynamicBinomialTreeMap {
@@ -74,6 +79,12 @@ impl BinomialTreeStackImpl for DynamicBinomialTreeMap {
     }
 }
 
+impl BinomialTreeStackBuilder for DynamicBinomialTreeMap {
+    fn with_steps(number_of_steps: usize) -> Self {
+        Self::new(number_of_steps)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::binomial_tree_map::dynamic::DynamicBinomialTreeMap;
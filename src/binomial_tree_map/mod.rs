@@ -1,3 +1,4 @@
+use crate::Float;
 use nodes::NodeNameTrait;
 
 use std::cell::OnceCell;
@@ -5,23 +6,39 @@ use std::fmt::Debug;
 use std::hash::Hash;
 use std::ops::Deref;
 
+pub mod array;
 mod capacity;
 pub mod dynamic; // TODO: Visibility
 pub(crate) mod nodes;
 pub mod r#static; // TODO: Visibility
+pub mod trinomial;
 
-pub(crate) type BinomialTreeMapNumericType = f32;
+pub(crate) type BinomialTreeMapNumericType = Float;
 pub(crate) type BinomialTreeMapValue<T> = OnceCell<T>;
 pub(crate) type BinomTreeValueType = BinomialTreeMapValue<BinomialTreeMapNumericType>;
 
 pub(crate) trait BinomialTreeMapImpl {
     type NodeNameType: NodeNameTrait + Debug + Hash + Default;
-    type NumericType: From<f32> + Into<f32>;
+    type NumericType: From<Float> + Into<Float>;
     type ValueType: GetValue;
 
     fn get(&self, node_name: &Self::NodeNameType) -> Option<&Self::ValueType>;
     fn get_next_step(&self, node_name: &Self::NodeNameType) -> Option<&Self::ValueType>;
     fn set(&mut self, node_name: &Self::NodeNameType, value: Self::NumericType);
+    /// Removes all values, keeping the allocated memory.
+    fn clear(&mut self);
+
+    /// Value of the node, if the induction has set one.
+    fn value(&self, node_name: &Self::NodeNameType) -> Option<Float> {
+        self.get(node_name).and_then(GetValue::get).copied()
+    }
+
+    /// Value of the node two steps later at the same price, if the induction has set one.
+    fn next_step_value(&self, node_name: &Self::NodeNameType) -> Option<Float> {
+        self.get_next_step(node_name)
+            .and_then(GetValue::get)
+            .copied()
+    }
 }
 
 pub(crate) trait BinomialTreeStackImpl {
@@ -30,13 +47,33 @@ pub(crate) trait BinomialTreeStackImpl {
     fn iter(&self) -> impl DoubleEndedIterator + ExactSizeIterator<Item=&impl Deref<Target=[<<Self as BinomialTreeStackImpl>::NodeNameContainerType as BinomialTreeMapImpl>::NodeNameType]>>;
 }
 
+/// Shares a stack between several models, e.g. when re-evaluating bumped trees.
+impl<S: BinomialTreeStackImpl> BinomialTreeStackImpl for &S {
+    type NodeNameContainerType = S::NodeNameContainerType;
+
+    fn iter(&self) -> impl DoubleEndedIterator + ExactSizeIterator<Item=&impl Deref<Target=[<<Self as BinomialTreeStackImpl>::NodeNameContainerType as BinomialTreeMapImpl>::NodeNameType]>>{
+        (*self).iter()
+    }
+}
+
+/// Stacks that can be built for any number of steps, e.g. for trees extended before t = 0.
+pub(crate) trait BinomialTreeStackBuilder: BinomialTreeStackImpl {
+    fn with_steps(number_of_steps: usize) -> Self;
+}
+
 pub(crate) trait GetValue {
-    fn get(&self) -> &f32;
+    /// The value, unless it has not been set yet.
+    fn get(&self) -> Option<&Float>;
 }
 
 impl GetValue for BinomTreeValueType {
-    fn get(&self) -> &f32 {
-        let value = self.get();
-        value.expect("The tree should be evaluated backwards")
+    fn get(&self) -> Option<&Float> {
+        OnceCell::get(self)
+    }
+}
+
+impl GetValue for BinomialTreeMapNumericType {
+    fn get(&self) -> Option<&Float> {
+        Some(self)
     }
 }
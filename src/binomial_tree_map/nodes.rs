@@ -1,3 +1,5 @@
+use crate::Float;
+use crate::binomial_tree_map::r#static;
 use std::fmt::{Display, Formatter};
 use std::hash::{Hash, Hasher};
 use std::iter::once;
@@ -8,6 +10,7 @@ pub /*(crate)*/ static ALL_UPDOWNS: [UpDown; 2] = [UpDown::Up, UpDown::Down];
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
 pub(crate) enum UpDown {
     Up,
+    Middle,
     Down,
 }
 
@@ -15,6 +18,7 @@ impl From<&UpDown> for char {
     fn from(value: &UpDown) -> Self {
         match value {
             UpDown::Up => 'U',
+            UpDown::Middle => 'M',
             UpDown::Down => 'D',
         }
     }
@@ -32,6 +36,7 @@ impl TryFrom<char> for UpDown {
     fn try_from(value: char) -> Result<Self, Self::Error> {
         match value {
             'U' => Ok(UpDown::Up),
+            'M' => Ok(UpDown::Middle),
             'D' => Ok(UpDown::Down),
             _ => Err(()),
         }
@@ -43,7 +48,11 @@ pub(crate) trait NodeNameTrait {
 
     fn up(&self) -> Self;
     fn down(&self) -> Self;
-    fn value(&self, initial_value: f32, up_probability: f32, down_probability: f32) -> f32;
+    fn value(&self, initial_value: Float, up_probability: Float, down_probability: Float) -> Float;
+}
+
+pub(crate) trait TrinomialNodeNameTrait: NodeNameTrait {
+    fn middle(&self) -> Self;
 }
 
 #[derive(Debug, PartialEq, Eq, Ord, PartialOrd, Hash, Clone, Default)]
@@ -73,7 +82,7 @@ impl NodeNameTrait for NodeName {
         }
     }
 
-    fn value(&self, initial_value: f32, up_probability: f32, down_probability: f32) -> f32 {
+    fn value(&self, initial_value: Float, up_probability: Float, down_probability: Float) -> Float {
         let mut value = initial_value;
 
         for i in self.iter() {
@@ -81,6 +90,7 @@ impl NodeNameTrait for NodeName {
                 UpDown::Up => {
                     value *= up_probability;
                 }
+                UpDown::Middle => {}
                 UpDown::Down => {
                     value *= down_probability;
                 }
@@ -132,6 +142,161 @@ impl From<&[UpDown]> for NodeName {
     }
 }
 
+/// Node name in a recombining trinomial tree.
+///
+/// Names are kept in a canonical form: sorted (U < M < D) and never containing both an up
+/// and a down move, since an up followed by a down recombines with two middle moves.
+/// For example, `UUM` is two net ups after three steps and `MDD` two net downs.
+#[derive(Debug, PartialEq, Eq, Ord, PartialOrd, Hash, Clone, Default)]
+pub(crate) struct TrinomialNodeName {
+    name: Vec<UpDown>,
+}
+
+impl NodeNameTrait for TrinomialNodeName {
+    type NameType = Vec<UpDown>;
+
+    fn up(&self) -> Self {
+        let mut name = self.name.clone();
+        match name.last() {
+            Some(UpDown::Down) => {
+                // NOTE: An up move cancels a down move, UD = MM
+                name.pop();
+                name.insert(0, UpDown::Middle);
+                name.insert(0, UpDown::Middle);
+            }
+            _ => name.insert(0, UpDown::Up),
+        }
+
+        TrinomialNodeName { name }
+    }
+
+    fn down(&self) -> Self {
+        let mut name = self.name.clone();
+        match name.first() {
+            Some(UpDown::Up) => {
+                // NOTE: A down move cancels an up move, UD = MM
+                name.remove(0);
+                name.push(UpDown::Middle);
+                name.push(UpDown::Middle);
+                name.sort();
+            }
+            _ => name.push(UpDown::Down),
+        }
+
+        TrinomialNodeName { name }
+    }
+
+    fn value(&self, initial_value: Float, up_value: Float, down_value: Float) -> Float {
+        let mut value = initial_value;
+
+        for i in self.name.iter() {
+            match i {
+                UpDown::Up => {
+                    value *= up_value;
+                }
+                UpDown::Middle => {}
+                UpDown::Down => {
+                    value *= down_value;
+                }
+            }
+        }
+
+        value
+    }
+}
+
+impl TrinomialNodeNameTrait for TrinomialNodeName {
+    fn middle(&self) -> Self {
+        let mut name = self.name.clone();
+        let position = name.partition_point(|i| *i == UpDown::Up);
+        name.insert(position, UpDown::Middle);
+
+        TrinomialNodeName { name }
+    }
+}
+
+impl TrinomialNodeName {
+    /// Creates the canonical name of the node `net_ups` up moves away from the centre
+    /// after `number_of_steps` steps.
+    pub(crate) fn new(number_of_steps: usize, net_ups: isize) -> Self {
+        let moves = net_ups.unsigned_abs();
+        let direction = if net_ups > 0 {
+            UpDown::Up
+        } else {
+            UpDown::Down
+        };
+
+        let middles = std::iter::repeat_n(UpDown::Middle, number_of_steps - moves);
+        let name = if net_ups > 0 {
+            std::iter::repeat_n(direction, moves)
+                .chain(middles)
+                .collect()
+        } else {
+            middles
+                .chain(std::iter::repeat_n(direction, moves))
+                .collect()
+        };
+
+        TrinomialNodeName { name }
+    }
+}
+
+impl Display for TrinomialNodeName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s: String = self.name.iter().map(char::from).collect();
+        write!(f, "{}", s)
+    }
+}
+
+impl TryFrom<&str> for TrinomialNodeName {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let updowns: Result<Vec<_>, _> = value.chars().map(UpDown::try_from).collect();
+
+        Ok(TrinomialNodeName { name: updowns? })
+    }
+}
+
+/// Node of a recombining binomial tree, addressed by its step and its number of up moves.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub(crate) struct NodeIndex {
+    pub(crate) step: usize,
+    pub(crate) ups: usize,
+}
+
+impl NodeNameTrait for NodeIndex {
+    type NameType = (usize, usize);
+
+    fn up(&self) -> Self {
+        NodeIndex {
+            step: self.step + 1,
+            ups: self.ups + 1,
+        }
+    }
+
+    fn down(&self) -> Self {
+        NodeIndex {
+            step: self.step + 1,
+            ups: self.ups,
+        }
+    }
+
+    fn value(&self, initial_value: Float, up_value: Float, down_value: Float) -> Float {
+        // Multiplied in the same order as the named nodes, ups first
+        std::iter::repeat_n(up_value, self.ups)
+            .chain(std::iter::repeat_n(down_value, self.step - self.ups))
+            .fold(initial_value, |value, factor| value * factor)
+    }
+}
+
+impl NodeIndex {
+    /// Position of the node in an array holding the levels one after the other.
+    pub(crate) fn position(&self) -> usize {
+        self.step * (self.step + 1) / 2 + self.ups
+    }
+}
+
 #[derive(Debug, Default, Clone)]
 pub(crate) struct NodeName2 {
     pub(crate) name: &'static [UpDown],
@@ -142,20 +307,14 @@ impl NodeNameTrait for NodeName2 {
     type NameType = &'static [UpDown];
 
     fn up(&self) -> Self {
-        Self {
-            name: self.name,
-            direction: Some(UpDown::Up),
-        }
+        self.then(UpDown::Up)
     }
 
     fn down(&self) -> Self {
-        Self {
-            name: self.name,
-            direction: Some(UpDown::Down),
-        }
+        self.then(UpDown::Down)
     }
 
-    fn value(&self, initial_value: f32, up_value: f32, down_value: f32) -> f32 {
+    fn value(&self, initial_value: Float, up_value: Float, down_value: Float) -> Float {
         let mut value = initial_value;
 
         for i in self.iter() {
@@ -163,6 +322,7 @@ impl NodeNameTrait for NodeName2 {
                 UpDown::Up => {
                     value *= up_value;
                 }
+                UpDown::Middle => {}
                 UpDown::Down => {
                     value *= down_value;
                 }
@@ -174,6 +334,23 @@ impl NodeNameTrait for NodeName2 {
 }
 
 impl NodeName2 {
+    /// The node one `direction` move after this one. A node already one move past its name
+    /// gets the name of the moves so far, so that the moves can go on beyond the next level.
+    fn then(&self, direction: UpDown) -> Self {
+        let name = match self.direction {
+            None => self.name,
+            Some(_) => {
+                let ups = self.iter().filter(|&&step| step == UpDown::Up).count();
+                r#static::node_name(ups, self.name.len() + 1 - ups)
+            }
+        };
+
+        Self {
+            name,
+            direction: Some(direction),
+        }
+    }
+
     pub(crate) fn iter(&self) -> impl Iterator<Item = &UpDown> {
         self.name
             .iter()
@@ -216,6 +393,8 @@ impl PartialEq for NodeName2 {
                     return &self.name[..self.name.len() - 1] == other.name
                         && self.name[self.name.len() - 1] == direction;
                 }
+                // NOTE: Binomial nodes never move sideways
+                UpDown::Middle => return false,
             }
         }
         if let Some(direction) = self.direction {
@@ -230,6 +409,8 @@ impl PartialEq for NodeName2 {
                     return &other.name[..other.name.len() - 1] == self.name
                         && other.name[other.name.len() - 1] == direction;
                 }
+                // NOTE: Binomial nodes never move sideways
+                UpDown::Middle => return false,
             }
         }
 
@@ -307,6 +488,40 @@ mod tests {
         assert_ne!(NodeName2::default(), NodeName2::default().down());
     }
 
+    #[test]
+    fn test_nodename2_moves_beyond_next_level() {
+        let node = NodeName2::default().up().down();
+        assert_eq!(
+            node,
+            NodeName2 {
+                name: &[UpDown::Up, UpDown::Down],
+                direction: None
+            }
+        );
+        assert_eq!(
+            node.up().up(),
+            NodeName2 {
+                name: &[UpDown::Up, UpDown::Up, UpDown::Up, UpDown::Down],
+                direction: None
+            }
+        );
+        assert_eq!(node.up().down().value(10.0, 2.0, 0.5), 10.0);
+    }
+
+    #[test]
+    fn test_node_index() {
+        let node = NodeIndex::default();
+        assert_eq!(node.up(), NodeIndex { step: 1, ups: 1 });
+        assert_eq!(node.up().down(), node.down().up());
+        assert_eq!(node.up().down().value(10.0, 2.0, 0.5), 10.0);
+
+        // Positions are consecutive, level by level
+        let positions: Vec<_> = (0..4)
+            .flat_map(|step| (0..=step).map(move |ups| NodeIndex { step, ups }.position()))
+            .collect();
+        assert_eq!(positions, (0..10).collect::<Vec<_>>());
+    }
+
     #[test]
     fn test_tree_updown_from() {
         assert_eq!(UpDown::try_from('U').unwrap(), UpDown::Up);
@@ -371,4 +586,40 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_trinomial_node_name_moves() {
+        let name = TrinomialNodeName::default();
+
+        assert_eq!(name.up(), "U".try_into().unwrap());
+        assert_eq!(name.middle(), "M".try_into().unwrap());
+        assert_eq!(name.down(), "D".try_into().unwrap());
+
+        // Up and down moves recombine into middle moves
+        assert_eq!(name.up().down(), "MM".try_into().unwrap());
+        assert_eq!(name.down().up(), "MM".try_into().unwrap());
+        assert_eq!(name.up().middle().down(), "MMM".try_into().unwrap());
+        assert_eq!(name.up().up().down(), "UMM".try_into().unwrap());
+        assert_eq!(name.down().down().up(), "MMD".try_into().unwrap());
+        assert_eq!(name.down().middle(), "MD".try_into().unwrap());
+        assert_eq!(name.up().middle(), "UM".try_into().unwrap());
+    }
+
+    #[test]
+    fn test_trinomial_node_name_new() {
+        assert_eq!(TrinomialNodeName::new(0, 0), TrinomialNodeName::default());
+        assert_eq!(TrinomialNodeName::new(3, 2), "UUM".try_into().unwrap());
+        assert_eq!(TrinomialNodeName::new(3, 0), "MMM".try_into().unwrap());
+        assert_eq!(TrinomialNodeName::new(3, -1), "MMD".try_into().unwrap());
+        assert_eq!(TrinomialNodeName::new(2, -2), "DD".try_into().unwrap());
+    }
+
+    #[test]
+    fn test_trinomial_node_name_value() {
+        let name: TrinomialNodeName = "UUM".try_into().unwrap();
+        assert_eq!(name.value(10.0, 2.0, 0.5), 40.0);
+
+        let name: TrinomialNodeName = "MMD".try_into().unwrap();
+        assert_eq!(name.value(10.0, 2.0, 0.5), 5.0);
+    }
 }
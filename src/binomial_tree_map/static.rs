@@ -1,41 +1,138 @@
 use crate::binomial_tree_map::BinomialTreeMapNumericType;
 use crate::binomial_tree_map::capacity::calculate_capacity;
 use crate::binomial_tree_map::nodes::{NodeName2, NodeNameTrait, UpDown};
-use crate::binomial_tree_map::{BinomTreeValueType, BinomialTreeMapImpl, BinomialTreeStackImpl};
+use crate::binomial_tree_map::{
+    BinomTreeValueType, BinomialTreeMapImpl, BinomialTreeStackBuilder, BinomialTreeStackImpl,
+};
 use binomial_tree_macro::binomial_tree_stack;
 use hashbrown::HashMap;
-use id_arena::{Arena, Id};
-use std::cell::OnceCell;
-use std::ops::Deref;
+use std::iter::repeat_n;
+use std::ops::{Deref, RangeInclusive};
+use std::sync::{Arc, LazyLock, RwLock};
 
-pub const MAX_TREE_SIZE: usize = 128;
+const PRE_ALLOCATED_STEPS: usize = 128;
 const PRE_ALLOCATED_STACK: &[&[NodeName2]] = binomial_tree_stack!(128);
-pub(crate) const MAX_CAPACITY: usize = calculate_capacity(MAX_TREE_SIZE);
+pub(crate) const DEFAULT_CAPACITY: usize = calculate_capacity(PRE_ALLOCATED_STEPS);
 
-#[derive(Debug, Default)]
+/// Number of steps of the stack generated at compile time, which bounded the number of
+/// steps of static maps before the shared stack could grow.
+#[deprecated(note = "static maps grow to any number of steps")]
+pub const MAX_TREE_SIZE: usize = PRE_ALLOCATED_STEPS;
+
+/// Nodes of one level of a stack, shared by the stacks of all sizes that include it.
+type Level = Arc<[NodeName2]>;
+
+/// Stack shared by all static maps, starting with the stack generated at compile time.
+///
+/// Larger trees grow it at runtime to exactly their number of steps. The levels are shared
+/// with the previous stack, which is dropped with the last map using it.
+static SHARED_STACK: LazyLock<RwLock<Arc<[Level]>>> = LazyLock::new(|| {
+    RwLock::new(
+        PRE_ALLOCATED_STACK
+            .iter()
+            .map(|level| Level::from(*level))
+            .collect(),
+    )
+});
+
+/// Run of ups followed by as many downs, which the names of the grown levels are windows
+/// of. Names need `'static` moves, so runs are leaked, growing at least twice as long to
+/// bound them by a few bytes per step of the largest tree.
+static MOVES: RwLock<&'static [UpDown]> = RwLock::new(&[]);
+
+#[derive(Debug)]
 pub struct StaticBinomialTreeMap {
-    pub(crate) stack: &'static [&'static [NodeName2]],
+    pub(crate) stack: Arc<[Level]>,
+    number_of_steps: usize,
 }
 
 impl StaticBinomialTreeMap {
     pub fn with_capacity(capacity: usize) -> StaticBinomialTreeMap {
-        let stack: &'static [&'static [NodeName2]] = &PRE_ALLOCATED_STACK[..=capacity]; // TODO: This trick could probably be used in a dynamic case as well!
+        StaticBinomialTreeMap {
+            stack: shared_stack(capacity),
+            number_of_steps: capacity,
+        }
+    }
+}
 
-        StaticBinomialTreeMap { stack }
+impl Default for StaticBinomialTreeMap {
+    fn default() -> Self {
+        Self::with_capacity(0)
     }
 }
 
+/// The shared stack with at least `number_of_steps` steps, growing it if needed.
+fn shared_stack(number_of_steps: usize) -> Arc<[Level]> {
+    let stack = SHARED_STACK.read().unwrap().clone();
+    if number_of_steps < stack.len() {
+        return stack;
+    }
+    drop(stack);
+
+    let mut stack = SHARED_STACK.write().unwrap();
+    // Another thread may have grown it in the meantime
+    if number_of_steps >= stack.len() {
+        *stack = stack
+            .iter()
+            .cloned()
+            .chain(build_levels(stack.len()..=number_of_steps))
+            .collect();
+    }
+    stack.clone()
+}
+
+/// Builds the given levels, sorted from the highest to the lowest price as the stack
+/// generated at compile time.
+fn build_levels(levels: RangeInclusive<usize>) -> impl Iterator<Item = Level> {
+    let moves = moves(*levels.end());
+    let middle = moves.len() / 2;
+
+    levels.map(move |i| {
+        (0..=i)
+            .rev()
+            .map(|ups| NodeName2 {
+                name: &moves[middle - ups..middle - ups + i],
+                direction: None,
+            })
+            .collect()
+    })
+}
+
+/// Name of the node with `ups` up moves and `downs` down moves, ups first as in the stacks.
+pub(crate) fn node_name(ups: usize, downs: usize) -> &'static [UpDown] {
+    let moves = moves(ups.max(downs));
+    let middle = moves.len() / 2;
+    &moves[middle - ups..middle + downs]
+}
+
+/// A run of at least `number_of_steps` ups followed by as many downs.
+fn moves(number_of_steps: usize) -> &'static [UpDown] {
+    let moves = *MOVES.read().unwrap();
+    if number_of_steps <= moves.len() / 2 {
+        return moves;
+    }
+
+    let mut moves = MOVES.write().unwrap();
+    if number_of_steps > moves.len() / 2 {
+        let length = number_of_steps.max(moves.len());
+        *moves = Box::leak(
+            repeat_n(UpDown::Up, length)
+                .chain(repeat_n(UpDown::Down, length))
+                .collect(),
+        );
+    }
+    *moves
+}
+
 #[derive(Debug)]
 pub struct StaticContainer {
-    arena: Arena<BinomTreeValueType>,
-    pub(crate) map: HashMap<NodeName2, Id<BinomTreeValueType>>,
+    pub(crate) map: HashMap<NodeName2, BinomTreeValueType>,
 }
 
 impl Default for StaticContainer {
     fn default() -> Self {
         Self {
-            arena: Arena::with_capacity(MAX_CAPACITY),
-            map: HashMap::<NodeName2, Id<BinomTreeValueType>>::with_capacity(MAX_CAPACITY),
+            map: HashMap::<NodeName2, BinomTreeValueType>::with_capacity(DEFAULT_CAPACITY),
         }
     }
 }
@@ -46,20 +143,23 @@ impl BinomialTreeMapImpl for StaticContainer {
     type ValueType = BinomTreeValueType;
 
     fn get(&self, node_name: &Self::NodeNameType) -> Option<&Self::ValueType> {
-        let id = self.map.get(node_name)?;
-        Some(&self.arena[*id])
+        self.map.get(node_name)
     }
 
     fn get_next_step(&self, node_name: &Self::NodeNameType) -> Option<&Self::ValueType> {
-        let (key, _) = self.map.get_key_value(&node_name.up()).unwrap();
-        self.get(&key.down())
+        self.get(&node_name.up().down())
     }
 
     fn set(&mut self, node_name: &Self::NodeNameType, value: Self::NumericType) {
-        let id = self.arena.alloc(OnceCell::new());
-        self.map.entry(node_name.clone()).or_insert(id);
+        self.map
+            .entry(node_name.clone())
+            .or_default()
+            .set(value)
+            .unwrap();
+    }
 
-        self.arena[id].set(value).unwrap();
+    fn clear(&mut self) {
+        self.map.clear();
     }
 }
 
@@ -72,14 +172,57 @@ impl BinomialTreeStackImpl for StaticBinomialTreeMap {
                 Target=[<<Self as BinomialTreeStackImpl>::NodeNameContainerType as BinomialTreeMapImpl>::NodeNameType]
             >
     >{
-        self.stack.iter()
+        self.stack[..=self.number_of_steps].iter()
+    }
+}
+
+impl BinomialTreeStackBuilder for StaticBinomialTreeMap {
+    fn with_steps(number_of_steps: usize) -> Self {
+        Self::with_capacity(number_of_steps)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    //use super::*;
+    use super::*;
+    use pretty_assertions::assert_eq;
 
     #[test]
-    fn test_static_binomial_tree() {}
+    fn test_build_levels() {
+        let levels: Vec<Level> = build_levels(0..=PRE_ALLOCATED_STEPS).collect();
+        let pre_allocated: Vec<Level> = PRE_ALLOCATED_STACK
+            .iter()
+            .map(|level| Level::from(*level))
+            .collect();
+        assert_eq!(levels, pre_allocated);
+    }
+
+    #[test]
+    fn test_shared_stack_growth() {
+        let tree = StaticBinomialTreeMap::with_capacity(300);
+        let levels: Vec<_> = tree.iter().collect();
+        assert_eq!(levels.len(), 301);
+        assert_eq!(levels[300].len(), 301);
+        assert!(
+            levels
+                .iter()
+                .zip(PRE_ALLOCATED_STACK)
+                .all(|(level, pre_allocated)| ***level == **pre_allocated)
+        );
+
+        // Smaller trees share the levels of the grown stack
+        let smaller = StaticBinomialTreeMap::with_capacity(200);
+        assert!(Arc::ptr_eq(&smaller.stack[200], &tree.stack[200]));
+        assert_eq!(smaller.iter().len(), 201);
+    }
+
+    #[test]
+    fn test_shared_stack_release() {
+        let stack = Arc::downgrade(&StaticBinomialTreeMap::with_capacity(500).stack);
+
+        // One more step grows the stack by one level, and the previous stack is dropped
+        let tree = StaticBinomialTreeMap::with_capacity(501);
+        assert_eq!(tree.iter().len(), 502);
+        assert!(stack.upgrade().is_none());
+    }
 }
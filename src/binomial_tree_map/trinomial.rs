@@ -0,0 +1,121 @@
+use crate::binomial_tree_map::nodes::{TrinomialNodeName, TrinomialNodeNameTrait};
+use crate::binomial_tree_map::{
+    BinomialTreeMapImpl, BinomialTreeMapNumericType, BinomialTreeMapValue, BinomialTreeStackImpl,
+};
+use hashbrown::HashMap;
+use std::ops::Deref;
+
+#[derive(Default, Debug)]
+pub struct TrinomialTreeMap {
+    // Map consists of canonical keys only (sorted, never both U and D). For example: UUMM.
+    map: HashMap<TrinomialNodeName, BinomialTreeMapValue<BinomialTreeMapNumericType>>,
+    stack: Vec<Vec<TrinomialNodeName>>,
+}
+
+impl TrinomialTreeMap {
+    pub fn new(number_of_steps: usize) -> Self {
+        let mut stack: Vec<Vec<TrinomialNodeName>> = Vec::with_capacity(number_of_steps + 1);
+
+        for i in 0..=number_of_steps {
+            // Sorted from the highest to the lowest price, as in the binomial stacks
+            let steps = i as isize;
+            let vec = (-steps..=steps)
+                .rev()
+                .map(|net_ups| TrinomialNodeName::new(i, net_ups))
+                .collect();
+
+            stack.push(vec);
+        }
+
+        Self {
+            map: Default::default(),
+            stack,
+        }
+    }
+}
+
+impl BinomialTreeMapImpl for TrinomialTreeMap {
+    type NodeNameType = TrinomialNodeName;
+    type NumericType = BinomialTreeMapNumericType;
+    type ValueType = BinomialTreeMapValue<Self::NumericType>;
+
+    fn get(&self, node_name: &Self::NodeNameType) -> Option<&Self::ValueType> {
+        self.map.get(node_name)
+    }
+
+    fn get_next_step(&self, node_name: &Self::NodeNameType) -> Option<&Self::ValueType> {
+        // The middle move keeps the price, so the next step is a single move away
+        self.map.get(&node_name.middle())
+    }
+
+    fn set(&mut self, node_name: &Self::NodeNameType, value: Self::NumericType) {
+        self.map
+            .entry(node_name.clone())
+            .or_default()
+            .set(value)
+            .unwrap();
+    }
+
+    fn clear(&mut self) {
+        self.map.clear();
+    }
+}
+
+impl BinomialTreeStackImpl for TrinomialTreeMap {
+    type NodeNameContainerType = TrinomialTreeMap;
+
+    fn iter(&self) -> impl DoubleEndedIterator +
+        ExactSizeIterator<
+            Item=&impl Deref<
+                Target=[<<Self as BinomialTreeStackImpl>::NodeNameContainerType as BinomialTreeMapImpl>::NodeNameType]
+            >
+    >{
+        self.stack.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::binomial_tree_map::trinomial::TrinomialTreeMap;
+
+    #[test]
+    fn test_stack_initialization() {
+        let tree = TrinomialTreeMap::new(3);
+
+        let stack_vec: Vec<_> = tree.stack.iter().collect();
+        assert_eq!(stack_vec.len(), 4); // 0 to 3 steps
+
+        // A recombining trinomial tree has 2n + 1 nodes after n steps
+        assert_eq!(stack_vec[3].len(), 7);
+        assert_eq!(tree.map.len(), 0);
+    }
+
+    #[test]
+    fn test_stack_structure() {
+        let tree = TrinomialTreeMap::new(2);
+        let mut stack_iter = tree.stack.iter().rev();
+
+        assert_eq!(
+            stack_iter.next().unwrap(),
+            &vec![
+                "UU".try_into().unwrap(),
+                "UM".try_into().unwrap(),
+                "MM".try_into().unwrap(),
+                "MD".try_into().unwrap(),
+                "DD".try_into().unwrap()
+            ]
+        );
+
+        assert_eq!(
+            stack_iter.next().unwrap(),
+            &vec![
+                "U".try_into().unwrap(),
+                "M".try_into().unwrap(),
+                "D".try_into().unwrap()
+            ]
+        );
+
+        assert_eq!(stack_iter.next().unwrap(), &vec!["".try_into().unwrap()]);
+        assert_eq!(stack_iter.next(), None);
+    }
+}
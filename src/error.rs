@@ -0,0 +1,117 @@
+//! Errors of the fallible constructors and evaluators.
+
+use crate::Float;
+use crate::implied_volatility::ImpliedVolatilityError;
+
+use std::fmt;
+
+/// Inputs rejected by `try_new` and `try_eval`, and errors of the solvers built on the trees.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Error {
+    /// The spot is zero, negative or not a number.
+    NonPositiveSpot(Float),
+    /// The spot net of the present value of the cash dividends until expiry is zero,
+    /// negative or not a number, so the escrowed-dividend lattice has no positive prices.
+    NonPositiveNetSpot(Float),
+    /// The strike is zero, negative or not a number.
+    NonPositiveStrike(Float),
+    /// The volatility is zero, negative or not a number.
+    NonPositiveVolatility(Float),
+    /// The time to expiry is zero, negative or not a number.
+    NonPositiveExpiry(Float),
+    /// The tree has fewer steps than the model needs, e.g. two for gamma and theta.
+    TooFewSteps {
+        steps: usize,
+        minimum: usize,
+    },
+    /// The node stack was built for a different number of steps than the model.
+    StackSteps {
+        stack_steps: usize,
+        number_of_steps: usize,
+    },
+    /// The lattice is only defined for an odd number of steps, as the Leisen-Reimer lattice.
+    EvenSteps(usize),
+    /// The model does not price barrier options, which the binomial tree over the whole
+    /// node stack does.
+    UnsupportedBarrier,
+    /// The risk-neutral probability of an up move lies outside (0, 1), so the lattice admits
+    /// arbitrage. Happens when the drift over a step outweighs the volatility over a step,
+    /// and is fixed by taking more steps.
+    Arbitrage {
+        probability: Float,
+    },
+    /// A bump size of the bump-and-revalue sensitivities is zero, negative or not a number.
+    NonPositiveBump(Float),
+    /// The stretch of the trinomial lattice is below one, or not a number, which gives a
+    /// negative middle probability.
+    StretchBelowOne(Float),
+    ImpliedVolatility(ImpliedVolatilityError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NonPositiveSpot(spot) => write!(f, "spot {spot} is not positive"),
+            Error::NonPositiveNetSpot(spot) => {
+                write!(f, "spot net of the cash dividends {spot} is not positive")
+            }
+            Error::NonPositiveStrike(strike) => write!(f, "strike {strike} is not positive"),
+            Error::NonPositiveVolatility(volatility) => {
+                write!(f, "volatility {volatility} is not positive")
+            }
+            Error::NonPositiveExpiry(expiry) => {
+                write!(f, "time to expiry {expiry} is not positive")
+            }
+            Error::TooFewSteps { steps, minimum } => {
+                write!(f, "the tree needs at least {minimum} steps, got {steps}")
+            }
+            Error::StackSteps {
+                stack_steps,
+                number_of_steps,
+            } => write!(
+                f,
+                "the node stack has {stack_steps} steps, the model {number_of_steps}"
+            ),
+            Error::EvenSteps(steps) => {
+                write!(f, "the lattice needs an odd number of steps, got {steps}")
+            }
+            Error::UnsupportedBarrier => write!(
+                f,
+                "barrier options are priced on the binomial tree over the whole node stack"
+            ),
+            Error::Arbitrage { probability } => write!(
+                f,
+                "risk-neutral up probability {probability} is outside (0, 1), increase the number of steps"
+            ),
+            Error::NonPositiveBump(bump) => write!(f, "bump size {bump} is not positive"),
+            Error::StretchBelowOne(stretch) => {
+                write!(f, "the lattice stretch {stretch} is below one")
+            }
+            Error::ImpliedVolatility(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::ImpliedVolatility(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<ImpliedVolatilityError> for Error {
+    fn from(error: ImpliedVolatilityError) -> Self {
+        Error::ImpliedVolatility(error)
+    }
+}
+
+/// Checks that `value` is positive, reporting it with `error` otherwise.
+pub(crate) fn positive(value: Float, error: fn(Float) -> Error) -> Result<Float, Error> {
+    if value > 0.0 {
+        Ok(value)
+    } else {
+        Err(error(value))
+    }
+}
@@ -0,0 +1,239 @@
+//! Implied volatility by inverting the Cox-Ross-Rubinstein tree or the Black-Scholes formula.
+//!
+//! Both solvers keep a bracket around the root and take Newton steps with the tree's vega
+//! (Black-Scholes uses secant steps), falling back to bisection whenever a step would leave
+//! the bracket.
+
+use crate::Float;
+use crate::binomial_tree_map::r#static::StaticBinomialTreeMap;
+use crate::black_scholes::black_value;
+use crate::instruments::{OptionContract, OptionType};
+use crate::model::{CoxRossRubenstein, Expiry, Spot};
+
+use std::fmt;
+
+/// Lowest volatility searched for, raised for trees with a strong drift per step
+pub const MIN_VOLATILITY: Float = 1e-3;
+/// Highest volatility searched for
+pub const MAX_VOLATILITY: Float = 5.0;
+
+const MAX_ITERATIONS: usize = 100;
+const PRICE_TOLERANCE: Float = 1e-5;
+const VOLATILITY_TOLERANCE: Float = 1e-6;
+
+/// Reasons why no implied volatility could be found.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ImpliedVolatilityError {
+    /// The market price lies outside the prices attainable with volatilities up to
+    /// [`MAX_VOLATILITY`], e.g. below the intrinsic value.
+    PriceOutOfBounds {
+        market_price: Float,
+        lower_bound: Float,
+        upper_bound: Float,
+    },
+    /// The solver did not reach the tolerance within the maximum number of iterations.
+    NotConverged {
+        volatility: Float,
+        iterations: usize,
+    },
+}
+
+impl fmt::Display for ImpliedVolatilityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImpliedVolatilityError::PriceOutOfBounds {
+                market_price,
+                lower_bound,
+                upper_bound,
+            } => write!(
+                f,
+                "market price {market_price} is outside of the attainable range [{lower_bound}, {upper_bound}]"
+            ),
+            ImpliedVolatilityError::NotConverged {
+                volatility,
+                iterations,
+            } => write!(
+                f,
+                "implied volatility did not converge after {iterations} iterations (last estimate {volatility})"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ImpliedVolatilityError {}
+
+/// Implied volatility of an American or European option priced on a Cox-Ross-Rubinstein tree
+/// with `steps` steps.
+pub fn implied_volatility<T: OptionContract + Clone + Sync>(
+    market_price: Float,
+    option: &T,
+    spot: Float,
+    expiry: Float,
+    rate: Float,
+    dividends: Float,
+    steps: usize,
+) -> Result<Float, ImpliedVolatilityError> {
+    // Below this volatility the risk-neutral probabilities of the tree leave [0, 1]
+    let min_volatility =
+        MIN_VOLATILITY.max(2.0 * (rate - dividends).abs() * (expiry / steps as Float).sqrt());
+
+    solve(market_price, min_volatility, |volatility| {
+        let model: CoxRossRubenstein<StaticBinomialTreeMap> = CoxRossRubenstein::new(
+            StaticBinomialTreeMap::with_capacity(steps),
+            Spot(spot),
+            steps,
+            Expiry(expiry),
+            volatility,
+            rate,
+            dividends,
+        );
+        let eval = model.eval(option.clone());
+
+        (eval.value().0, Some(eval.vega().0))
+    })
+}
+
+/// Implied volatility of a European option priced with the Black-Scholes formula.
+pub fn black_implied_volatility(
+    market_price: Float,
+    option_type: OptionType,
+    spot: Float,
+    strike: Float,
+    rate: Float,
+    dividends: Float,
+    expiry: Float,
+) -> Result<Float, ImpliedVolatilityError> {
+    solve(market_price, MIN_VOLATILITY, |volatility| {
+        let value = black_value(
+            option_type,
+            spot,
+            strike,
+            volatility,
+            rate,
+            dividends,
+            expiry,
+        );
+
+        (value, None)
+    })
+}
+
+/// Finds the volatility at which `value_at` matches the market price.
+///
+/// `value_at` returns the option value and, if available, its vega. Option values are
+/// increasing in the volatility, which keeps the bracket valid throughout.
+fn solve(
+    market_price: Float,
+    min_volatility: Float,
+    value_at: impl Fn(Float) -> (Float, Option<Float>),
+) -> Result<Float, ImpliedVolatilityError> {
+    let (lower_bound, _) = value_at(min_volatility);
+    let (upper_bound, _) = value_at(MAX_VOLATILITY);
+    if !(lower_bound..=upper_bound).contains(&market_price) {
+        return Err(ImpliedVolatilityError::PriceOutOfBounds {
+            market_price,
+            lower_bound,
+            upper_bound,
+        });
+    }
+
+    let (mut low, mut high) = (min_volatility, MAX_VOLATILITY);
+    // Secant steps start from the lower end of the bracket
+    let mut previous = (min_volatility, lower_bound - market_price);
+    let mut volatility = Float::clamp(0.2, low, high);
+
+    for _ in 0..MAX_ITERATIONS {
+        let (value, vega) = value_at(volatility);
+        let difference = value - market_price;
+        if difference.abs() < PRICE_TOLERANCE {
+            return Ok(volatility);
+        }
+
+        if difference < 0.0 {
+            low = volatility;
+        } else {
+            high = volatility;
+        }
+        if high - low < VOLATILITY_TOLERANCE {
+            return Ok(volatility);
+        }
+
+        let slope = vega.unwrap_or_else(|| (difference - previous.1) / (volatility - previous.0));
+        previous = (volatility, difference);
+
+        let step = volatility - difference / slope;
+        volatility = if step.is_finite() && low < step && step < high {
+            step
+        } else {
+            (low + high) / 2.0
+        };
+    }
+
+    Err(ImpliedVolatilityError::NotConverged {
+        volatility,
+        iterations: MAX_ITERATIONS,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instruments::{AmericanOption, EuropeanOption, StrikeContract};
+
+    #[test]
+    fn test_black_implied_volatility() {
+        for option_type in [OptionType::Call, OptionType::Put] {
+            for volatility in [0.05, 0.3, 1.2] {
+                let price = black_value(option_type, 100.0, 95.0, volatility, 0.05, 0.02, 0.5);
+                let implied =
+                    black_implied_volatility(price, option_type, 100.0, 95.0, 0.05, 0.02, 0.5)
+                        .unwrap();
+
+                assert!((implied - volatility).abs() < 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_tree_implied_volatility() {
+        let option = AmericanOption::new(OptionType::Put, 105.0, 0.5);
+        let model: CoxRossRubenstein<StaticBinomialTreeMap> = CoxRossRubenstein::new(
+            StaticBinomialTreeMap::with_capacity(50),
+            Spot(100.0),
+            50,
+            Expiry(0.5),
+            0.3,
+            0.05,
+            0.02,
+        );
+        let price = model.eval(option).value().0;
+
+        let implied = implied_volatility(price, &option, 100.0, 0.5, 0.05, 0.02, 50).unwrap();
+        assert!((implied - 0.3).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_tree_implied_volatility_matches_black() {
+        let option = EuropeanOption::new(OptionType::Call, 95.0, 0.5);
+        let price = black_value(OptionType::Call, 100.0, 95.0, 0.25, 0.05, 0.0, 0.5);
+
+        let implied = implied_volatility(price, &option, 100.0, 0.5, 0.05, 0.0, 100).unwrap();
+        assert!((implied - 0.25).abs() < 5e-3);
+    }
+
+    #[test]
+    fn test_implied_volatility_out_of_bounds() {
+        // Below the intrinsic value of 5
+        let option = AmericanOption::new(OptionType::Put, 105.0, 0.5);
+        assert!(matches!(
+            implied_volatility(4.0, &option, 100.0, 0.5, 0.05, 0.0, 50),
+            Err(ImpliedVolatilityError::PriceOutOfBounds { .. })
+        ));
+
+        // Above the spot
+        assert!(matches!(
+            black_implied_volatility(101.0, OptionType::Call, 100.0, 95.0, 0.05, 0.0, 0.5),
+            Err(ImpliedVolatilityError::PriceOutOfBounds { .. })
+        ));
+    }
+}
@@ -1,32 +1,121 @@
+use crate::Float;
+use crate::analytical::AmericanApproximation;
+use crate::black_scholes::{
+    asset_or_nothing_value, barrier_value, black_value, cash_or_nothing_value, one_touch_value,
+    payoff_value,
+};
+use crate::error::{Error, positive};
+
 pub trait OptionContract {
-    fn new(option_type: OptionType, strike: f32, expiry: f32) -> Self;
-    fn expiry(&self) -> f32;
-    fn strike(&self) -> f32;
+    fn expiry(&self) -> Float;
+    fn value(&self, value: Float, price: Float, time: NodeTime) -> Float;
+    fn intrinsic_value(&self, price: Float) -> Float;
+
+    /// European value with `expiry` left, used by the Black-Scholes based strategies.
+    fn black_value(
+        &self,
+        spot: Float,
+        volatility: Float,
+        rate: Float,
+        dividends: Float,
+        expiry: Float,
+    ) -> Float;
+
+    /// Value with `expiry` left from an analytic American approximation, used by the
+    /// strategies based on it. Contracts without such an approximation keep their
+    /// [`Self::black_value`].
+    fn american_value(
+        &self,
+        _approximation: AmericanApproximation,
+        spot: Float,
+        volatility: Float,
+        rate: Float,
+        dividends: Float,
+        expiry: Float,
+    ) -> Float {
+        self.black_value(spot, volatility, rate, dividends, expiry)
+    }
+
+    /// Strike the strike-dependent lattices are centred on, `None` centres them on the spot.
+    fn strike(&self) -> Option<Float> {
+        None
+    }
+
+    /// Barrier knocking the option in or out, applied by the binomial tree model.
+    fn barrier(&self) -> Option<&Barrier> {
+        None
+    }
+
+    /// Type and strike of a plain call or put payoff, `None` for other payoffs.
+    fn vanilla(&self) -> Option<(OptionType, Float)> {
+        None
+    }
+
+    /// Whether [`Self::black_value`] and [`Self::american_value`] value the contract. The
+    /// strategies leave the others to the tree.
+    fn has_analytic_value(&self) -> bool {
+        true
+    }
+}
+
+/// Put or call contract on a single strike.
+pub trait StrikeContract: OptionContract {
+    fn new(option_type: OptionType, strike: Float, expiry: Float) -> Self;
+
+    /// Contract with a positive strike and time to expiry, see [`Self::new`].
+    fn try_new(option_type: OptionType, strike: Float, expiry: Float) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        positive(strike, Error::NonPositiveStrike)?;
+        positive(expiry, Error::NonPositiveExpiry)?;
+        Ok(Self::new(option_type, strike, expiry))
+    }
+
     fn option_type(&self) -> OptionType;
-    fn value(&self, value: f32, price: f32) -> f32;
+}
 
-    fn intrinsic_value(&self, price: f32) -> f32 {
-        match self.option_type() {
-            OptionType::Put => (self.strike() - price).max(0.0),
-            OptionType::Call => (price - self.strike()).max(0.0),
-        }
+/// Time of the tree level a node value is computed at.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NodeTime {
+    /// Time from the valuation date in years
+    pub time: Float,
+    /// Spacing between the tree levels in years
+    pub time_step: Float,
+}
+
+impl NodeTime {
+    /// Whether `date` falls on this level or is nearer to it than to any other level.
+    pub fn is_nearest_level(&self, date: Float) -> bool {
+        (date / self.time_step).round() == (self.time / self.time_step).round()
     }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum OptionType {
     Put = -1,
     Call = 1,
 }
 
+impl OptionType {
+    /// Payoff of exercising against `strike` at `price`.
+    pub fn payoff(self, strike: Float, price: Float) -> Float {
+        match self {
+            OptionType::Put => (strike - price).max(0.0),
+            OptionType::Call => (price - strike).max(0.0),
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
 pub struct AmericanOption {
     option_type: OptionType,
-    strike: f32,
-    expiry: f32,
+    strike: Float,
+    expiry: Float,
 }
 
-impl OptionContract for AmericanOption {
-    fn new(option_type: OptionType, strike: f32, expiry: f32) -> Self {
+impl StrikeContract for AmericanOption {
+    fn new(option_type: OptionType, strike: Float, expiry: Float) -> Self {
         Self {
             option_type,
             strike,
@@ -34,56 +123,711 @@ impl OptionContract for AmericanOption {
         }
     }
 
-    fn expiry(&self) -> f32 {
+    fn option_type(&self) -> OptionType {
+        self.option_type
+    }
+}
+
+impl OptionContract for AmericanOption {
+    fn expiry(&self) -> Float {
         self.expiry
     }
-    fn strike(&self) -> f32 {
-        self.strike
+    fn strike(&self) -> Option<Float> {
+        Some(self.strike)
     }
-    fn option_type(&self) -> OptionType {
-        self.option_type
+    fn vanilla(&self) -> Option<(OptionType, Float)> {
+        Some((self.option_type, self.strike))
     }
 
-    fn value(&self, value: f32, price: f32) -> f32 {
+    fn value(&self, value: Float, price: Float, _: NodeTime) -> Float {
         let payoff = self.intrinsic_value(price);
         payoff.max(value)
     }
+
+    fn intrinsic_value(&self, price: Float) -> Float {
+        self.option_type.payoff(self.strike, price)
+    }
+
+    fn black_value(
+        &self,
+        spot: Float,
+        volatility: Float,
+        rate: Float,
+        dividends: Float,
+        expiry: Float,
+    ) -> Float {
+        black_value(
+            self.option_type,
+            spot,
+            self.strike,
+            volatility,
+            rate,
+            dividends,
+            expiry,
+        )
+    }
+
+    fn american_value(
+        &self,
+        approximation: AmericanApproximation,
+        spot: Float,
+        volatility: Float,
+        rate: Float,
+        dividends: Float,
+        expiry: Float,
+    ) -> Float {
+        approximation.american_value()(
+            self.option_type,
+            spot,
+            self.strike,
+            volatility,
+            rate,
+            dividends,
+            expiry,
+        )
+    }
 }
 
+#[derive(Copy, Clone)]
 pub struct EuropeanOption {
     option_type: OptionType,
-    strike: f32,
-    expiry: f32,
+    strike: Float,
+    expiry: Float,
+}
+
+impl StrikeContract for EuropeanOption {
+    fn new(option_type: OptionType, strike: Float, expiry: Float) -> Self {
+        Self {
+            option_type,
+            strike,
+            expiry,
+        }
+    }
+
+    fn option_type(&self) -> OptionType {
+        self.option_type
+    }
 }
 
 impl OptionContract for EuropeanOption {
-    fn new(option_type: OptionType, strike: f32, expiry: f32) -> Self {
+    fn expiry(&self) -> Float {
+        self.expiry
+    }
+    fn strike(&self) -> Option<Float> {
+        Some(self.strike)
+    }
+    fn vanilla(&self) -> Option<(OptionType, Float)> {
+        Some((self.option_type, self.strike))
+    }
+
+    fn value(&self, value: Float, _: Float, _: NodeTime) -> Float {
+        value
+    }
+
+    fn intrinsic_value(&self, price: Float) -> Float {
+        self.option_type.payoff(self.strike, price)
+    }
+
+    fn black_value(
+        &self,
+        spot: Float,
+        volatility: Float,
+        rate: Float,
+        dividends: Float,
+        expiry: Float,
+    ) -> Float {
+        black_value(
+            self.option_type,
+            spot,
+            self.strike,
+            volatility,
+            rate,
+            dividends,
+            expiry,
+        )
+    }
+}
+
+/// Option exercisable at expiry and on the given exercise times.
+///
+/// Exercise times between tree levels are moved to the nearest level.
+#[derive(Clone)]
+pub struct BermudanOption {
+    option_type: OptionType,
+    strike: Float,
+    expiry: Float,
+    exercise_times: Vec<Float>,
+}
+
+impl BermudanOption {
+    pub fn with_exercise_times(
+        option_type: OptionType,
+        strike: Float,
+        expiry: Float,
+        exercise_times: Vec<Float>,
+    ) -> Self {
         Self {
             option_type,
             strike,
             expiry,
+            exercise_times,
         }
     }
 
-    fn expiry(&self) -> f32 {
+    pub fn exercise_times(&self) -> &[Float] {
+        &self.exercise_times
+    }
+}
+
+impl StrikeContract for BermudanOption {
+    /// Bermudan option without exercise times before expiry, see [`Self::with_exercise_times`].
+    fn new(option_type: OptionType, strike: Float, expiry: Float) -> Self {
+        Self::with_exercise_times(option_type, strike, expiry, Vec::new())
+    }
+
+    fn option_type(&self) -> OptionType {
+        self.option_type
+    }
+}
+
+impl OptionContract for BermudanOption {
+    fn expiry(&self) -> Float {
+        self.expiry
+    }
+    fn strike(&self) -> Option<Float> {
+        Some(self.strike)
+    }
+    fn vanilla(&self) -> Option<(OptionType, Float)> {
+        Some((self.option_type, self.strike))
+    }
+
+    fn value(&self, value: Float, price: Float, time: NodeTime) -> Float {
+        if self
+            .exercise_times
+            .iter()
+            .any(|exercise_time| time.is_nearest_level(*exercise_time))
+        {
+            self.intrinsic_value(price).max(value)
+        } else {
+            value
+        }
+    }
+
+    fn intrinsic_value(&self, price: Float) -> Float {
+        self.option_type.payoff(self.strike, price)
+    }
+
+    fn black_value(
+        &self,
+        spot: Float,
+        volatility: Float,
+        rate: Float,
+        dividends: Float,
+        expiry: Float,
+    ) -> Float {
+        black_value(
+            self.option_type,
+            spot,
+            self.strike,
+            volatility,
+            rate,
+            dividends,
+            expiry,
+        )
+    }
+}
+
+/// Times at which an option can be exercised before expiry.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Exercise {
+    /// At expiry only
+    European,
+    /// At any time
+    American,
+    /// On the given times, each moved to the nearest tree level
+    Bermudan(Vec<Float>),
+}
+
+impl Exercise {
+    /// Whether the option can be exercised on the level at `time`.
+    pub fn is_exercisable(&self, time: NodeTime) -> bool {
+        match self {
+            Exercise::European => false,
+            Exercise::American => true,
+            Exercise::Bermudan(exercise_times) => exercise_times
+                .iter()
+                .any(|exercise_time| time.is_nearest_level(*exercise_time)),
+        }
+    }
+}
+
+/// Option paying an arbitrary function of the price, e.g. a spread, straddle or power payoff.
+///
+/// The Black-Scholes based strategies integrate the payoff numerically, see
+/// [`payoff_value`].
+#[derive(Clone)]
+pub struct CustomOption<F> {
+    payoff: F,
+    exercise: Exercise,
+    expiry: Float,
+}
+
+impl<F: Fn(Float) -> Float> CustomOption<F> {
+    pub fn new(payoff: F, exercise: Exercise, expiry: Float) -> Self {
+        Self {
+            payoff,
+            exercise,
+            expiry,
+        }
+    }
+
+    pub fn exercise(&self) -> &Exercise {
+        &self.exercise
+    }
+}
+
+impl<F: Fn(Float) -> Float> OptionContract for CustomOption<F> {
+    fn expiry(&self) -> Float {
         self.expiry
     }
-    fn strike(&self) -> f32 {
-        self.strike
+
+    fn value(&self, value: Float, price: Float, time: NodeTime) -> Float {
+        if self.exercise.is_exercisable(time) {
+            self.intrinsic_value(price).max(value)
+        } else {
+            value
+        }
+    }
+
+    fn intrinsic_value(&self, price: Float) -> Float {
+        (self.payoff)(price)
+    }
+
+    fn black_value(
+        &self,
+        spot: Float,
+        volatility: Float,
+        rate: Float,
+        dividends: Float,
+        expiry: Float,
+    ) -> Float {
+        payoff_value(&self.payoff, spot, volatility, rate, dividends, expiry)
+    }
+}
+
+/// European option paying a fixed amount of cash if it expires in the money.
+#[derive(Copy, Clone)]
+pub struct CashOrNothingOption {
+    option_type: OptionType,
+    strike: Float,
+    expiry: Float,
+    cash: Float,
+}
+
+impl CashOrNothingOption {
+    pub fn with_cash(option_type: OptionType, strike: Float, expiry: Float, cash: Float) -> Self {
+        Self {
+            option_type,
+            strike,
+            expiry,
+            cash,
+        }
+    }
+}
+
+impl StrikeContract for CashOrNothingOption {
+    /// Cash-or-nothing option paying 1, see [`Self::with_cash`].
+    fn new(option_type: OptionType, strike: Float, expiry: Float) -> Self {
+        Self::with_cash(option_type, strike, expiry, 1.0)
+    }
+
+    fn option_type(&self) -> OptionType {
+        self.option_type
+    }
+}
+
+impl OptionContract for CashOrNothingOption {
+    fn expiry(&self) -> Float {
+        self.expiry
+    }
+    fn strike(&self) -> Option<Float> {
+        Some(self.strike)
+    }
+
+    fn value(&self, value: Float, _: Float, _: NodeTime) -> Float {
+        value
+    }
+
+    fn intrinsic_value(&self, price: Float) -> Float {
+        let in_the_money = match self.option_type {
+            OptionType::Put => price < self.strike,
+            OptionType::Call => price > self.strike,
+        };
+
+        if in_the_money { self.cash } else { 0.0 }
+    }
+
+    fn black_value(
+        &self,
+        spot: Float,
+        volatility: Float,
+        rate: Float,
+        dividends: Float,
+        expiry: Float,
+    ) -> Float {
+        self.cash
+            * cash_or_nothing_value(
+                self.option_type,
+                spot,
+                self.strike,
+                volatility,
+                rate,
+                dividends,
+                expiry,
+            )
     }
+}
+
+/// European option paying the asset if it expires in the money.
+#[derive(Copy, Clone)]
+pub struct AssetOrNothingOption {
+    option_type: OptionType,
+    strike: Float,
+    expiry: Float,
+}
+
+impl StrikeContract for AssetOrNothingOption {
+    fn new(option_type: OptionType, strike: Float, expiry: Float) -> Self {
+        Self {
+            option_type,
+            strike,
+            expiry,
+        }
+    }
+
     fn option_type(&self) -> OptionType {
         self.option_type
     }
+}
+
+impl OptionContract for AssetOrNothingOption {
+    fn expiry(&self) -> Float {
+        self.expiry
+    }
+    fn strike(&self) -> Option<Float> {
+        Some(self.strike)
+    }
 
-    fn value(&self, value: f32, _: f32) -> f32 {
+    fn value(&self, value: Float, _: Float, _: NodeTime) -> Float {
         value
     }
+
+    fn intrinsic_value(&self, price: Float) -> Float {
+        let in_the_money = match self.option_type {
+            OptionType::Put => price < self.strike,
+            OptionType::Call => price > self.strike,
+        };
+
+        if in_the_money { price } else { 0.0 }
+    }
+
+    fn black_value(
+        &self,
+        spot: Float,
+        volatility: Float,
+        rate: Float,
+        dividends: Float,
+        expiry: Float,
+    ) -> Float {
+        asset_or_nothing_value(
+            self.option_type,
+            spot,
+            self.strike,
+            volatility,
+            rate,
+            dividends,
+            expiry,
+        )
+    }
+}
+
+/// American cash-or-nothing option, paying the cash as soon as the price touches the strike.
+///
+/// Calls pay on touching the strike from below, puts from above.
+#[derive(Copy, Clone)]
+pub struct OneTouchOption {
+    option_type: OptionType,
+    strike: Float,
+    expiry: Float,
+    cash: Float,
+}
+
+impl OneTouchOption {
+    pub fn with_cash(option_type: OptionType, strike: Float, expiry: Float, cash: Float) -> Self {
+        Self {
+            option_type,
+            strike,
+            expiry,
+            cash,
+        }
+    }
+}
+
+impl StrikeContract for OneTouchOption {
+    /// One-touch option paying 1, see [`Self::with_cash`].
+    fn new(option_type: OptionType, strike: Float, expiry: Float) -> Self {
+        Self::with_cash(option_type, strike, expiry, 1.0)
+    }
+
+    fn option_type(&self) -> OptionType {
+        self.option_type
+    }
+}
+
+impl OptionContract for OneTouchOption {
+    fn expiry(&self) -> Float {
+        self.expiry
+    }
+    fn strike(&self) -> Option<Float> {
+        Some(self.strike)
+    }
+
+    fn value(&self, value: Float, price: Float, _: NodeTime) -> Float {
+        self.intrinsic_value(price).max(value)
+    }
+
+    fn intrinsic_value(&self, price: Float) -> Float {
+        let touched = match self.option_type {
+            OptionType::Put => price <= self.strike,
+            OptionType::Call => price >= self.strike,
+        };
+
+        if touched { self.cash } else { 0.0 }
+    }
+
+    fn black_value(
+        &self,
+        spot: Float,
+        volatility: Float,
+        rate: Float,
+        dividends: Float,
+        expiry: Float,
+    ) -> Float {
+        self.cash
+            * one_touch_value(
+                self.option_type,
+                spot,
+                self.strike,
+                volatility,
+                rate,
+                dividends,
+                expiry,
+            )
+    }
+}
+
+/// Direction of a barrier and whether crossing it knocks the option in or out.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum BarrierKind {
+    UpAndOut,
+    DownAndOut,
+    UpAndIn,
+    DownAndIn,
+}
+
+/// Times at which a barrier is observed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Monitoring {
+    /// On every tree level, with the Derman-Kani correction for the barrier position
+    Continuous,
+    /// On the given times only, each moved to the nearest tree level
+    Discrete(Vec<Float>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Barrier {
+    pub kind: BarrierKind,
+    pub level: Float,
+    /// Paid when knocked out, or at expiry if never knocked in
+    pub rebate: Float,
+    pub monitoring: Monitoring,
+}
+
+impl Barrier {
+    pub fn is_knock_in(&self) -> bool {
+        matches!(self.kind, BarrierKind::UpAndIn | BarrierKind::DownAndIn)
+    }
+
+    pub fn is_up(&self) -> bool {
+        matches!(self.kind, BarrierKind::UpAndOut | BarrierKind::UpAndIn)
+    }
+
+    /// Whether `price` is on or beyond the barrier.
+    pub fn is_crossed(&self, price: Float) -> bool {
+        if self.is_up() {
+            price >= self.level
+        } else {
+            price <= self.level
+        }
+    }
+
+    pub fn is_monitored(&self, time: NodeTime) -> bool {
+        match &self.monitoring {
+            Monitoring::Continuous => true,
+            Monitoring::Discrete(times) => times.iter().any(|t| time.is_nearest_level(*t)),
+        }
+    }
+}
+
+/// Option with a knock-in or knock-out barrier.
+///
+/// A knock-in option turns into the underlying option once the barrier is crossed.
+///
+/// Knock-out calls and puts with a continuous barrier get the closed-form values of
+/// [`barrier_value`], also as their American value. Other knock-out options have no
+/// analytical value and are valued on the tree alone. Knock-in options are valued as the
+/// knocked-in contract, which the lattice evaluates them from.
+#[derive(Clone)]
+pub struct BarrierOption<T> {
+    underlying: T,
+    barrier: Barrier,
+}
+
+impl<T: OptionContract> BarrierOption<T> {
+    pub fn with_barrier(underlying: T, barrier: Barrier) -> Self {
+        Self {
+            underlying,
+            barrier,
+        }
+    }
+}
+
+impl<T: OptionContract> BarrierOption<T> {
+    /// Closed-form value of a knock-out call or put with a continuous barrier.
+    fn knock_out_value(
+        &self,
+        spot: Float,
+        volatility: Float,
+        rate: Float,
+        dividends: Float,
+        expiry: Float,
+    ) -> Option<Float> {
+        let barrier = &self.barrier;
+        if barrier.is_knock_in() || barrier.monitoring != Monitoring::Continuous {
+            return None;
+        }
+        let (option_type, strike) = self.underlying.vanilla()?;
+        Some(barrier_value(
+            option_type,
+            spot,
+            strike,
+            barrier,
+            volatility,
+            rate,
+            dividends,
+            expiry,
+        ))
+    }
+}
+
+impl<T: StrikeContract> StrikeContract for BarrierOption<T> {
+    /// Barrier option with a barrier that is never crossed, see [`Self::with_barrier`].
+    fn new(option_type: OptionType, strike: Float, expiry: Float) -> Self {
+        Self::with_barrier(
+            T::new(option_type, strike, expiry),
+            Barrier {
+                kind: BarrierKind::UpAndOut,
+                level: Float::INFINITY,
+                rebate: 0.0,
+                monitoring: Monitoring::Continuous,
+            },
+        )
+    }
+
+    fn option_type(&self) -> OptionType {
+        self.underlying.option_type()
+    }
+}
+
+impl<T: OptionContract> OptionContract for BarrierOption<T> {
+    fn expiry(&self) -> Float {
+        self.underlying.expiry()
+    }
+    fn strike(&self) -> Option<Float> {
+        self.underlying.strike()
+    }
+
+    fn value(&self, value: Float, price: Float, time: NodeTime) -> Float {
+        self.underlying.value(value, price, time)
+    }
+
+    fn intrinsic_value(&self, price: Float) -> Float {
+        self.underlying.intrinsic_value(price)
+    }
+
+    fn black_value(
+        &self,
+        spot: Float,
+        volatility: Float,
+        rate: Float,
+        dividends: Float,
+        expiry: Float,
+    ) -> Float {
+        self.knock_out_value(spot, volatility, rate, dividends, expiry)
+            .unwrap_or_else(|| {
+                self.underlying
+                    .black_value(spot, volatility, rate, dividends, expiry)
+            })
+    }
+
+    fn american_value(
+        &self,
+        approximation: AmericanApproximation,
+        spot: Float,
+        volatility: Float,
+        rate: Float,
+        dividends: Float,
+        expiry: Float,
+    ) -> Float {
+        self.knock_out_value(spot, volatility, rate, dividends, expiry)
+            .unwrap_or_else(|| {
+                self.underlying.american_value(
+                    approximation,
+                    spot,
+                    volatility,
+                    rate,
+                    dividends,
+                    expiry,
+                )
+            })
+    }
+
+    fn barrier(&self) -> Option<&Barrier> {
+        Some(&self.barrier)
+    }
+
+    fn vanilla(&self) -> Option<(OptionType, Float)> {
+        self.underlying.vanilla()
+    }
+
+    fn has_analytic_value(&self) -> bool {
+        if self.barrier.is_knock_in() {
+            self.underlying.has_analytic_value()
+        } else {
+            self.barrier.monitoring == Monitoring::Continuous && self.vanilla().is_some()
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const TIME: NodeTime = NodeTime {
+        time: 0.25,
+        time_step: 0.05,
+    };
+
     #[test]
     fn test_american() {
         let option = AmericanOption {
@@ -94,10 +838,10 @@ mod tests {
 
         assert_eq!(option.intrinsic_value(30.0), 20.0);
         assert_eq!(option.intrinsic_value(60.0), 0.0);
-        assert_eq!(option.value(10.0, 30.0), 20.0);
-        assert_eq!(option.value(30.0, 40.0), 30.0);
-        assert_eq!(option.value(40.0, 30.0), 40.0);
-        assert_eq!(option.value(20.0, 10.0), 40.0);
+        assert_eq!(option.value(10.0, 30.0, TIME), 20.0);
+        assert_eq!(option.value(30.0, 40.0, TIME), 30.0);
+        assert_eq!(option.value(40.0, 30.0, TIME), 40.0);
+        assert_eq!(option.value(20.0, 10.0, TIME), 40.0);
     }
 
     #[test]
@@ -110,9 +854,150 @@ mod tests {
 
         assert_eq!(option.intrinsic_value(30.0), 20.0);
         assert_eq!(option.intrinsic_value(60.0), 0.0);
-        assert_eq!(option.value(10.0, 30.0), 10.0);
-        assert_eq!(option.value(30.0, 40.0), 30.0);
-        assert_eq!(option.value(40.0, 30.0), 40.0);
-        assert_eq!(option.value(20.0, 10.0), 20.0);
+        assert_eq!(option.value(10.0, 30.0, TIME), 10.0);
+        assert_eq!(option.value(30.0, 40.0, TIME), 30.0);
+        assert_eq!(option.value(40.0, 30.0, TIME), 40.0);
+        assert_eq!(option.value(20.0, 10.0, TIME), 20.0);
+    }
+
+    #[test]
+    fn test_bermudan() {
+        let option = BermudanOption::with_exercise_times(OptionType::Put, 50.0, 0.5, vec![0.26]);
+
+        assert_eq!(option.value(10.0, 30.0, TIME), 20.0);
+        assert_eq!(option.value(30.0, 40.0, TIME), 30.0);
+        // Not an exercise level
+        let time = NodeTime { time: 0.3, ..TIME };
+        assert_eq!(option.value(10.0, 30.0, time), 10.0);
+    }
+
+    #[test]
+    fn test_custom() {
+        let straddle = |price: Float| (price - 50.0).abs();
+
+        let option = CustomOption::new(straddle, Exercise::American, 0.5);
+        assert_eq!(option.intrinsic_value(30.0), 20.0);
+        assert_eq!(option.intrinsic_value(60.0), 10.0);
+        assert_eq!(option.value(10.0, 30.0, TIME), 20.0);
+        assert_eq!(option.value(30.0, 60.0, TIME), 30.0);
+        assert_eq!(option.strike(), None);
+
+        let option = CustomOption::new(straddle, Exercise::European, 0.5);
+        assert_eq!(option.value(10.0, 30.0, TIME), 10.0);
+
+        let option = CustomOption::new(straddle, Exercise::Bermudan(vec![0.26]), 0.5);
+        assert_eq!(option.value(10.0, 30.0, TIME), 20.0);
+        let time = NodeTime { time: 0.3, ..TIME };
+        assert_eq!(option.value(10.0, 30.0, time), 10.0);
+    }
+
+    #[test]
+    fn test_try_new() {
+        assert!(AmericanOption::try_new(OptionType::Put, 50.0, 0.5).is_ok());
+        assert!(matches!(
+            EuropeanOption::try_new(OptionType::Put, -50.0, 0.5),
+            Err(Error::NonPositiveStrike(-50.0))
+        ));
+        assert!(matches!(
+            BermudanOption::try_new(OptionType::Call, 50.0, 0.0),
+            Err(Error::NonPositiveExpiry(0.0))
+        ));
+        assert!(matches!(
+            AmericanOption::try_new(OptionType::Call, Float::NAN, 0.5),
+            Err(Error::NonPositiveStrike(_))
+        ));
+    }
+
+    #[test]
+    fn test_nearest_level() {
+        assert!(TIME.is_nearest_level(0.25));
+        assert!(TIME.is_nearest_level(0.27));
+        assert!(TIME.is_nearest_level(0.23));
+        assert!(!TIME.is_nearest_level(0.28));
+        assert!(!TIME.is_nearest_level(0.22));
+    }
+
+    #[test]
+    fn test_digitals() {
+        let cash = CashOrNothingOption::with_cash(OptionType::Call, 50.0, 0.5, 10.0);
+        assert_eq!(cash.intrinsic_value(60.0), 10.0);
+        assert_eq!(cash.intrinsic_value(50.0), 0.0);
+        assert_eq!(cash.value(3.0, 60.0, TIME), 3.0);
+
+        let asset = AssetOrNothingOption::new(OptionType::Put, 50.0, 0.5);
+        assert_eq!(asset.intrinsic_value(40.0), 40.0);
+        assert_eq!(asset.intrinsic_value(60.0), 0.0);
+        assert_eq!(asset.value(3.0, 40.0, TIME), 3.0);
+
+        let touch = OneTouchOption::with_cash(OptionType::Put, 50.0, 0.5, 10.0);
+        assert_eq!(touch.intrinsic_value(50.0), 10.0);
+        assert_eq!(touch.intrinsic_value(60.0), 0.0);
+        assert_eq!(touch.value(3.0, 50.0, TIME), 10.0);
+        assert_eq!(touch.value(3.0, 60.0, TIME), 3.0);
+    }
+
+    #[test]
+    fn test_barrier_black_value() {
+        let call = EuropeanOption::new(OptionType::Call, 100.0, 0.5);
+        let barrier = |kind, monitoring| Barrier {
+            kind,
+            level: 90.0,
+            rebate: 2.0,
+            monitoring,
+        };
+        let black =
+            |option: &dyn OptionContract, spot| option.black_value(spot, 0.3, 0.05, 0.0, 0.5);
+        let closed_form = |kind, spot| {
+            barrier_value(
+                OptionType::Call,
+                spot,
+                100.0,
+                &barrier(kind, Monitoring::Continuous),
+                0.3,
+                0.05,
+                0.0,
+                0.5,
+            )
+        };
+
+        let down_and_out = BarrierOption::with_barrier(
+            call,
+            barrier(BarrierKind::DownAndOut, Monitoring::Continuous),
+        );
+        assert!(down_and_out.has_analytic_value());
+        // Knocked out, near the barrier and out of its reach
+        for spot in [85.0, 95.0, 200.0] {
+            assert_eq!(
+                black(&down_and_out, spot),
+                closed_form(BarrierKind::DownAndOut, spot)
+            );
+        }
+        assert_eq!(black(&down_and_out, 85.0), 2.0);
+        assert_eq!(
+            down_and_out.american_value(
+                AmericanApproximation::BaroneAdesiWhaley,
+                95.0,
+                0.3,
+                0.05,
+                0.0,
+                0.5
+            ),
+            closed_form(BarrierKind::DownAndOut, 95.0)
+        );
+
+        // Knock-in options are valued as the knocked-in contract
+        let down_and_in = BarrierOption::with_barrier(
+            call,
+            barrier(BarrierKind::DownAndIn, Monitoring::Continuous),
+        );
+        assert!(down_and_in.has_analytic_value());
+        assert_eq!(black(&down_and_in, 95.0), black(&call, 95.0));
+
+        // No closed form for discrete barriers, valued on the tree alone
+        let discrete = BarrierOption::with_barrier(
+            call,
+            barrier(BarrierKind::DownAndOut, Monitoring::Discrete(vec![0.25])),
+        );
+        assert!(!discrete.has_analytic_value());
     }
 }
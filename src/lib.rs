@@ -1,7 +1,53 @@
+/// Asserts that `actual` equals the single precision reference value `expected`.
+///
+/// The comparison is exact in the default build. The `f64` feature only matches the
+/// reference values up to their rounding, so there `actual` has to be within `tolerance`,
+/// relative to `expected` and at least absolute.
+#[cfg(all(test, not(feature = "f64")))]
+macro_rules! assert_reference_eq {
+    ($actual:expr, $expected:expr $(, $tolerance:expr)?) => {
+        assert_eq!($actual, $expected)
+    };
+}
+
+#[cfg(all(test, feature = "f64"))]
+macro_rules! assert_reference_eq {
+    ($actual:expr, $expected:expr) => {
+        assert_reference_eq!($actual, $expected, 1e-4)
+    };
+    ($actual:expr, $expected:expr, $tolerance:expr) => {{
+        let (actual, expected): ($crate::Float, $crate::Float) = ($actual, $expected);
+        let tolerance: $crate::Float = $tolerance * expected.abs().max(1.0);
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "{actual} is not within {tolerance} of {expected}"
+        );
+    }};
+}
+
 pub(crate) mod analytical;
+pub mod batch;
 pub mod binomial_tree_map;
+pub mod error;
+pub mod implied_volatility;
 pub mod instruments;
 pub mod macros;
 pub mod model;
 
-pub use analytical::black_scholes;
+pub use analytical::{
+    AmericanApproximation, barone_adesi_whaley, bjerksund_stensland, black_scholes,
+};
+pub use error::Error;
+
+/// Floating point type used for prices, parameters and greeks throughout the crate.
+///
+/// Single precision by default; enable the `f64` feature for double precision, e.g. for
+/// trees with thousands of steps where the accumulated rounding error becomes visible.
+#[cfg(not(feature = "f64"))]
+pub type Float = f32;
+
+/// Floating point type used for prices, parameters and greeks throughout the crate.
+///
+/// Double precision, selected by the `f64` feature.
+#[cfg(feature = "f64")]
+pub type Float = f64;
@@ -5,10 +5,12 @@ macro_rules! binomial_tree_map {
 }
 /// Evaluates a binomial tree for an option with specified parameters.
 ///
-/// Automatically uses dynamic storage for trees with >128 steps, static storage otherwise.
+/// Uses the static node storage, which is shared between trees of any number of steps.
 /// Returns a type-erased `Box<dyn EvaluatedBinomialTree>` implementing Display and providing
 /// access to value, greeks, and tree visualization.
 ///
+/// Uses the Cox-Ross-Rubinstein lattice, see `eval_binomial_tree_with_lattice!` for other lattices.
+///
 /// # Arguments
 /// - `steps` - Number of binomial tree steps
 /// - `option_type` - AmericanOption or EuropeanOption
@@ -33,46 +35,62 @@ macro_rules! binomial_tree_map {
 #[macro_export]
 macro_rules! eval_binomial_tree {
     ($N:expr, $option:ty, $option_type:ident, $strike:expr, $spot:expr, $expiry:expr, $volatility:expr, $interest_rate:expr, $dividend_rate:expr) => {{
-        use $crate::binomial_tree_map::r#static::{MAX_TREE_SIZE, StaticBinomialTreeMap};
-        use $crate::instruments::{OptionContract, OptionType, $option};
-        use $crate::model::{CoxRossRubenstein, border_truncation, erase_type, leaf_smoothing};
+        $crate::eval_binomial_tree_with_lattice!(
+            CoxRossRubenstein,
+            $N,
+            $option,
+            $option_type,
+            $strike,
+            $spot,
+            $expiry,
+            $volatility,
+            $interest_rate,
+            $dividend_rate
+        )
+    }};
+}
+
+/// Evaluates a binomial tree for an option using the given lattice parametrisation.
+///
+/// Takes the name of a marker type in `model::lattice` (`CoxRossRubenstein`, `JarrowRudd`,
+/// `Tian` or `LeisenReimer`) followed by the same arguments as `eval_binomial_tree!`.
+/// `LeisenReimer` needs an odd number of steps.
+///
+/// # Example
+/// ```
+/// use binominal_tree_model::{eval_binomial_tree_with_lattice, instruments::AmericanOption};
+///
+/// let tree = eval_binomial_tree_with_lattice!(Tian, 100, AmericanOption, Call, 95.0, 100.0, 0.5, 0.3, 0.05, 0.0);
+/// assert!(tree.value().0 > 0.0);
+/// ```
+#[doc(hidden)]
+#[macro_export]
+macro_rules! eval_binomial_tree_with_lattice {
+    ($lattice:ident, $N:expr, $option:ty, $option_type:ident, $strike:expr, $spot:expr, $expiry:expr, $volatility:expr, $interest_rate:expr, $dividend_rate:expr) => {{
+        use $crate::binomial_tree_map::r#static::StaticBinomialTreeMap;
+        use $crate::instruments::{OptionType, StrikeContract, $option};
+        use $crate::model::{
+            BinomialTreeModel, border_truncation, erase_type, lattice, leaf_smoothing,
+        };
         use $crate::model::{Expiry, Spot};
 
-        if $N > MAX_TREE_SIZE {
-            let tree_map = $crate::binomial_tree_map::dynamic::DynamicBinomialTreeMap::new($N);
-            let binom_tree: CoxRossRubenstein<
-                $crate::binomial_tree_map::dynamic::DynamicBinomialTreeMap,
-                leaf_smoothing::Black,
-                border_truncation::Black,
-            > = CoxRossRubenstein::new(
-                tree_map,
-                Spot($spot),
-                $N,
-                Expiry($expiry),
-                $volatility,
-                $interest_rate,
-                $dividend_rate,
-            );
-
-            erase_type(binom_tree.eval(<$option>::new(OptionType::$option_type, $strike, $expiry)))
-        } else {
-            let tree_map = $crate::binomial_tree_map!($N);
-            let binom_tree: CoxRossRubenstein<
-                StaticBinomialTreeMap,
-                leaf_smoothing::Black,
-                border_truncation::Black,
-            > = CoxRossRubenstein::new(
-                tree_map,
-                Spot($spot),
-                $N,
-                Expiry($expiry),
-                $volatility,
-                $interest_rate,
-                $dividend_rate,
-            );
-
-            erase_type(binom_tree.eval(<$option>::new(OptionType::$option_type, $strike, $expiry)))
-        }
+        let tree_map = $crate::binomial_tree_map!($N);
+        let binom_tree: BinomialTreeModel<
+            StaticBinomialTreeMap,
+            leaf_smoothing::Black,
+            border_truncation::Black,
+            lattice::$lattice,
+        > = BinomialTreeModel::new(
+            tree_map,
+            Spot($spot),
+            $N,
+            Expiry($expiry),
+            $volatility,
+            $interest_rate,
+            $dividend_rate,
+        );
+
+        erase_type(binom_tree.eval(<$option>::new(OptionType::$option_type, $strike, $expiry)))
     }};
 }
 
@@ -80,11 +98,19 @@ macro_rules! eval_binomial_tree {
 #[doc(hidden)]
 #[macro_export]
 macro_rules! eval_binomial_tree_with_steps {
-    ($N:expr, $($y:tt),+) => {
-        {
-            $crate::eval_binomial_tree!($N, $($y),+)
-        }
-    };
+    ($N:expr, $option:ty, $option_type:ident, $strike:expr, $spot:expr, $expiry:expr, $volatility:expr, $interest_rate:expr, $dividend_rate:expr) => {{
+        $crate::eval_binomial_tree!(
+            $N,
+            $option,
+            $option_type,
+            $strike,
+            $spot,
+            $expiry,
+            $volatility,
+            $interest_rate,
+            $dividend_rate
+        )
+    }};
 }
 
 /// Calculates American option value (100-step binomial tree).
@@ -234,15 +260,76 @@ mod tests {
     #[test]
     fn test_value() {
         let val = american_value!(Call, 95.0, 100.0, 0.5, 0.3, 0.05, 0.0);
-        assert_eq!(val.0, 12.333031);
+        assert_reference_eq!(val.0, 12.333031);
 
         let val = european_value!(Put, 105.0, 100.0, 0.5, 0.3, 0.05, 0.0);
-        assert_eq!(val.0, 9.805331);
+        assert_reference_eq!(val.0, 9.805331);
 
         let greeks = american_greeks!(Call, 95.0, 100.0, 0.5, 0.3, 0.05, 0.0);
-        assert_eq!(greeks.delta.0, 0.6791013);
+        assert_reference_eq!(greeks.delta.0, 0.6791013);
 
         let greeks = european_greeks!(Put, 105.0, 100.0, 0.5, 0.3, 0.05, 0.0);
-        assert_eq!(greeks.delta.0, -0.50278103);
+        assert_reference_eq!(greeks.delta.0, -0.50278103);
+    }
+
+    #[test]
+    fn test_expression_arguments() {
+        use crate::black_scholes::black_value;
+        use crate::instruments::OptionType;
+
+        // Negative literals and operators are whole arguments
+        let rate = 0.01;
+        let tree = eval_binomial_tree_with_steps!(
+            100,
+            EuropeanOption,
+            Put,
+            105.0,
+            100.0,
+            0.5,
+            0.3,
+            -0.01,
+            rate + 0.01
+        );
+        let expected = black_value(OptionType::Put, 100.0, 105.0, 0.3, -0.01, 0.02, 0.5);
+        assert!((tree.value().0 - expected).abs() < 1e-2);
+
+        let val = european_value!(Put, 105.0, 100.0, 0.5, 0.3, -rate, 0.0);
+        let expected = black_value(OptionType::Put, 100.0, 105.0, 0.3, -0.01, 0.0, 0.5);
+        assert!((val.0 - expected).abs() < 1e-2);
+    }
+
+    #[test]
+    fn test_lattice() {
+        let crr = eval_binomial_tree_with_lattice!(
+            CoxRossRubenstein,
+            100,
+            AmericanOption,
+            Call,
+            95.0,
+            100.0,
+            0.5,
+            0.3,
+            0.05,
+            0.0
+        );
+        assert_eq!(
+            crr.value(),
+            eval_binomial_tree!(100, AmericanOption, Call, 95.0, 100.0, 0.5, 0.3, 0.05, 0.0)
+                .value()
+        );
+
+        let tian = eval_binomial_tree_with_lattice!(
+            Tian,
+            100,
+            AmericanOption,
+            Call,
+            95.0,
+            100.0,
+            0.5,
+            0.3,
+            0.05,
+            0.0
+        );
+        assert_reference_eq!(tian.value().0, 12.323953);
     }
 }
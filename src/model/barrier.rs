@@ -0,0 +1,132 @@
+//! Knock-in and knock-out barriers on the lattice.
+//!
+//! Nodes on or beyond a monitored barrier are worth the rebate (knock-out) or the
+//! knocked-in contract (knock-in). With continuous monitoring the lattice effectively moves
+//! the barrier to the nearest layer of nodes, which makes prices zig-zag as the number of
+//! steps changes. In the spirit of Derman and Kani, the tree is evaluated with the barrier
+//! on the layers just inside and just beyond it, and the node values are interpolated
+//! according to where the barrier lies between the two layers.
+
+use crate::Float;
+use crate::instruments::{Barrier, NodeTime};
+
+/// Applies the barrier to the value of a node.
+///
+/// * `value` - Value of the node while the barrier has not been crossed
+/// * `knocked_in_value` - Value of the knocked-in contract at the node, for knock-in options
+/// * `price` - The stock price at the node
+pub(crate) fn barrier_value(
+    barrier: &Barrier,
+    value: Float,
+    knocked_in_value: Option<Float>,
+    price: Float,
+    time: NodeTime,
+) -> Float {
+    if !barrier.is_monitored(time) || !barrier.is_crossed(price) {
+        return value;
+    }
+
+    match knocked_in_value {
+        Some(knocked_in_value) => knocked_in_value,
+        None => barrier.rebate,
+    }
+}
+
+/// Barrier moved to the lattice layers just inside and just beyond it.
+pub(crate) struct BarrierLayers {
+    pub(crate) inner: Barrier,
+    pub(crate) outer: Barrier,
+    /// Interpolation weight of the values with the outer barrier
+    pub(crate) outer_weight: Float,
+}
+
+impl BarrierLayers {
+    /// Finds the layers around the barrier of a lattice starting at `spot`.
+    ///
+    /// `layer_ratio` is the ratio between neighbouring layers, `u` for Cox-Ross-Rubinstein.
+    pub(crate) fn new(barrier: &Barrier, spot: Float, layer_ratio: Float) -> Self {
+        let layers = (barrier.level / spot).ln() / layer_ratio.ln();
+        // Each layered barrier sits halfway between two layers, so that the nodes on the
+        // layer itself are clearly crossed
+        let (inner, outer, half_step) = if barrier.is_up() {
+            let outer = spot * layer_ratio.powf(layers.ceil());
+            (outer / layer_ratio, outer, layer_ratio.sqrt().recip())
+        } else {
+            let outer = spot * layer_ratio.powf(layers.floor());
+            (outer * layer_ratio, outer, layer_ratio.sqrt())
+        };
+
+        Self {
+            inner: Barrier {
+                level: inner * half_step,
+                ..barrier.clone()
+            },
+            outer: Barrier {
+                level: outer * half_step,
+                ..barrier.clone()
+            },
+            outer_weight: (inner - barrier.level) / (inner - outer),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instruments::{BarrierKind, Monitoring};
+
+    fn barrier(kind: BarrierKind, monitoring: Monitoring) -> Barrier {
+        Barrier {
+            kind,
+            level: 90.0,
+            rebate: 1.0,
+            monitoring,
+        }
+    }
+
+    #[test]
+    fn test_barrier_value() {
+        let time = NodeTime {
+            time: 0.1,
+            time_step: 0.01,
+        };
+
+        let knock_out = barrier(BarrierKind::DownAndOut, Monitoring::Continuous);
+        assert_eq!(barrier_value(&knock_out, 5.0, None, 89.0, time), 1.0);
+        assert_eq!(barrier_value(&knock_out, 5.0, None, 91.0, time), 5.0);
+
+        let knock_in = barrier(BarrierKind::DownAndIn, Monitoring::Continuous);
+        assert_eq!(barrier_value(&knock_in, 5.0, Some(7.0), 89.0, time), 7.0);
+
+        let discrete = barrier(BarrierKind::DownAndOut, Monitoring::Discrete(vec![0.5]));
+        assert_eq!(barrier_value(&discrete, 5.0, None, 89.0, time), 5.0);
+    }
+
+    #[test]
+    fn test_barrier_layers() {
+        let down = BarrierLayers::new(
+            &barrier(BarrierKind::DownAndOut, Monitoring::Continuous),
+            100.0,
+            1.1,
+        );
+        // Layers at 100 / 1.1 and 100 / 1.1^2
+        assert!(down.inner.is_crossed(100.0 / 1.1) && !down.inner.is_crossed(100.0));
+        assert!(down.outer.is_crossed(100.0 / 1.21) && !down.outer.is_crossed(100.0 / 1.1));
+        assert!(
+            (down.outer_weight - (100.0 / 1.1 - 90.0) / (100.0 / 1.1 - 100.0 / 1.21)).abs() < 1e-5
+        );
+
+        let up = BarrierLayers::new(
+            &Barrier {
+                level: 115.0,
+                ..barrier(BarrierKind::UpAndIn, Monitoring::Continuous)
+            },
+            100.0,
+            1.1,
+        );
+        // Layers at 110 and 121
+        assert!(up.inner.is_crossed(110.0) && !up.inner.is_crossed(100.0));
+        assert!(up.outer.is_crossed(121.0) && !up.outer.is_crossed(110.0));
+        assert!((up.outer_weight - 5.0 / 11.0).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,339 @@
+//! Control-variate correction of tree values (Hull and White, 1988).
+//!
+//! The European counterpart of the option, without early exercise and barrier, is
+//! evaluated on the same lattice and node prices as the option. Its tree error, the
+//! difference between its Black-Scholes value and its tree value, is largely shared with
+//! the option and is added to the option's tree value. The greeks on the nodes are
+//! corrected node by node, vega, rho and psi by bump-and-revalue of the corrected value.
+//!
+//! Models built with `with_control_variate` carry the counterpart's tree along the
+//! backward induction of the option; otherwise it is evaluated when first needed. Each
+//! bumped revaluation evaluates the option and its counterpart in a single induction.
+
+use crate::Float;
+use crate::binomial_tree_map::nodes::NodeNameTrait;
+use crate::binomial_tree_map::{BinomialTreeMapImpl, BinomialTreeStackImpl};
+use crate::black_scholes::black_value;
+use crate::instruments::{NodeTime, OptionContract, OptionType};
+use crate::model::{
+    BinomialTreeModel, EvaluatedBinomialTreeModelImpl, FullGreeks, Greeks, Psi, Rho, Theta, Value,
+    Vega, border_truncation, lattice, leaf_smoothing,
+};
+
+type Node<Stack> =
+    <<Stack as BinomialTreeStackImpl>::NodeNameContainerType as BinomialTreeMapImpl>::NodeNameType;
+
+/// European counterpart of a contract, valued by its Black-Scholes value.
+///
+/// Barrier options count as their underlying option, calls and puts getting the vanilla
+/// value rather than the closed-form barrier value.
+pub(crate) struct European<'a, T>(pub(crate) &'a T);
+
+impl<T: OptionContract> OptionContract for European<'_, T> {
+    fn expiry(&self) -> Float {
+        self.0.expiry()
+    }
+
+    fn strike(&self) -> Option<Float> {
+        self.0.strike()
+    }
+
+    fn value(&self, value: Float, _: Float, _: NodeTime) -> Float {
+        value
+    }
+
+    fn intrinsic_value(&self, price: Float) -> Float {
+        self.0.intrinsic_value(price)
+    }
+
+    fn black_value(
+        &self,
+        spot: Float,
+        volatility: Float,
+        rate: Float,
+        dividends: Float,
+        expiry: Float,
+    ) -> Float {
+        match (self.0.barrier(), self.0.vanilla()) {
+            (Some(_), Some((option_type, strike))) => black_value(
+                option_type,
+                spot,
+                strike,
+                volatility,
+                rate,
+                dividends,
+                expiry,
+            ),
+            _ => self
+                .0
+                .black_value(spot, volatility, rate, dividends, expiry),
+        }
+    }
+
+    fn vanilla(&self) -> Option<(OptionType, Float)> {
+        self.0.vanilla()
+    }
+
+    fn has_analytic_value(&self) -> bool {
+        (self.0.barrier().is_some() && self.0.vanilla().is_some()) || self.0.has_analytic_value()
+    }
+}
+
+#[allow(private_bounds)]
+impl<
+    Stack: BinomialTreeStackImpl,
+    V: leaf_smoothing::ValueAtLeaf,
+    U: border_truncation::ValueAtBorder,
+    L: lattice::Lattice,
+    T: OptionContract + Sync,
+> EvaluatedBinomialTreeModelImpl<Stack, V, U, L, T>
+{
+    /// Value corrected with the European counterpart of the option as control variate.
+    ///
+    /// European contracts get their Black-Scholes value.
+    pub fn control_variate_value(&self) -> Value {
+        Value(
+            self.with_european(|european| {
+                self.corrected_value(european, &Node::<Stack>::default(), 0)
+            }),
+        )
+    }
+
+    /// Greeks corrected with the European counterpart of the option as control variate,
+    /// see [`Self::control_variate_value`].
+    pub fn control_variate_greeks(&self) -> Greeks {
+        self.with_european(|european| {
+            let value = |node: &Node<Stack>, step| self.corrected_value(european, node, step);
+            let initial_node = Node::<Stack>::default();
+
+            // Two steps later at the same price, as in `Self::theta`
+            let next_step =
+                |map: &Stack::NodeNameContainerType| map.next_step_value(&initial_node).unwrap();
+            let tree_spot = self.model.tree_spot();
+            let next_step_price = self.model.params.calculate_price(tree_spot, 1, 1);
+            let value_2 = next_step(&self.map) - next_step(european)
+                + self.black_value_at(next_step_price, 2);
+            let value_0 = value(&initial_node, 0);
+
+            Greeks {
+                value: Value(value_0),
+                delta: self.delta_from(&initial_node, 0, &value),
+                gamma: self.gamma_of(&value),
+                theta: Theta((value_2 - value_0) / (2.0 * self.model.time_step)),
+            }
+        })
+    }
+
+    /// Corrected greeks together with vega, rho and psi of the corrected value, see
+    /// [`Self::control_variate_greeks`].
+    pub fn control_variate_full_greeks(&self) -> FullGreeks {
+        let greeks = self.control_variate_greeks();
+        let params = &self.model.params;
+        let bumps = &self.model.bumps;
+        let derivative = |x, h, value_at: &dyn Fn(Float) -> Float| {
+            bumps.difference.derivative(x, h, greeks.value.0, value_at)
+        };
+        let vega = Vega(derivative(
+            params.volatility,
+            bumps.volatility,
+            &|volatility| {
+                self.revalue_control_variate(volatility, params.interest_rate, params.dividends)
+            },
+        ));
+        let rho = Rho(derivative(
+            params.interest_rate,
+            bumps.interest_rate,
+            &|rate| self.revalue_control_variate(params.volatility, rate, params.dividends),
+        ));
+        let psi = Psi(derivative(
+            params.dividends,
+            bumps.dividends,
+            &|dividends| {
+                self.revalue_control_variate(params.volatility, params.interest_rate, dividends)
+            },
+        ));
+        FullGreeks::new(greeks, vega, rho, psi)
+    }
+
+    /// Runs `f` with the tree of the European counterpart, as carried along by models
+    /// [with the control variate](BinomialTreeModel::with_control_variate), or else
+    /// evaluated with a map of the workspace.
+    fn with_european<R>(&self, f: impl FnOnce(&Stack::NodeNameContainerType) -> R) -> R {
+        if let Some(european) = &self.european {
+            return f(european);
+        }
+
+        let european = self.model.backward_induction(
+            &European(&self.option),
+            &self.model.node_prices(),
+            &mut self.workspace.borrow_mut(),
+        );
+        let result = f(&european);
+        self.workspace.borrow_mut().give(european);
+        result
+    }
+
+    /// Value of `node` on `step`, corrected with the `european` tree.
+    fn corrected_value(
+        &self,
+        european: &Stack::NodeNameContainerType,
+        node: &Node<Stack>,
+        step: usize,
+    ) -> Float {
+        let params = &self.model.params;
+        // The European value of the escrowed-dividend model is on the price without the
+        // cash dividends still to be paid
+        let price = node.value(self.model.tree_spot(), params.u, params.d);
+
+        self.node_value(node) - european.value(node).unwrap() + self.black_value_at(price, step)
+    }
+
+    /// Black-Scholes value of the European counterpart at `price` on `step`.
+    fn black_value_at(&self, price: Float, step: usize) -> Float {
+        let params = &self.model.params;
+        European(&self.option).black_value(
+            price,
+            params.volatility,
+            params.interest_rate,
+            params.dividends,
+            self.model.expiry.0 - self.model.time_step * step as Float,
+        )
+    }
+
+    /// Control-variate value with bumped market parameters.
+    fn revalue_control_variate(
+        &self,
+        volatility: Float,
+        interest_rate: Float,
+        dividends: Float,
+    ) -> Float {
+        let model: BinomialTreeModel<&Stack, V, U, L> =
+            self.bumped_model(volatility, interest_rate, dividends);
+        let black_value = European(&self.option).black_value(
+            model.tree_spot(),
+            volatility,
+            interest_rate,
+            dividends,
+            model.expiry.0,
+        );
+
+        // One induction carries the European tree along with the option
+        let mut workspace = self.workspace.borrow_mut();
+        let (tree_map, european) = model.backward_induction_with_european(
+            &self.option,
+            &model.node_prices(),
+            &mut workspace,
+        );
+        let initial_node = Node::<Stack>::default();
+        let value = tree_map.value(&initial_node).unwrap() - european.value(&initial_node).unwrap()
+            + black_value;
+
+        workspace.give(tree_map);
+        workspace.give(european);
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::binomial_tree_map::array::ArrayBinomialTreeMap;
+    use crate::black_scholes::{black_greeks, black_value};
+    use crate::instruments::{
+        AmericanOption, Barrier, BarrierKind, BarrierOption, EuropeanOption, Monitoring,
+        OptionType, StrikeContract,
+    };
+    use crate::model::{CoxRossRubenstein, EvaluatedBinomialTree, Expiry, Spot};
+    #[cfg(test)]
+    use pretty_assertions::assert_eq;
+
+    fn model(steps: usize) -> CoxRossRubenstein<ArrayBinomialTreeMap> {
+        CoxRossRubenstein::new(
+            ArrayBinomialTreeMap::new(steps),
+            Spot(100.0),
+            steps,
+            Expiry(0.5),
+            0.3,
+            0.05,
+            0.02,
+        )
+    }
+
+    #[test]
+    fn test_control_variate_european() {
+        let eval = model(100).eval(EuropeanOption::new(OptionType::Put, 105.0, 0.5));
+        let black = black_greeks(OptionType::Put, 100.0, 105.0, 0.3, 0.05, 0.02, 0.5);
+
+        // The tree values of the option and its counterpart cancel exactly
+        assert_eq!(
+            EvaluatedBinomialTree::control_variate_value(&eval).0,
+            black_value(OptionType::Put, 100.0, 105.0, 0.3, 0.05, 0.02, 0.5)
+        );
+
+        let greeks = eval.control_variate_full_greeks();
+        assert_eq!(greeks.value.0, black.value);
+        assert!((greeks.delta.0 - black.delta).abs() < 1e-3);
+        assert!((greeks.gamma.0 - black.gamma).abs() < 2e-4);
+        assert!((greeks.theta.0 - black.theta).abs() < 0.1);
+        assert!((greeks.vega.0 - black.vega).abs() < 1e-2);
+        assert!((greeks.rho.0 - black.rho).abs() < 1e-2);
+        assert!((greeks.psi.0 - black.psi).abs() < 1e-2);
+    }
+
+    #[test]
+    fn test_control_variate_american_put() {
+        let put = AmericanOption::new(OptionType::Put, 105.0, 0.5);
+        // Average of an even and odd number of steps, to cancel most of the oscillation
+        let reference = model(1000).eval(put);
+        let reference_value = (reference.value().0 + model(1001).eval(put).value().0) / 2.0;
+
+        let (mut tree_error, mut control_variate_error) = (0.0, 0.0);
+        for steps in 100..=105 {
+            let eval = model(steps).eval(put);
+            tree_error += (eval.value().0 - reference_value).abs();
+            control_variate_error += (eval.control_variate_value().0 - reference_value).abs();
+        }
+        assert!(control_variate_error < tree_error / 3.0);
+
+        let greeks = model(100).eval(put).control_variate_full_greeks();
+        let reference = reference.full_greeks();
+        assert!((greeks.delta.0 - reference.delta.0).abs() < 2e-3);
+        assert!((greeks.gamma.0 - reference.gamma.0).abs() < 3e-4);
+        assert!((greeks.theta.0 - reference.theta.0).abs() < 0.1);
+        assert!((greeks.vega.0 - reference.vega.0).abs() < 0.3);
+    }
+
+    #[test]
+    fn test_control_variate_carried_european() {
+        let put = AmericanOption::new(OptionType::Put, 105.0, 0.5);
+        let down_and_in = BarrierOption::with_barrier(
+            put,
+            Barrier {
+                kind: BarrierKind::DownAndIn,
+                level: 90.0,
+                rebate: 0.0,
+                monitoring: Monitoring::Continuous,
+            },
+        );
+
+        // Carrying the counterpart along the induction gives the same tree as evaluating it
+        let eval = model(100).eval(put);
+        let carried = model(100).with_control_variate().eval(put);
+        assert_eq!(carried.value(), eval.value());
+        assert_eq!(
+            carried.control_variate_greeks(),
+            eval.control_variate_greeks()
+        );
+        assert_eq!(
+            carried.control_variate_full_greeks(),
+            eval.control_variate_full_greeks()
+        );
+
+        let eval = model(100).eval(down_and_in.clone());
+        let carried = model(100).with_control_variate().eval(down_and_in);
+        assert_eq!(carried.value(), eval.value());
+        assert_eq!(
+            carried.control_variate_value(),
+            eval.control_variate_value()
+        );
+    }
+}
@@ -0,0 +1,74 @@
+//! Discrete cash dividends for the escrowed-dividend model.
+//!
+//! The lattice is built on the spot net of the present value of the dividends paid until
+//! expiry. Every node adds back the present value of the dividends still to be paid, so
+//! exercise decisions see the cum-dividend stock price right up to each ex-date.
+
+use crate::Float;
+
+/// A cash dividend paid on the ex-date, given in years from today.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CashDividend {
+    pub ex_date: Float,
+    pub amount: Float,
+}
+
+/// Known cash dividends of the underlying.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DividendSchedule {
+    dividends: Vec<CashDividend>,
+}
+
+impl DividendSchedule {
+    pub fn new(dividends: Vec<CashDividend>) -> Self {
+        Self { dividends }
+    }
+
+    /// Present value at `time` of the dividends going ex after `time` and until `expiry`.
+    pub(crate) fn present_value(&self, time: Float, interest_rate: Float, expiry: Float) -> Float {
+        self.dividends
+            .iter()
+            .filter(|dividend| time < dividend.ex_date && dividend.ex_date <= expiry)
+            .map(|dividend| dividend.amount * (-interest_rate * (dividend.ex_date - time)).exp())
+            .sum()
+    }
+}
+
+impl FromIterator<CashDividend> for DividendSchedule {
+    fn from_iter<I: IntoIterator<Item = CashDividend>>(iter: I) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_present_value() {
+        let schedule = DividendSchedule::new(vec![
+            CashDividend {
+                ex_date: 0.25,
+                amount: 1.0,
+            },
+            CashDividend {
+                ex_date: 0.75,
+                amount: 2.0,
+            },
+        ]);
+
+        assert_eq!(schedule.present_value(0.0, 0.0, 1.0), 3.0);
+        // Already gone ex
+        assert_eq!(schedule.present_value(0.25, 0.0, 1.0), 2.0);
+        // Paid after expiry
+        assert_eq!(schedule.present_value(0.0, 0.0, 0.5), 1.0);
+        assert_eq!(
+            schedule.present_value(0.5, 0.04, 1.0),
+            2.0 * (-0.04 * 0.25 as Float).exp()
+        );
+        assert_eq!(
+            DividendSchedule::default().present_value(0.0, 0.05, 1.0),
+            0.0
+        );
+    }
+}
@@ -0,0 +1,144 @@
+//! Value of a node in the backward induction, shared by the tree models.
+//!
+//! The models differ in how they lay out and look up the nodes, and in how many children a
+//! node has, but not in what a node is worth once the values of its children are known.
+
+use crate::Float;
+use crate::instruments::{Barrier, NodeTime, OptionContract};
+use crate::model::barrier::barrier_value;
+use crate::model::{VolatilityParameters, border_truncation, leaf_smoothing};
+
+use std::marker::PhantomData;
+
+/// Values of the children of a node, as far as the tree reaches them.
+pub(crate) enum Children {
+    /// Discounted expectation of the values of the children, which all have one
+    Value(Float),
+    /// Border truncation left some of the children without a value
+    Partial,
+    /// None of the children have a value, as at the leaves
+    Empty,
+}
+
+impl Children {
+    /// Children with the `values` of the next level, with `expectation` their discounted
+    /// expectation when all of them have a value.
+    pub(crate) fn of<const N: usize>(
+        values: [Option<Float>; N],
+        expectation: impl FnOnce([Float; N]) -> Float,
+    ) -> Self {
+        if values.iter().all(Option::is_some) {
+            Children::Value(expectation(values.map(Option::unwrap)))
+        } else if values.iter().any(Option::is_some) {
+            Children::Partial
+        } else {
+            Children::Empty
+        }
+    }
+}
+
+/// Level of the tree being evaluated.
+pub(crate) struct Level {
+    /// Time left to the expiry of the option
+    pub(crate) time_to_expiry: Float,
+    pub(crate) time: NodeTime,
+    /// The last level of the tree, where the nodes get the value at the leaves
+    pub(crate) leaves: bool,
+    /// The level one step before the leaves
+    pub(crate) before_leaves: bool,
+}
+
+/// Evaluates the nodes of a tree for an option, with an optional barrier.
+pub(crate) struct Induction<'a, T, V, U> {
+    option: &'a T,
+    params: &'a VolatilityParameters,
+    truncation: U,
+    barrier: Option<&'a Barrier>,
+    /// Whether the strategies may use the analytical values of the option
+    analytic: bool,
+    _phantom_data: PhantomData<V>,
+}
+
+impl<
+    'a,
+    T: OptionContract + Sync,
+    V: leaf_smoothing::ValueAtLeaf,
+    U: border_truncation::ValueAtBorder,
+> Induction<'a, T, V, U>
+{
+    /// Induction over a tree starting at `spot`, for an option expiring at `expiry`.
+    pub(crate) fn new(
+        option: &'a T,
+        params: &'a VolatilityParameters,
+        spot: Float,
+        expiry: Float,
+        barrier: Option<&'a Barrier>,
+    ) -> Self {
+        Self {
+            option,
+            params,
+            truncation: U::new(
+                spot,
+                expiry,
+                params.volatility,
+                params.interest_rate,
+                params.dividends,
+            ),
+            barrier,
+            analytic: option.has_analytic_value(),
+            _phantom_data: PhantomData,
+        }
+    }
+
+    /// Value of a node at `price` on `level`, or `None` for nodes left out of the tree.
+    ///
+    /// `knocked_in_value` is the value of the knocked-in contract at the node, for knock-in
+    /// options. These are not alive before knocking in, so there is nothing to exercise.
+    /// Options without analytical values skip the smoothing and truncation strategies.
+    pub(crate) fn node_value(
+        &self,
+        level: &Level,
+        price: Float,
+        children: Children,
+        knocked_in_value: Option<Float>,
+    ) -> Option<Float> {
+        let (option, params) = (self.option, self.params);
+        let value = match children {
+            Children::Value(value) if knocked_in_value.is_some() => Some(value),
+            Children::Value(value) if level.before_leaves && self.analytic => {
+                Some(V::value_before_expiry(
+                    option,
+                    value,
+                    price,
+                    params,
+                    level.time_to_expiry,
+                    level.time,
+                ))
+            }
+            Children::Value(value) => Some(option.value(value, price, level.time)),
+            Children::Partial if !self.analytic => Some(option.value(0.0, price, level.time)),
+            Children::Partial => {
+                self.truncation
+                    .value(option, 0.0, price, params, level.time_to_expiry, level.time)
+            }
+            Children::Empty if !level.leaves => None,
+            Children::Empty => match (self.barrier, knocked_in_value) {
+                // Expires without knocking in
+                (Some(barrier), Some(_)) => Some(barrier.rebate),
+                _ if !self.analytic => Some(option.intrinsic_value(price)),
+                _ => Some(V::value_at_leaf(
+                    option,
+                    price,
+                    params,
+                    level.time_to_expiry,
+                    level.time,
+                )),
+            },
+        };
+
+        value.map(|value| match self.barrier {
+            Some(barrier) => barrier_value(barrier, value, knocked_in_value, price, level.time),
+            None => value,
+        })
+    }
+}
@@ -1,56 +1,242 @@
+use crate::Float;
 use crate::binomial_tree_map::nodes::NodeNameTrait;
-use crate::binomial_tree_map::{BinomialTreeMapImpl, BinomialTreeStackImpl, GetValue};
-use crate::instruments::OptionContract;
-
+use crate::binomial_tree_map::{
+    BinomialTreeMapImpl, BinomialTreeStackBuilder, BinomialTreeStackImpl,
+};
+use crate::error::{Error, positive};
+use crate::instruments::{Barrier, Monitoring, NodeTime, OptionContract};
+use barrier::BarrierLayers;
+use control_variate::European;
+use induction::{Children, Induction, Level};
+
+use rayon::prelude::*;
+use std::cell::RefCell;
 use std::fmt;
 use std::marker::PhantomData;
 
+/// Knock-in and knock-out barriers with the Derman-Kani correction
+mod barrier;
+/// Control-variate correction with the European counterpart of the option
+mod control_variate;
+/// Discrete cash dividends handled with the escrowed-dividend model
+pub mod dividends;
+/// Node values of the backward induction shared by the tree models
+mod induction;
+/// Richardson extrapolation of the values and greeks of two trees
+pub mod richardson;
+/// Evaluation keeping two tree levels in memory instead of the whole tree
+pub mod rolling;
+/// Bump-and-revalue settings for vega, rho and psi
+pub mod sensitivities;
 /// Strategy-based modules for binomial tree evaluation
 pub mod strategies;
+/// Trinomial tree model sharing the strategies and greeks of the binomial models
+pub mod trinomial;
+/// Node value maps reused between evaluations
+pub mod workspace;
 
 // Re-export strategy traits and marker types for convenience
 pub use strategies::border_truncation;
+pub use strategies::lattice;
 pub use strategies::leaf_smoothing;
 
-pub struct CoxRossRubenstein<Stack, V = leaf_smoothing::None, U = border_truncation::None> {
+pub use dividends::{CashDividend, DividendSchedule};
+pub use rolling::RollingLevels;
+pub use sensitivities::{Bumps, FiniteDifference};
+use workspace::MapPool;
+pub use workspace::Workspace;
+
+/// Fewest steps of a binomial tree, as gamma and theta look two steps ahead.
+const MIN_STEPS: usize = 2;
+
+/// Binomial tree model using the Cox-Ross-Rubinstein parametrisation.
+pub type CoxRossRubenstein<Stack, V = leaf_smoothing::None, U = border_truncation::None> =
+    BinomialTreeModel<Stack, V, U, lattice::CoxRossRubenstein>;
+
+/// Binomial tree model using the equal-probability Jarrow-Rudd parametrisation.
+pub type JarrowRudd<Stack, V = leaf_smoothing::None, U = border_truncation::None> =
+    BinomialTreeModel<Stack, V, U, lattice::JarrowRudd>;
+
+/// Binomial tree model using the moment-matching Tian parametrisation.
+pub type Tian<Stack, V = leaf_smoothing::None, U = border_truncation::None> =
+    BinomialTreeModel<Stack, V, U, lattice::Tian>;
+
+/// Binomial tree model using the strike-centred Leisen-Reimer parametrisation, on an odd
+/// number of steps.
+pub type LeisenReimer<Stack, V = leaf_smoothing::None, U = border_truncation::None> =
+    BinomialTreeModel<Stack, V, U, lattice::LeisenReimer>;
+
+/// Binomial tree model, generic over node storage, leaf smoothing, border truncation
+/// and the lattice parametrisation of the up/down factors.
+pub struct BinomialTreeModel<
+    Stack,
+    V = leaf_smoothing::None,
+    U = border_truncation::None,
+    L = lattice::CoxRossRubenstein,
+> {
     stack: Stack,
     params: VolatilityParameters,
     spot: Spot,
     expiry: Expiry,
-    discount_factor: f32,
-    time_step: f32,
+    number_of_steps: usize,
+    discount_factor: Float,
+    time_step: Float,
+    bumps: Bumps,
+    cash_dividends: DividendSchedule,
+    /// Time of the initial node from the valuation date, negative for extended trees
+    start_time: Float,
+    /// Levels with at least this many nodes are evaluated in parallel
+    parallel_width: usize,
+    /// Whether the evaluation carries the tree of the European counterpart along
+    control_variate: bool,
     _phantom_data: PhantomData<V>,
     _phantom_data2: PhantomData<U>,
+    _phantom_data3: PhantomData<L>,
 }
 
-#[allow(private_bounds)]
-impl<
-    Stack: BinomialTreeStackImpl,
-    V: leaf_smoothing::ValueAtLeaf,
-    U: border_truncation::ValueAtBorder,
-> CoxRossRubenstein<Stack, V, U>
-{
+impl<Stack, V, U, L: lattice::Lattice> BinomialTreeModel<Stack, V, U, L> {
     pub fn new(
         stack: Stack,
         initial_price: Spot,
         number_of_steps: usize,
         expiry: Expiry,
-        volatility: f32,
-        interest_rate: f32,
-        dividends: f32,
+        volatility: Float,
+        interest_rate: Float,
+        dividends: Float,
     ) -> Self {
-        let time_step = expiry.0 / number_of_steps as f32;
-        let vol_params = VolatilityParameters::new(volatility, interest_rate, dividends, time_step);
+        let time_step = expiry.0 / number_of_steps as Float;
+        // Strike-dependent lattices are re-centred on the option's strike in `eval`
+        let vol_params = L::parameters(
+            initial_price.0,
+            initial_price.0,
+            number_of_steps,
+            expiry.0,
+            volatility,
+            interest_rate,
+            dividends,
+        );
 
         Self {
             stack,
             params: vol_params,
             spot: initial_price,
             expiry,
+            number_of_steps,
             discount_factor: (-interest_rate * time_step).exp(),
             time_step,
+            bumps: Bumps::default(),
+            cash_dividends: DividendSchedule::default(),
+            start_time: 0.0,
+            parallel_width: usize::MAX,
+            control_variate: false,
             _phantom_data: Default::default(),
             _phantom_data2: Default::default(),
+            _phantom_data3: Default::default(),
+        }
+    }
+
+    /// Model with validated inputs, see [`Self::new`].
+    ///
+    /// Rejects a non-positive spot, expiry or volatility, trees with fewer than the two steps
+    /// of gamma and theta or with a number of steps the lattice is not defined for, and
+    /// lattices whose up probability lies outside (0, 1). Strike-dependent lattices are
+    /// checked again for the strike of the option in
+    /// [`Self::try_eval`](BinomialTreeModel::try_eval), as is the node stack.
+    pub fn try_new(
+        stack: Stack,
+        initial_price: Spot,
+        number_of_steps: usize,
+        expiry: Expiry,
+        volatility: Float,
+        interest_rate: Float,
+        dividends: Float,
+    ) -> Result<Self, Error> {
+        positive(initial_price.0, Error::NonPositiveSpot)?;
+        positive(expiry.0, Error::NonPositiveExpiry)?;
+        positive(volatility, Error::NonPositiveVolatility)?;
+        if number_of_steps < MIN_STEPS {
+            return Err(Error::TooFewSteps {
+                steps: number_of_steps,
+                minimum: MIN_STEPS,
+            });
+        }
+        L::validate_steps(number_of_steps)?;
+
+        let model = Self::new(
+            stack,
+            initial_price,
+            number_of_steps,
+            expiry,
+            volatility,
+            interest_rate,
+            dividends,
+        );
+        model.params.validate()?;
+        Ok(model)
+    }
+
+    /// Sets the bump sizes used for vega, rho and psi of the evaluated tree.
+    ///
+    /// They have to be positive, which [`Self::try_eval`](BinomialTreeModel::try_eval)
+    /// checks.
+    pub fn with_bumps(mut self, bumps: Bumps) -> Self {
+        self.bumps = bumps;
+        self
+    }
+
+    /// Sets the cash dividends paid until expiry, on top of the continuous dividend yield.
+    ///
+    /// Their present value has to stay below the spot, which
+    /// [`Self::try_eval`](BinomialTreeModel::try_eval) checks.
+    pub fn with_cash_dividends(mut self, cash_dividends: DividendSchedule) -> Self {
+        self.cash_dividends = cash_dividends;
+        self
+    }
+
+    /// Evaluates the levels with at least `min_width` nodes in parallel on the rayon thread
+    /// pool, with the same results as the serial evaluation.
+    ///
+    /// Splitting a level only pays off for wide levels, i.e. trees with thousands of steps.
+    pub fn with_parallel_levels(mut self, min_width: usize) -> Self {
+        self.parallel_width = min_width;
+        self
+    }
+
+    /// Evaluates the European counterpart of the option in the same backward induction,
+    /// for the control-variate values and greeks of the evaluated tree.
+    ///
+    /// Without it, the control-variate methods evaluate the counterpart on a tree of its
+    /// own.
+    pub fn with_control_variate(mut self) -> Self {
+        self.control_variate = true;
+        self
+    }
+
+    /// Spot of the lattice, net of the present value of the cash dividends.
+    fn tree_spot(&self) -> Float {
+        self.spot.0 - self.dividends_at(0)
+    }
+
+    /// Checks that the cash dividends leave a positive spot for the lattice.
+    fn validate_tree_spot(&self) -> Result<(), Error> {
+        positive(self.tree_spot(), Error::NonPositiveNetSpot)?;
+        Ok(())
+    }
+
+    /// Present value at the given step of the cash dividends still to be paid.
+    fn dividends_at(&self, step: usize) -> Float {
+        self.cash_dividends.present_value(
+            self.time_at(step).time,
+            self.params.interest_rate,
+            self.start_time + self.expiry.0,
+        )
+    }
+
+    /// Time of the given step from the valuation date.
+    fn time_at(&self, step: usize) -> NodeTime {
+        NodeTime {
+            time: self.start_time + self.time_step * step as Float,
+            time_step: self.time_step,
         }
     }
 
@@ -65,80 +251,421 @@ impl<
     ///
     /// # Returns
     /// The discounted expected value: `(up_value * p + down_value * (1 - p)) * discount_factor`
-    fn calculate_node_value(&self, up_value: f32, down_value: f32) -> f32 {
+    fn calculate_node_value(&self, up_value: Float, down_value: Float) -> Float {
         let p = self.params.p();
         (up_value * p + down_value * (1.0 - p)) * self.discount_factor
     }
 
-    pub fn eval<T: OptionContract + Sync>(
-        self,
-        option: T,
-    ) -> EvaluatedBinomialTreeModelImpl<Stack, V, U> {
-        let mut tree_map = <Stack as BinomialTreeStackImpl>::NodeNameContainerType::default();
-        let truncation = U::new(
-            self.spot.0,
+    /// Checks the strike of the option and the lattice centred on it.
+    fn validate_strike(&self, strike: Option<Float>) -> Result<(), Error> {
+        if let Some(strike) = strike {
+            positive(strike, Error::NonPositiveStrike)?;
+        }
+
+        self.centred_parameters(strike).validate()
+    }
+
+    /// Centres strike-dependent lattices on the strike of the option being evaluated, or on
+    /// the spot for payoffs without a single strike.
+    fn set_strike(&mut self, strike: Option<Float>) {
+        self.params = self.centred_parameters(strike);
+    }
+
+    fn centred_parameters(&self, strike: Option<Float>) -> VolatilityParameters {
+        let tree_spot = self.tree_spot();
+        L::parameters(
+            tree_spot,
+            strike.unwrap_or(tree_spot),
+            self.number_of_steps,
             self.expiry.0,
             self.params.volatility,
             self.params.interest_rate,
             self.params.dividends,
+        )
+    }
+}
+
+#[allow(private_bounds)]
+impl<
+    Stack: BinomialTreeStackImpl,
+    V: leaf_smoothing::ValueAtLeaf,
+    U: border_truncation::ValueAtBorder,
+    L: lattice::Lattice,
+> BinomialTreeModel<Stack, V, U, L>
+{
+    pub fn eval<T: OptionContract + Sync>(
+        self,
+        option: T,
+    ) -> EvaluatedBinomialTreeModelImpl<Stack, V, U, L, T> {
+        self.eval_in(option, &mut Workspace::default())
+    }
+
+    /// Evaluates the option after checking its strike and, for strike-dependent lattices,
+    /// the lattice centred on it, see [`Self::try_new`](BinomialTreeModel::try_new). Also
+    /// checks that the node stack has the steps of the model, as the tree has the levels
+    /// of the stack, and the state set through the builders: that the cash dividends leave
+    /// a positive spot for the lattice and that the bump sizes are positive.
+    pub fn try_eval<T: OptionContract + Sync>(
+        self,
+        option: T,
+    ) -> Result<EvaluatedBinomialTreeModelImpl<Stack, V, U, L, T>, Error> {
+        self.validate_eval(&option)?;
+        Ok(self.eval(option))
+    }
+
+    /// Evaluates the option with the node value maps of `workspace`, after the checks of
+    /// [`Self::try_eval`].
+    pub fn try_eval_in<T: OptionContract + Sync>(
+        self,
+        option: T,
+        workspace: &mut Workspace<Stack>,
+    ) -> Result<EvaluatedBinomialTreeModelImpl<Stack, V, U, L, T>, Error> {
+        self.validate_eval(&option)?;
+        Ok(self.eval_in(option, workspace))
+    }
+
+    fn validate_eval<T: OptionContract>(&self, option: &T) -> Result<(), Error> {
+        let stack_steps = self.stack.iter().len().saturating_sub(1);
+        if stack_steps != self.number_of_steps {
+            return Err(Error::StackSteps {
+                stack_steps,
+                number_of_steps: self.number_of_steps,
+            });
+        }
+        self.validate_tree_spot()?;
+        self.bumps.validate(self.params.volatility)?;
+        self.validate_strike(option.strike())
+    }
+
+    /// Evaluates the option with the node value maps of `workspace`.
+    ///
+    /// The maps move into the evaluated tree, which also re-evaluates the bumped trees of
+    /// the greeks with them, until [`EvaluatedBinomialTreeModelImpl::recycle`] gives them
+    /// back to a workspace.
+    pub fn eval_in<T: OptionContract + Sync>(
+        mut self,
+        option: T,
+        workspace: &mut Workspace<Stack>,
+    ) -> EvaluatedBinomialTreeModelImpl<Stack, V, U, L, T> {
+        let mut workspace = std::mem::take(&mut workspace.maps);
+        self.set_strike(option.strike());
+        let (tree_map, european) = self.backward_inductions(
+            &option,
+            &self.node_prices(),
+            &mut workspace,
+            self.control_variate,
         );
 
-        let mut first_level = true;
-        for (i, node_level) in self.stack.iter().enumerate().rev() {
-            let current_expiry = self.expiry.0 - self.time_step * (i as f32); // Is the last step 0 or 1 timestep to expiry?
+        EvaluatedBinomialTreeModelImpl {
+            model: self,
+            map: tree_map,
+            european,
+            option,
+            workspace: RefCell::new(workspace),
+        }
+    }
 
-            node_level.iter().rev().enumerate().for_each(|(j, node)| {
-                let up_value = tree_map.get(&node.up());
-                let down_value = tree_map.get(&node.down());
+    /// Evaluates several contracts on the same model, e.g. a chain of strikes.
+    ///
+    /// The contracts share the node stack, and the node prices are computed once for all
+    /// contracts on the same lattice. Strike-dependent lattices are still centred on the
+    /// strike of each contract, as in [`Self::eval`].
+    pub fn eval_portfolio<T: OptionContract + Sync>(
+        &self,
+        options: impl IntoIterator<Item = T>,
+    ) -> Vec<EvaluatedBinomialTreeModelImpl<&Stack, V, U, L, T>> {
+        let mut lattices: Vec<(VolatilityParameters, Vec<Vec<Float>>)> = Vec::new();
+        let mut workspace = MapPool::default();
+
+        options
+            .into_iter()
+            .map(|option| {
+                let mut model = self.by_ref();
+                model.set_strike(option.strike());
+
+                let lattice = match lattices
+                    .iter()
+                    .position(|(params, _)| *params == model.params)
+                {
+                    Some(lattice) => lattice,
+                    None => {
+                        lattices.push((model.params, model.node_prices()));
+                        lattices.len() - 1
+                    }
+                };
+                let (tree_map, european) = model.backward_inductions(
+                    &option,
+                    &lattices[lattice].1,
+                    &mut workspace,
+                    model.control_variate,
+                );
+
+                EvaluatedBinomialTreeModelImpl {
+                    model,
+                    map: tree_map,
+                    european,
+                    option,
+                    workspace: RefCell::default(),
+                }
+            })
+            .collect()
+    }
+
+    /// Copy of the model sharing its node stack.
+    fn by_ref(&self) -> BinomialTreeModel<&Stack, V, U, L> {
+        BinomialTreeModel {
+            stack: &self.stack,
+            params: self.params,
+            spot: Spot(self.spot.0),
+            expiry: Expiry(self.expiry.0),
+            number_of_steps: self.number_of_steps,
+            discount_factor: self.discount_factor,
+            time_step: self.time_step,
+            bumps: self.bumps,
+            cash_dividends: self.cash_dividends.clone(),
+            start_time: self.start_time,
+            parallel_width: self.parallel_width,
+            control_variate: self.control_variate,
+            _phantom_data: PhantomData,
+            _phantom_data2: PhantomData,
+            _phantom_data3: PhantomData,
+        }
+    }
+
+    /// Prices of the nodes on each level, indexed by their number of up steps.
+    fn node_prices(&self) -> Vec<Vec<Float>> {
+        let tree_spot = self.tree_spot();
+
+        self.stack
+            .iter()
+            .enumerate()
+            .map(|(i, node_level)| {
+                let dividends = self.dividends_at(i);
+                (0..node_level.len())
+                    .map(|j| self.params.calculate_price(tree_spot, j, i - j) + dividends)
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Evaluates the option backwards from the leaves to the initial node.
+    ///
+    /// Knock-in options first evaluate the knocked-in contract, which they turn into on
+    /// crossing the barrier. Continuously monitored barriers interpolate between the trees
+    /// with the barrier on the lattice layers either side of it.
+    ///
+    /// The maps of the intermediate trees are given back to `workspace`.
+    fn backward_induction<T: OptionContract + Sync>(
+        &self,
+        option: &T,
+        prices: &[Vec<Float>],
+        workspace: &mut MapPool<<Stack as BinomialTreeStackImpl>::NodeNameContainerType>,
+    ) -> <Stack as BinomialTreeStackImpl>::NodeNameContainerType {
+        self.backward_inductions(option, prices, workspace, false).0
+    }
 
-                let price = self.params.calculate_price(self.spot.0, j, i - j);
+    /// As [`Self::backward_induction`], also giving the tree of the European counterpart
+    /// of the option, which the first of the inductions carries along.
+    fn backward_induction_with_european<T: OptionContract + Sync>(
+        &self,
+        option: &T,
+        prices: &[Vec<Float>],
+        workspace: &mut MapPool<<Stack as BinomialTreeStackImpl>::NodeNameContainerType>,
+    ) -> (
+        <Stack as BinomialTreeStackImpl>::NodeNameContainerType,
+        <Stack as BinomialTreeStackImpl>::NodeNameContainerType,
+    ) {
+        let (tree_map, european) = self.backward_inductions(option, prices, workspace, true);
+        (
+            tree_map,
+            european.expect("The first induction carries the European tree"),
+        )
+    }
 
-                //println!("{:?}{:?}", node.up(), up_value);
-                //println!("{:?}{:?}", node.down(), down_value);
+    fn backward_inductions<T: OptionContract + Sync>(
+        &self,
+        option: &T,
+        prices: &[Vec<Float>],
+        workspace: &mut MapPool<<Stack as BinomialTreeStackImpl>::NodeNameContainerType>,
+        with_european: bool,
+    ) -> (
+        <Stack as BinomialTreeStackImpl>::NodeNameContainerType,
+        Option<<Stack as BinomialTreeStackImpl>::NodeNameContainerType>,
+    ) {
+        // The European tree has no barrier, so any of the inductions can carry it
+        let mut european = with_european.then(|| workspace.take());
+        let mut carried = None;
+        let mut induction =
+            |barrier: Option<&Barrier>,
+             knocked_in: Option<&<Stack as BinomialTreeStackImpl>::NodeNameContainerType>,
+             tree_map| {
+                let (tree_map, european) = self.induction(
+                    option,
+                    prices,
+                    barrier,
+                    knocked_in,
+                    tree_map,
+                    european.take(),
+                );
+                carried = carried.take().or(european);
+                tree_map
+            };
+
+        let Some(barrier) = option.barrier() else {
+            let tree_map = induction(None, None, workspace.take());
+            return (tree_map, carried);
+        };
+        let knocked_in = barrier
+            .is_knock_in()
+            .then(|| induction(None, None, workspace.take()));
+
+        let tree_map = if barrier.monitoring != Monitoring::Continuous {
+            induction(Some(barrier), knocked_in.as_ref(), workspace.take())
+        } else {
+            let layers = BarrierLayers::new(
+                barrier,
+                self.tree_spot(),
+                (self.params.u / self.params.d).sqrt(),
+            );
+            let inner = induction(Some(&layers.inner), knocked_in.as_ref(), workspace.take());
+            let outer = induction(Some(&layers.outer), knocked_in.as_ref(), workspace.take());
+
+            let mut tree_map = workspace.take();
+            for node in self.stack.iter().flat_map(|level| level.iter()) {
+                if let (Some(inner), Some(outer)) = (inner.value(node), outer.value(node)) {
+                    tree_map.set(node, (inner + (outer - inner) * layers.outer_weight).into());
+                }
+            }
 
-                match (up_value, down_value) {
-                    (Some(up_value), Some(down_value)) => {
-                        let up_value = up_value.get();
-                        let down_value = down_value.get(); //.expect("The tree should be evaluated backwards");
+            workspace.give(inner);
+            workspace.give(outer);
+            tree_map
+        };
 
-                        let value = self.calculate_node_value(*up_value, *down_value);
+        if let Some(knocked_in) = knocked_in {
+            workspace.give(knocked_in);
+        }
+        (tree_map, carried)
+    }
 
-                        let option_value = option.value(value, price);
-                        tree_map.set(node, option_value.into());
+    /// Backward induction into the empty `tree_map` with an optional barrier, using
+    /// `knocked_in` as the value of knock-in options on crossing it.
+    ///
+    /// With an empty `european` map, the European counterpart of the option is evaluated
+    /// into it on the same pass over the levels.
+    fn induction<T: OptionContract + Sync>(
+        &self,
+        option: &T,
+        prices: &[Vec<Float>],
+        barrier: Option<&Barrier>,
+        knocked_in: Option<&<Stack as BinomialTreeStackImpl>::NodeNameContainerType>,
+        mut tree_map: <Stack as BinomialTreeStackImpl>::NodeNameContainerType,
+        mut european: Option<<Stack as BinomialTreeStackImpl>::NodeNameContainerType>,
+    ) -> (
+        <Stack as BinomialTreeStackImpl>::NodeNameContainerType,
+        Option<<Stack as BinomialTreeStackImpl>::NodeNameContainerType>,
+    ) {
+        let (tree_spot, expiry) = (self.tree_spot(), self.expiry.0);
+        let params = &self.params;
+        let european_option = European(option);
+        let new_induction =
+            || Induction::<T, V, U>::new(option, params, tree_spot, expiry, barrier);
+        let new_european_induction = || {
+            Induction::<European<T>, V, U>::new(&european_option, params, tree_spot, expiry, None)
+        };
+        let (induction, european_induction) = (new_induction(), new_european_induction());
+        let with_european = european.is_some();
+
+        let last_level = self.stack.iter().len() - 1;
+        let (p, discount_factor) = (self.params.p(), self.discount_factor);
+        let expectation = |[up_value, down_value]: [Float; 2]| {
+            (up_value * p + down_value * (1.0 - p)) * discount_factor
+        };
+        // Values of the level after the current one, kept while the levels are evaluated in
+        // parallel. The levels shrink backwards, so the parallel ones come first.
+        let mut next_values: Vec<(Option<Float>, Option<Float>)> = Vec::new();
+        for (i, node_level) in self.stack.iter().enumerate().rev() {
+            let level = Level {
+                time_to_expiry: self.expiry.0 - self.time_step * (i as Float), // Is the last step 0 or 1 timestep to expiry?
+                time: self.time_at(i),
+                leaves: i == last_level,
+                before_leaves: i + 1 == last_level,
+            };
+            let knocked_in_value =
+                |node: &<<Stack as BinomialTreeStackImpl>::NodeNameContainerType as BinomialTreeMapImpl>::NodeNameType| {
+                    knocked_in.map(|map| map.value(node).unwrap())
+                };
+
+            if node_level.len() >= self.parallel_width {
+                // The nodes of a level only depend on the values of the next level, so they
+                // are evaluated in parallel from these and then stored in the same order as below
+                let knocked_in_values: Vec<Option<Float>> =
+                    node_level.iter().rev().map(knocked_in_value).collect();
+                let next = |ups: usize| next_values.get(ups).copied().unwrap_or_default();
+                let values: Vec<(Option<Float>, Option<Float>)> = (0..node_level.len())
+                    .into_par_iter()
+                    .map_init(
+                        || (new_induction(), new_european_induction()),
+                        |(induction, european_induction), j| {
+                            let ((up_value, up_european), (down_value, down_european)) =
+                                (next(j + 1), next(j));
+                            let children = Children::of([up_value, down_value], expectation);
+                            let value = induction.node_value(
+                                &level,
+                                prices[i][j],
+                                children,
+                                knocked_in_values[j],
+                            );
+                            let european_value = with_european.then(|| {
+                                let children =
+                                    Children::of([up_european, down_european], expectation);
+                                european_induction.node_value(&level, prices[i][j], children, None)
+                            });
+                            (value, european_value.flatten())
+                        },
+                    )
+                    .collect();
+                for (node, (value, european_value)) in node_level.iter().rev().zip(&values) {
+                    if let Some(value) = value {
+                        tree_map.set(node, (*value).into());
                     }
-                    (Some(_up_value), None) => {
-                        let option_value =
-                            truncation.value(&option, 0.0, price, &self.params, current_expiry);
-                        if let Some(option_value) = option_value {
-                            tree_map.set(node, option_value.into());
-                        }
+                    if let (Some(european), Some(value)) = (european.as_mut(), european_value) {
+                        european.set(node, (*value).into());
                     }
-                    (None, Some(_down_value)) => {
-                        let option_value =
-                            truncation.value(&option, 0.0, price, &self.params, current_expiry);
-                        if let Some(option_value) = option_value {
-                            tree_map.set(node, option_value.into());
-                        }
+                }
+                next_values = values;
+            } else {
+                node_level.iter().rev().enumerate().for_each(|(j, node)| {
+                    let up_value = tree_map.value(&node.up());
+                    let down_value = tree_map.value(&node.down());
+                    let children = Children::of([up_value, down_value], expectation);
+                    let value = induction.node_value(
+                        &level,
+                        prices[i][j],
+                        children,
+                        knocked_in_value(node),
+                    );
+                    if let Some(value) = value {
+                        tree_map.set(node, value.into());
                     }
-                    (None, None) => {
-                        if first_level {
-                            let option_value =
-                                V::value_at_leaf(&option, price, &self.params, current_expiry);
-                            tree_map.set(node, option_value.into());
+
+                    if let Some(european) = european.as_mut() {
+                        let up_value = european.value(&node.up());
+                        let down_value = european.value(&node.down());
+                        let children = Children::of([up_value, down_value], expectation);
+                        let value =
+                            european_induction.node_value(&level, prices[i][j], children, None);
+                        if let Some(value) = value {
+                            european.set(node, value.into());
                         }
                     }
-                }
-            });
-
-            first_level = false;
+                });
+            }
         }
 
         //println!("{:?}", tree_map);
 
-        EvaluatedBinomialTreeModelImpl {
-            model: self,
-            map: tree_map,
-        }
+        (tree_map, european)
     }
 }
 
@@ -147,16 +674,25 @@ pub struct EvaluatedBinomialTreeModelImpl<
     Stack: BinomialTreeStackImpl,
     V: leaf_smoothing::ValueAtLeaf,
     U: border_truncation::ValueAtBorder,
+    L: lattice::Lattice,
+    T: OptionContract,
 > {
-    model: CoxRossRubenstein<Stack, V, U>,
+    model: BinomialTreeModel<Stack, V, U, L>,
     map: <Stack as BinomialTreeStackImpl>::NodeNameContainerType,
+    /// Tree of the European counterpart, carried along for the control variate
+    european: Option<<Stack as BinomialTreeStackImpl>::NodeNameContainerType>,
+    option: T,
+    /// Maps for the bumped trees of vega, rho and psi
+    workspace: RefCell<MapPool<<Stack as BinomialTreeStackImpl>::NodeNameContainerType>>,
 }
 
 impl<
     Stack: BinomialTreeStackImpl,
     V: leaf_smoothing::ValueAtLeaf,
     U: border_truncation::ValueAtBorder,
-> fmt::Display for EvaluatedBinomialTreeModelImpl<Stack, V, U>
+    L: lattice::Lattice,
+    T: OptionContract,
+> fmt::Display for EvaluatedBinomialTreeModelImpl<Stack, V, U, L, T>
 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         const GAP: usize = 8; // minimum spacing between sibling nodes
@@ -172,14 +708,16 @@ impl<
         for (i, level) in self.model.stack.iter().enumerate() {
             let mut row = Vec::with_capacity(level.len());
             for (j, node) in level.iter().enumerate() {
-                let value = self.map.get(node).unwrap().get();
+                let value = self.map.value(node);
                 let price = self
                     .model
                     .params
-                    .calculate_price(self.model.spot.0, j, i - j);
+                    .calculate_price(self.model.tree_spot(), j, i - j)
+                    + self.model.dividends_at(i);
 
                 let price_str = format!("{:.2}", price);
-                let value_str = format!("{:.4}", value);
+                // Border truncation leaves nodes without a value
+                let value_str = value.map_or("-".to_string(), |value| format!("{:.4}", value));
                 row.push((price_str, value_str));
             }
             node_data.push(row);
@@ -354,28 +892,43 @@ impl<
     Stack: BinomialTreeStackImpl,
     V: leaf_smoothing::ValueAtLeaf,
     U: border_truncation::ValueAtBorder,
-> EvaluatedBinomialTreeModelImpl<Stack, V, U>
+    L: lattice::Lattice,
+    T: OptionContract + Sync,
+> EvaluatedBinomialTreeModelImpl<Stack, V, U, L, T>
 {
     pub fn value(&self) -> Value {
         let initial_node = <<Stack as BinomialTreeStackImpl>::NodeNameContainerType as BinomialTreeMapImpl>::NodeNameType::default();
-        let value = self.map.get(&initial_node).unwrap().get();
-        Value(*value)
+        Value(self.map.value(&initial_node).unwrap())
     }
 
     pub fn delta(&self) -> Delta {
-        self.delta_from(&<<Stack as BinomialTreeStackImpl>::NodeNameContainerType as BinomialTreeMapImpl>::NodeNameType::default())
+        self.delta_from(&<<Stack as BinomialTreeStackImpl>::NodeNameContainerType as BinomialTreeMapImpl>::NodeNameType::default(), 0, &|node, _| self.node_value(node))
+    }
+
+    /// Value of `node` in the evaluated tree.
+    fn node_value(
+        &self,
+        node: &<<Stack as BinomialTreeStackImpl>::NodeNameContainerType as BinomialTreeMapImpl>::NodeNameType,
+    ) -> Float {
+        self.map.value(node).unwrap()
     }
 
+    /// Delta between the children of `from_node` on `step`, with `value(node, step)` the
+    /// value of a node on a step.
     fn delta_from(
         &self,
         from_node: &<<Stack as BinomialTreeStackImpl>::NodeNameContainerType as BinomialTreeMapImpl>::NodeNameType,
+        step: usize,
+        value: &impl Fn(&<<Stack as BinomialTreeStackImpl>::NodeNameContainerType as BinomialTreeMapImpl>::NodeNameType, usize) -> Float,
     ) -> Delta {
         let last_up = from_node.up();
-        let last_up_value = self.map.get(&last_up).unwrap().get();
+        let last_up_value = value(&last_up, step + 1);
         let last_down = from_node.down();
-        let last_down_value = self.map.get(&last_down).unwrap().get();
-        let h = last_up.value(self.model.spot.0, self.model.params.u, self.model.params.d)
-            - last_down.value(self.model.spot.0, self.model.params.u, self.model.params.d);
+        let last_down_value = value(&last_down, step + 1);
+        // Both nodes are at the same step, so the cash dividends added back cancel out
+        let tree_spot = self.model.tree_spot();
+        let h = last_up.value(tree_spot, self.model.params.u, self.model.params.d)
+            - last_down.value(tree_spot, self.model.params.u, self.model.params.d);
 
         if h != 0.0 {
             let delta = (last_up_value - last_down_value) / h;
@@ -386,13 +939,22 @@ impl<
     }
 
     pub fn gamma(&self) -> Gamma {
+        self.gamma_of(&|node, _| self.node_value(node))
+    }
+
+    /// Gamma of the node values `value(node, step)`, see [`Self::delta_from`].
+    fn gamma_of(
+        &self,
+        value: &impl Fn(&<<Stack as BinomialTreeStackImpl>::NodeNameContainerType as BinomialTreeMapImpl>::NodeNameType, usize) -> Float,
+    ) -> Gamma {
         let initial_node = <<Stack as BinomialTreeStackImpl>::NodeNameContainerType as BinomialTreeMapImpl>::NodeNameType::default();
         let node_u = initial_node.up();
         let node_d = initial_node.down();
-        let delta_u = self.delta_from(&node_u);
-        let delta_d = self.delta_from(&node_d);
-        let spot_u = self.map.get(&node_u).unwrap().get();
-        let spot_d = self.map.get(&node_d).unwrap().get();
+        let delta_u = self.delta_from(&node_u, 1, value);
+        let delta_d = self.delta_from(&node_d, 1, value);
+        let tree_spot = self.model.tree_spot();
+        let spot_u = node_u.value(tree_spot, self.model.params.u, self.model.params.d);
+        let spot_d = node_d.value(tree_spot, self.model.params.u, self.model.params.d);
 
         if spot_u == spot_d {
             Gamma(0.0)
@@ -401,22 +963,175 @@ impl<
         }
     }
 
+    /// Theta from the initial node and the node two steps later at the same price. The time
+    /// step is positive for models built with [`BinomialTreeModel::try_new`].
     pub fn theta(&self) -> Theta {
         let initial_node = <<Stack as BinomialTreeStackImpl>::NodeNameContainerType as BinomialTreeMapImpl>::NodeNameType::default();
-        let val_0 = self.map.get(&initial_node).unwrap().get();
-        let val_2 = self.map.get_next_step(&initial_node).unwrap().get();
+        let val_0 = self.map.value(&initial_node).unwrap();
+        let val_2 = self.map.next_step_value(&initial_node).unwrap();
 
-        assert_ne!(self.model.time_step, 0.0);
         Theta((val_2 - val_0) / (2.0 * self.model.time_step))
     }
 
+    /// Model with bumped market parameters, sharing the node stack.
+    fn bumped_model(
+        &self,
+        volatility: Float,
+        interest_rate: Float,
+        dividends: Float,
+    ) -> BinomialTreeModel<&Stack, V, U, L> {
+        let mut model: BinomialTreeModel<&Stack, V, U, L> = BinomialTreeModel::new(
+            &self.model.stack,
+            Spot(self.model.spot.0),
+            self.model.number_of_steps,
+            Expiry(self.model.expiry.0),
+            volatility,
+            interest_rate,
+            dividends,
+        )
+        .with_cash_dividends(self.model.cash_dividends.clone())
+        .with_parallel_levels(self.model.parallel_width);
+        model.set_strike(self.option.strike());
+        model
+    }
+
+    /// Value of `option` at the initial node of `model`, evaluated with the maps of the
+    /// workspace.
+    fn root_value<O: OptionContract + Sync>(
+        &self,
+        model: &BinomialTreeModel<&Stack, V, U, L>,
+        option: &O,
+        prices: &[Vec<Float>],
+    ) -> Float {
+        let initial_node = <<Stack as BinomialTreeStackImpl>::NodeNameContainerType as BinomialTreeMapImpl>::NodeNameType::default();
+        let mut workspace = self.workspace.borrow_mut();
+        let map = model.backward_induction(option, prices, &mut workspace);
+        let value = map.value(&initial_node).unwrap();
+
+        workspace.give(map);
+        value
+    }
+
+    /// Re-evaluates the option value with bumped market parameters, sharing the node stack.
+    fn revalue(&self, volatility: Float, interest_rate: Float, dividends: Float) -> Float {
+        let model = self.bumped_model(volatility, interest_rate, dividends);
+        self.root_value(&model, &self.option, &model.node_prices())
+    }
+
+    pub fn vega(&self) -> Vega {
+        let params = &self.model.params;
+        Vega(self.model.bumps.difference.derivative(
+            params.volatility,
+            self.model.bumps.volatility,
+            self.value().0,
+            |volatility| self.revalue(volatility, params.interest_rate, params.dividends),
+        ))
+    }
+
+    pub fn rho(&self) -> Rho {
+        let params = &self.model.params;
+        Rho(self.model.bumps.difference.derivative(
+            params.interest_rate,
+            self.model.bumps.interest_rate,
+            self.value().0,
+            |interest_rate| self.revalue(params.volatility, interest_rate, params.dividends),
+        ))
+    }
+
+    pub fn psi(&self) -> Psi {
+        let params = &self.model.params;
+        Psi(self.model.bumps.difference.derivative(
+            params.dividends,
+            self.model.bumps.dividends,
+            self.value().0,
+            |dividends| self.revalue(params.volatility, params.interest_rate, dividends),
+        ))
+    }
+
+    /// Greeks read off the evaluated tree, without re-evaluating it.
     pub fn greeks(&self) -> Greeks {
         Greeks {
             value: self.value(),
             delta: self.delta(),
             gamma: self.gamma(),
             theta: self.theta(),
-            // TODO: Implement vega and rho
+        }
+    }
+
+    /// Gives the node value maps of the tree, and of the bumped trees of its greeks, to
+    /// `workspace` for the next evaluation, together with the node stack.
+    pub fn recycle(self, workspace: &mut Workspace<Stack>) {
+        workspace.maps.give(self.map);
+        if let Some(european) = self.european {
+            workspace.maps.give(european);
+        }
+        workspace.maps.append(self.workspace.into_inner());
+        workspace.give_stack(self.model.stack);
+    }
+}
+
+#[allow(private_bounds)]
+impl<
+    Stack: BinomialTreeStackBuilder,
+    V: leaf_smoothing::ValueAtLeaf,
+    U: border_truncation::ValueAtBorder,
+    L: lattice::Lattice,
+    T: OptionContract + Sync,
+> EvaluatedBinomialTreeModelImpl<Stack, V, U, L, T>
+{
+    /// Greeks from a tree extended two steps before t = 0 (Pelsser and Vorst).
+    ///
+    /// The extended tree has three nodes at t = 0 centred on the spot, so delta, gamma and
+    /// theta are measured at the actual spot and valuation date instead of one or two steps
+    /// into the tree.
+    pub fn extended_greeks(&self) -> Greeks {
+        let params = self.model.params;
+        let number_of_steps = self.model.number_of_steps + 2;
+        let start_time = -2.0 * self.model.time_step;
+        let mut model: BinomialTreeModel<Stack, V, U, L> = BinomialTreeModel::new(
+            Stack::with_steps(number_of_steps),
+            Spot(self.model.spot.0),
+            number_of_steps,
+            Expiry(self.model.expiry.0 - start_time),
+            params.volatility,
+            params.interest_rate,
+            params.dividends,
+        )
+        .with_cash_dividends(self.model.cash_dividends.clone())
+        .with_parallel_levels(self.model.parallel_width);
+        // Keeps the lattice of the original tree, strike-centred lattices included
+        model.params = params;
+        model.start_time = start_time;
+        // Puts the middle node at t = 0 on the spot, also for lattices with `u * d != 1`
+        model.spot = Spot(self.model.tree_spot() / (params.u * params.d) + model.dividends_at(0));
+        let mut workspace = self.workspace.borrow_mut();
+        let map = model.backward_induction(&self.option, &model.node_prices(), &mut workspace);
+
+        let mut levels = model.stack.iter();
+        let root = &levels.next().unwrap()[0];
+        let today = levels.nth(1).unwrap();
+        let (up, middle, down) = (&today[0], &today[1], &today[2]);
+
+        let value_root = map.value(root).unwrap();
+        let value_up = map.value(up).unwrap();
+        let value_middle = map.value(middle).unwrap();
+        let value_down = map.value(down).unwrap();
+        // The cash dividends added back at t = 0 cancel out in the differences below
+        let tree_spot = model.tree_spot();
+        let price_up = up.value(tree_spot, params.u, params.d);
+        let price_middle = middle.value(tree_spot, params.u, params.d);
+        let price_down = down.value(tree_spot, params.u, params.d);
+
+        let delta_up = (value_up - value_middle) / (price_up - price_middle);
+        let delta_down = (value_middle - value_down) / (price_middle - price_down);
+        workspace.give(map);
+        drop(workspace);
+
+        Greeks {
+            value: Value(value_middle),
+            delta: Delta((value_up - value_down) / (price_up - price_down)),
+            gamma: Gamma((delta_up - delta_down) / ((price_up - price_down) / 2.0)),
+            theta: Theta((value_middle - value_root) / (2.0 * model.time_step)),
         }
     }
 }
@@ -439,9 +1154,32 @@ pub trait EvaluatedBinomialTree: fmt::Display {
     /// Get the theta greek (sensitivity to time decay)
     fn theta(&self) -> Theta;
 
-    /// Get all greeks together
+    /// Get the vega greek (sensitivity to volatility), by bump-and-revalue
+    fn vega(&self) -> Vega;
+
+    /// Get the rho greek (sensitivity to the risk-free rate), by bump-and-revalue
+    fn rho(&self) -> Rho;
+
+    /// Get the psi greek (sensitivity to the dividend yield), by bump-and-revalue
+    fn psi(&self) -> Psi;
+
+    /// Get the greeks read off the tree together
     fn greeks(&self) -> Greeks;
 
+    /// Get all greeks together, including the bump-and-revalue ones, see [`Bumps`]
+    fn full_greeks(&self) -> FullGreeks {
+        FullGreeks::new(self.greeks(), self.vega(), self.rho(), self.psi())
+    }
+
+    /// Get the value corrected with the European counterpart as control variate
+    fn control_variate_value(&self) -> Value;
+
+    /// Get the greeks corrected with the European counterpart as control variate
+    fn control_variate_greeks(&self) -> Greeks;
+
+    /// Get all greeks corrected with the European counterpart as control variate
+    fn control_variate_full_greeks(&self) -> FullGreeks;
+
     /// Display the entire binomial tree (for debugging/visualization)
     ///
     /// This is a convenience method. You can also use the Display trait directly
@@ -455,7 +1193,9 @@ impl<
     Stack: BinomialTreeStackImpl,
     V: leaf_smoothing::ValueAtLeaf,
     U: border_truncation::ValueAtBorder,
-> EvaluatedBinomialTree for EvaluatedBinomialTreeModelImpl<Stack, V, U>
+    L: lattice::Lattice,
+    T: OptionContract + Sync,
+> EvaluatedBinomialTree for EvaluatedBinomialTreeModelImpl<Stack, V, U, L, T>
 {
     fn value(&self) -> Value {
         EvaluatedBinomialTreeModelImpl::value(self)
@@ -473,13 +1213,37 @@ impl<
         EvaluatedBinomialTreeModelImpl::theta(self)
     }
 
+    fn vega(&self) -> Vega {
+        EvaluatedBinomialTreeModelImpl::vega(self)
+    }
+
+    fn rho(&self) -> Rho {
+        EvaluatedBinomialTreeModelImpl::rho(self)
+    }
+
+    fn psi(&self) -> Psi {
+        EvaluatedBinomialTreeModelImpl::psi(self)
+    }
+
     fn greeks(&self) -> Greeks {
         EvaluatedBinomialTreeModelImpl::greeks(self)
     }
+
+    fn control_variate_value(&self) -> Value {
+        EvaluatedBinomialTreeModelImpl::control_variate_value(self)
+    }
+
+    fn control_variate_greeks(&self) -> Greeks {
+        EvaluatedBinomialTreeModelImpl::control_variate_greeks(self)
+    }
+
+    fn control_variate_full_greeks(&self) -> FullGreeks {
+        EvaluatedBinomialTreeModelImpl::control_variate_full_greeks(self)
+    }
 }
 
-pub struct Spot(pub f32);
-pub struct Expiry(pub f32);
+pub struct Spot(pub Float);
+pub struct Expiry(pub Float);
 
 /// Type-erased evaluated binomial tree result
 pub type EvaluatedTree = Box<dyn EvaluatedBinomialTree>;
@@ -494,43 +1258,80 @@ pub fn erase_type<
     Stack: BinomialTreeStackImpl + 'static,
     V: leaf_smoothing::ValueAtLeaf + 'static,
     U: border_truncation::ValueAtBorder + 'static,
+    L: lattice::Lattice + 'static,
+    T: OptionContract + Sync + 'static,
 >(
-    tree: EvaluatedBinomialTreeModelImpl<Stack, V, U>,
+    tree: EvaluatedBinomialTreeModelImpl<Stack, V, U, L, T>,
 ) -> EvaluatedTree {
     Box::new(tree)
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq)]
 pub struct VolatilityParameters {
-    a: f32,
-    pub(crate) u: f32,
-    pub(crate) d: f32,
+    pub(crate) u: Float,
+    pub(crate) d: Float,
+    p: Float,
 
-    volatility: f32,
-    interest_rate: f32,
-    dividends: f32,
+    volatility: Float,
+    interest_rate: Float,
+    dividends: Float,
 }
 
 impl VolatilityParameters {
     pub fn new(
-        volatility: f32,
-        interest_rate: f32,
-        dividends: f32,
-        timestep: f32,
+        volatility: Float,
+        interest_rate: Float,
+        dividends: Float,
+        timestep: Float,
     ) -> VolatilityParameters {
         let u = (volatility * timestep.sqrt()).exp();
+        let a = ((interest_rate - dividends) * timestep).exp();
+        let d = 1.0 / u;
+        VolatilityParameters {
+            u,
+            d,
+            p: (a - d) / (u - d),
+            volatility,
+            interest_rate,
+            dividends,
+        }
+    }
+
+    /// Creates parameters from explicit up/down factors and up probability.
+    ///
+    /// Used by lattices that do not follow the Cox-Ross-Rubinstein `u = 1/d` convention.
+    pub(crate) fn with_factors(
+        u: Float,
+        d: Float,
+        p: Float,
+        volatility: Float,
+        interest_rate: Float,
+        dividends: Float,
+    ) -> VolatilityParameters {
         VolatilityParameters {
-            a: ((interest_rate - dividends) * timestep).exp(),
             u,
-            d: 1.0 / u,
+            d,
+            p,
             volatility,
             interest_rate,
             dividends,
         }
     }
 
-    pub(crate) fn p(&self) -> f32 {
-        (self.a - self.d) / (self.u - self.d)
+    pub(crate) fn p(&self) -> Float {
+        self.p
+    }
+
+    /// Checks that the up probability lies in (0, 1), without which the lattice admits
+    /// arbitrage.
+    pub(crate) fn validate(&self) -> Result<(), Error> {
+        if self.p > 0.0 && self.p < 1.0 {
+            Ok(())
+        } else {
+            Err(Error::Arbitrage {
+                probability: self.p,
+            })
+        }
     }
 
     /// Calculates the asset price at a given node in the binomial tree.
@@ -544,10 +1345,10 @@ impl VolatilityParameters {
     /// The asset price at the node: `spot_price * u^up_steps * d^down_steps`
     pub(crate) fn calculate_price(
         &self,
-        spot_price: f32,
+        spot_price: Float,
         up_steps: usize,
         down_steps: usize,
-    ) -> f32 {
+    ) -> Float {
         spot_price * self.u.powi(up_steps as i32) * self.d.powi(down_steps as i32)
     }
 }
@@ -560,25 +1361,76 @@ pub struct Greeks {
     pub theta: Theta,
 }
 
+/// Greeks together with the sensitivities to the market parameters.
+///
+/// Kept apart from [`Greeks`], which are read off the evaluated tree: vega, rho and psi
+/// re-evaluate the tree with bumped market parameters, six more evaluations with central
+/// differences, so that [`EvaluatedBinomialTree::greeks`] keeps the cost of one evaluation.
+#[derive(Debug, PartialEq)]
+pub struct FullGreeks {
+    pub value: Value,
+    pub delta: Delta,
+    pub gamma: Gamma,
+    pub theta: Theta,
+    pub vega: Vega,
+    pub rho: Rho,
+    pub psi: Psi,
+}
+
+impl FullGreeks {
+    pub(crate) fn new(greeks: Greeks, vega: Vega, rho: Rho, psi: Psi) -> Self {
+        let Greeks {
+            value,
+            delta,
+            gamma,
+            theta,
+        } = greeks;
+        Self {
+            value,
+            delta,
+            gamma,
+            theta,
+            vega,
+            rho,
+            psi,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Value(pub Float);
+
+#[derive(Debug, PartialEq)]
+pub struct Delta(pub Float);
+
+#[derive(Debug, PartialEq)]
+pub struct Gamma(pub Float);
+
 #[derive(Debug, PartialEq)]
-pub struct Value(pub f32);
+pub struct Theta(pub Float);
 
 #[derive(Debug, PartialEq)]
-pub struct Delta(pub f32);
+pub struct Vega(pub Float);
 
 #[derive(Debug, PartialEq)]
-pub struct Gamma(pub f32);
+pub struct Rho(pub Float);
 
 #[derive(Debug, PartialEq)]
-pub struct Theta(pub f32);
+pub struct Psi(pub Float);
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::binomial_tree_map::array::ArrayBinomialTreeMap;
+    use crate::binomial_tree_map::dynamic::DynamicBinomialTreeMap;
     use crate::binomial_tree_map::r#static::StaticBinomialTreeMap;
-    use crate::instruments::{AmericanOption, EuropeanOption, OptionType};
-    use crate::model::leaf_smoothing::Black;
-    use crate::{binomial_tree_map, eval_binomial_tree_with_steps};
+    use crate::instruments::{
+        AmericanOption, AssetOrNothingOption, BarrierKind, BarrierOption, BermudanOption,
+        CashOrNothingOption, CustomOption, EuropeanOption, Exercise, Monitoring, OneTouchOption,
+        OptionType, StrikeContract,
+    };
+    use crate::model::leaf_smoothing::{Black, BroadieDetemple};
+    use crate::{Error, binomial_tree_map, black_scholes, eval_binomial_tree_with_steps};
     #[cfg(test)]
     use pretty_assertions::assert_eq;
 
@@ -614,8 +1466,8 @@ V:49.4009        V:18.0290        V:0.0000         V:0.0000"#;
             CoxRossRubenstein::new(tree_map, Spot(100.0), 2, Expiry(0.5), 0.3, 0.05, 0.0);
         let option = EuropeanOption::new(OptionType::Call, 95.0, 0.5);
         let greeks = model.eval(option);
-        assert_eq!(greeks.value(), Value(12.3578));
-        assert_eq!(greeks.delta(), Delta(0.6599607));
+        assert_reference_eq!(greeks.value().0, 12.3578);
+        assert_reference_eq!(greeks.delta().0, 0.6599607);
     }
 
     #[test]
@@ -625,8 +1477,8 @@ V:49.4009        V:18.0290        V:0.0000         V:0.0000"#;
             CoxRossRubenstein::new(tree_map, Spot(810.0), 2, Expiry(0.5), 0.2, 0.05, 0.02);
         let option = EuropeanOption::new(OptionType::Call, 800.0, 0.5);
         let greeks = model.eval(option);
-        assert_eq!(greeks.value(), Value(53.394733));
-        assert_eq!(greeks.delta(), Delta(0.5891357));
+        assert_reference_eq!(greeks.value().0, 53.394733);
+        assert_reference_eq!(greeks.delta().0, 0.5891357);
     }
 
     #[test]
@@ -636,8 +1488,8 @@ V:49.4009        V:18.0290        V:0.0000         V:0.0000"#;
             CoxRossRubenstein::new(tree_map, Spot(0.61), 3, Expiry(0.25), 0.12, 0.05, 0.07);
         let option = EuropeanOption::new(OptionType::Call, 0.6, 0.25);
         let greeks = model.eval(option);
-        assert_eq!(greeks.value(), Value(0.018597357));
-        assert_eq!(greeks.delta(), Delta(0.6000447));
+        assert_reference_eq!(greeks.value().0, 0.018597357);
+        assert_reference_eq!(greeks.delta().0, 0.6000447);
     }
 
     #[test]
@@ -647,8 +1499,8 @@ V:49.4009        V:18.0290        V:0.0000         V:0.0000"#;
             CoxRossRubenstein::new(tree_map, Spot(50.0), 2, Expiry(2.0), 0.3, 0.05, 0.0);
         let option = EuropeanOption::new(OptionType::Put, 52.0, 2.0);
         let greeks = model.eval(option);
-        assert_eq!(greeks.value(), Value(6.2457113));
-        assert_eq!(greeks.delta(), Delta(-0.37732533));
+        assert_reference_eq!(greeks.value().0, 6.2457113);
+        assert_reference_eq!(greeks.delta().0, -0.37732533);
     }
 
     #[test]
@@ -659,39 +1511,455 @@ V:49.4009        V:18.0290        V:0.0000         V:0.0000"#;
         let option = AmericanOption::new(OptionType::Put, 52.0, 2.0);
         let eval = model.eval(option);
 
-        assert_eq!(
-            eval.greeks(),
-            Greeks {
-                value: Value(7.428405),
-                delta: Delta(-0.4606061),
-                gamma: Gamma(-0.0), // Hmm
-                theta: Theta(-2.7142024),
-            }
-        )
+        let greeks = eval.greeks();
+        assert_reference_eq!(greeks.value.0, 7.428405);
+        assert_reference_eq!(greeks.delta.0, -0.4606061);
+        assert_reference_eq!(greeks.gamma.0, 0.03124078);
+        assert_reference_eq!(greeks.theta.0, -2.7142024);
     }
 
     #[test]
-    fn test_binomial_tree_american_put2() {
-        let tree_map = binomial_tree_map!(3);
+    fn test_binomial_tree_american_put1_full_greeks() {
+        let tree_map = binomial_tree_map!(2);
         let model: CoxRossRubenstein<StaticBinomialTreeMap> =
-            CoxRossRubenstein::new(tree_map, Spot(31.0), 3, Expiry(0.75), 0.3, 0.05, 0.05);
-        let option = AmericanOption::new(OptionType::Put, 30.0, 0.75);
-        let greeks = model.eval(option);
-        assert_eq!(greeks.value(), Value(2.8356347));
-        assert_eq!(greeks.delta(), Delta(-0.38601997));
-        //assert_eq!(val.risk_free_probability, 0.4626);
+            CoxRossRubenstein::new(tree_map, Spot(50.0), 2, Expiry(2.0), 0.3, 0.05, 0.0);
+        let option = AmericanOption::new(OptionType::Put, 52.0, 2.0);
+        let eval = model.eval(option);
+
+        let greeks = eval.full_greeks();
+        assert_eq!(
+            (greeks.value, greeks.delta, greeks.gamma, greeks.theta),
+            (eval.value(), eval.delta(), eval.gamma(), eval.theta())
+        );
+        // The small bumps of the revaluations magnify the rounding of the values
+        assert_reference_eq!(greeks.vega.0, 24.883938, 1e-3);
+        assert_reference_eq!(greeks.rho.0, -32.50265, 1e-3);
+        assert_reference_eq!(greeks.psi.0, 24.6222, 1e-3);
     }
 
     #[test]
-    fn test_binomial_tree_american_put3() {
-        let tree_map = binomial_tree_map!(3);
+    fn test_binomial_tree_american_put1_extended_greeks() {
+        let tree_map = binomial_tree_map!(2);
         let model: CoxRossRubenstein<StaticBinomialTreeMap> =
-            CoxRossRubenstein::new(tree_map, Spot(60.0), 3, Expiry(3.0 / 12.0), 0.45, 0.1, 0.00);
-        let option = AmericanOption::new(OptionType::Put, 60.0, 3.0 / 12.0);
-        let greeks = model.eval(option);
-        assert_eq!(greeks.value(), Value(5.1627836));
-        assert_eq!(greeks.delta(), Delta(-0.43557432));
-        //println!("{:?}", greeks.model.tree_map.map);
+            CoxRossRubenstein::new(tree_map, Spot(50.0), 2, Expiry(2.0), 0.3, 0.05, 0.0);
+        let option = AmericanOption::new(OptionType::Put, 52.0, 2.0);
+        let eval = model.eval(option);
+
+        let greeks = eval.extended_greeks();
+        assert_reference_eq!(greeks.value.0, 7.428402);
+        assert_reference_eq!(greeks.delta.0, -0.37892592);
+        assert_reference_eq!(greeks.gamma.0, 0.018510567);
+        assert_reference_eq!(greeks.theta.0, -0.6872754);
+    }
+
+    #[test]
+    fn test_binomial_tree_european_extended_greeks() {
+        let black = |spot: Float, expiry: Float| {
+            crate::black_scholes::black_value(OptionType::Call, spot, 95.0, 0.3, 0.05, 0.02, expiry)
+        };
+        let delta = (black(100.5, 0.5) - black(99.5, 0.5)) / 1.0;
+        let gamma = black(101.0, 0.5) - 2.0 * black(100.0, 0.5) + black(99.0, 0.5);
+        let theta = -(black(100.0, 0.51) - black(100.0, 0.49)) / 0.02;
+
+        let tree_map = binomial_tree_map!(100);
+        let model: CoxRossRubenstein<StaticBinomialTreeMap> =
+            CoxRossRubenstein::new(tree_map, Spot(100.0), 100, Expiry(0.5), 0.3, 0.05, 0.02);
+        let eval = model.eval(EuropeanOption::new(OptionType::Call, 95.0, 0.5));
+        let greeks = eval.extended_greeks();
+
+        assert!((greeks.value.0 - eval.value().0).abs() < 1e-4);
+        assert!((greeks.delta.0 - delta).abs() < 1e-2);
+        assert!((greeks.gamma.0 - gamma).abs() < 1e-3);
+        assert!((greeks.theta.0 - theta).abs() < 1e-1);
+    }
+
+    #[test]
+    fn test_dynamic_binomial_tree_extended_greeks() {
+        let model: CoxRossRubenstein<StaticBinomialTreeMap> = CoxRossRubenstein::new(
+            binomial_tree_map!(20),
+            Spot(100.0),
+            20,
+            Expiry(0.5),
+            0.3,
+            0.05,
+            0.02,
+        );
+        let dynamic_model: CoxRossRubenstein<DynamicBinomialTreeMap> = CoxRossRubenstein::new(
+            DynamicBinomialTreeMap::new(20),
+            Spot(100.0),
+            20,
+            Expiry(0.5),
+            0.3,
+            0.05,
+            0.02,
+        );
+
+        assert_eq!(
+            dynamic_model
+                .eval(AmericanOption::new(OptionType::Put, 105.0, 0.5))
+                .extended_greeks(),
+            model
+                .eval(AmericanOption::new(OptionType::Put, 105.0, 0.5))
+                .extended_greeks()
+        );
+    }
+
+    #[test]
+    fn test_binomial_tree_array_map() {
+        fn eval<Stack: BinomialTreeStackImpl>(
+            stack: Stack,
+        ) -> EvaluatedBinomialTreeModelImpl<
+            Stack,
+            Black,
+            border_truncation::Black,
+            lattice::CoxRossRubenstein,
+            AmericanOption,
+        > {
+            let model: CoxRossRubenstein<Stack, Black, border_truncation::Black> =
+                CoxRossRubenstein::new(stack, Spot(100.0), 100, Expiry(0.5), 0.3, 0.05, 0.02);
+            model.eval(AmericanOption::new(OptionType::Put, 105.0, 0.5))
+        }
+
+        let array = eval(ArrayBinomialTreeMap::new(100));
+        let r#static = eval(StaticBinomialTreeMap::with_capacity(100));
+
+        assert_eq!(array.greeks(), r#static.greeks());
+        assert_eq!(
+            array.gamma(),
+            eval(DynamicBinomialTreeMap::new(100)).gamma()
+        );
+        assert_eq!(array.extended_greeks(), r#static.extended_greeks());
+
+        fn display<Stack: BinomialTreeStackImpl>(stack: Stack) -> String {
+            let model: CoxRossRubenstein<Stack> =
+                CoxRossRubenstein::new(stack, Spot(100.0), 3, Expiry(0.5), 0.3, 0.05, 0.0);
+            format!(
+                "{}",
+                model.eval(EuropeanOption::new(OptionType::Call, 95.0, 0.5))
+            )
+        }
+        assert_eq!(
+            display(ArrayBinomialTreeMap::new(3)),
+            display(binomial_tree_map!(3))
+        );
+    }
+
+    #[test]
+    fn test_binomial_tree_american_approximation_strategies() {
+        fn value<V: leaf_smoothing::ValueAtLeaf, U: border_truncation::ValueAtBorder>(
+            option: impl OptionContract + Sync,
+            number_of_steps: usize,
+        ) -> Float {
+            let model: CoxRossRubenstein<ArrayBinomialTreeMap, V, U> = CoxRossRubenstein::new(
+                ArrayBinomialTreeMap::new(number_of_steps),
+                Spot(100.0),
+                number_of_steps,
+                Expiry(0.5),
+                0.3,
+                0.05,
+                0.02,
+            );
+            model.eval(option).value().0
+        }
+
+        let put = AmericanOption::new(OptionType::Put, 105.0, 0.5);
+        let reference = value::<BroadieDetemple, border_truncation::Black>(put, 1000);
+        for approximated in [
+            value::<leaf_smoothing::BaroneAdesiWhaley, border_truncation::BaroneAdesiWhaley>(
+                put, 100,
+            ),
+            value::<leaf_smoothing::BjerksundStensland, border_truncation::BjerksundStensland>(
+                put, 100,
+            ),
+            value::<leaf_smoothing::BaroneAdesiWhaley, border_truncation::None>(put, 100),
+            value::<BroadieDetemple, border_truncation::BjerksundStensland>(put, 100),
+        ] {
+            assert!((approximated - reference).abs() < 1e-2);
+        }
+
+        // Without early exercise the approximations are the Black-Scholes values
+        let put = EuropeanOption::new(OptionType::Put, 105.0, 0.5);
+        assert_eq!(
+            value::<leaf_smoothing::BaroneAdesiWhaley, border_truncation::BjerksundStensland>(
+                put, 100
+            ),
+            value::<BroadieDetemple, border_truncation::Black>(put, 100)
+        );
+    }
+
+    #[test]
+    fn test_binomial_tree_european_greeks_against_black() {
+        for (option_type, strike) in [(OptionType::Call, 95.0), (OptionType::Put, 105.0)] {
+            let model: CoxRossRubenstein<
+                ArrayBinomialTreeMap,
+                BroadieDetemple,
+                border_truncation::Black,
+            > = CoxRossRubenstein::new(
+                ArrayBinomialTreeMap::new(500),
+                Spot(100.0),
+                500,
+                Expiry(0.5),
+                0.3,
+                0.05,
+                0.02,
+            );
+            let greeks = model
+                .eval(EuropeanOption::new(option_type, strike, 0.5))
+                .full_greeks();
+            let black =
+                black_scholes::black_greeks(option_type, 100.0, strike, 0.3, 0.05, 0.02, 0.5);
+
+            assert!((greeks.value.0 - black.value).abs() < 5e-3);
+            assert!((greeks.delta.0 - black.delta).abs() < 1e-4);
+            assert!((greeks.gamma.0 - black.gamma).abs() < 1e-4);
+            assert!((greeks.theta.0 - black.theta).abs() < 2e-2);
+            assert!((greeks.vega.0 - black.vega).abs() < 1e-2);
+            // Bump-and-revalue rho and psi are within two percent
+            assert!((greeks.rho.0 - black.rho).abs() < 2e-2 * black.rho.abs());
+            assert!((greeks.psi.0 - black.psi).abs() < 2e-2 * black.psi.abs());
+        }
+    }
+
+    #[test]
+    fn test_binomial_tree_static_map_beyond_128_steps() {
+        fn value<Stack: BinomialTreeStackImpl>(stack: Stack) -> (Value, Delta) {
+            let model: CoxRossRubenstein<Stack> =
+                CoxRossRubenstein::new(stack, Spot(100.0), 200, Expiry(0.5), 0.3, 0.05, 0.02);
+            let eval = model.eval(AmericanOption::new(OptionType::Put, 105.0, 0.5));
+            (eval.value(), eval.delta())
+        }
+
+        assert_eq!(
+            value(binomial_tree_map!(200)),
+            value(DynamicBinomialTreeMap::new(200))
+        );
+    }
+
+    #[test]
+    fn test_binomial_tree_try_new() {
+        fn model(
+            spot: Float,
+            number_of_steps: usize,
+            expiry: Float,
+            volatility: Float,
+        ) -> Result<CoxRossRubenstein<ArrayBinomialTreeMap>, Error> {
+            CoxRossRubenstein::try_new(
+                ArrayBinomialTreeMap::new(number_of_steps),
+                Spot(spot),
+                number_of_steps,
+                Expiry(expiry),
+                volatility,
+                0.5,
+                0.0,
+            )
+        }
+
+        assert!(matches!(
+            model(0.0, 100, 0.5, 0.1),
+            Err(Error::NonPositiveSpot(0.0))
+        ));
+        assert!(matches!(
+            model(100.0, 1, 0.5, 0.1),
+            Err(Error::TooFewSteps {
+                steps: 1,
+                minimum: 2
+            })
+        ));
+        assert!(matches!(
+            model(100.0, 100, -0.5, 0.1),
+            Err(Error::NonPositiveExpiry(-0.5))
+        ));
+        assert!(matches!(
+            model(100.0, 100, 0.5, Float::NAN),
+            Err(Error::NonPositiveVolatility(_))
+        ));
+        // The drift of a step outweighs its volatility
+        assert!(matches!(
+            model(100.0, 2, 0.5, 0.1),
+            Err(Error::Arbitrage { probability }) if probability > 1.0
+        ));
+
+        let option = AmericanOption::new(OptionType::Put, 105.0, 0.5);
+        let value = model(100.0, 100, 0.5, 0.1)
+            .unwrap()
+            .try_eval(option)
+            .unwrap()
+            .value();
+        assert_eq!(
+            value,
+            CoxRossRubenstein::<ArrayBinomialTreeMap>::new(
+                ArrayBinomialTreeMap::new(100),
+                Spot(100.0),
+                100,
+                Expiry(0.5),
+                0.1,
+                0.5,
+                0.0
+            )
+            .eval(option)
+            .value()
+        );
+
+        let option = EuropeanOption::new(OptionType::Put, -105.0, 0.5);
+        assert!(matches!(
+            model(100.0, 100, 0.5, 0.1).unwrap().try_eval(option),
+            Err(Error::NonPositiveStrike(-105.0))
+        ));
+
+        // The tree would have the 50 steps of the stack
+        let model = CoxRossRubenstein::<ArrayBinomialTreeMap>::try_new(
+            ArrayBinomialTreeMap::new(50),
+            Spot(100.0),
+            100,
+            Expiry(0.5),
+            0.1,
+            0.5,
+            0.0,
+        );
+        assert!(matches!(
+            model.unwrap().try_eval(option),
+            Err(Error::StackSteps {
+                stack_steps: 50,
+                number_of_steps: 100
+            })
+        ));
+    }
+
+    #[test]
+    fn test_binomial_tree_try_eval_bumps() {
+        let model = |bumps| {
+            CoxRossRubenstein::<ArrayBinomialTreeMap>::new(
+                ArrayBinomialTreeMap::new(100),
+                Spot(100.0),
+                100,
+                Expiry(0.5),
+                0.1,
+                0.05,
+                0.0,
+            )
+            .with_bumps(bumps)
+        };
+        let option = AmericanOption::new(OptionType::Put, 105.0, 0.5);
+
+        assert!(model(Bumps::default()).try_eval(option).is_ok());
+        let bumps = Bumps {
+            interest_rate: 0.0,
+            ..Default::default()
+        };
+        assert!(matches!(
+            model(bumps).try_eval(option),
+            Err(Error::NonPositiveBump(0.0))
+        ));
+        // Central differences would revalue with a zero volatility
+        let bumps = Bumps {
+            volatility: 0.1,
+            ..Default::default()
+        };
+        assert!(matches!(
+            model(bumps).try_eval(option),
+            Err(Error::NonPositiveVolatility(_))
+        ));
+        let bumps = Bumps {
+            volatility: 0.1,
+            difference: FiniteDifference::Forward,
+            ..Default::default()
+        };
+        assert!(model(bumps).try_eval(option).is_ok());
+    }
+
+    #[test]
+    fn test_binomial_tree_parallel_levels() {
+        fn model<Stack: BinomialTreeStackImpl>(
+            stack: Stack,
+        ) -> CoxRossRubenstein<Stack, Black, border_truncation::Black> {
+            CoxRossRubenstein::new(stack, Spot(100.0), 300, Expiry(0.5), 0.3, 0.05, 0.02)
+                .with_cash_dividends(DividendSchedule::new(vec![CashDividend {
+                    ex_date: 0.25,
+                    amount: 2.0,
+                }]))
+        }
+
+        let option = AmericanOption::new(OptionType::Put, 105.0, 0.5);
+        let serial = model(ArrayBinomialTreeMap::new(300)).eval(option);
+        for min_width in [1, 100] {
+            let parallel = model(ArrayBinomialTreeMap::new(300))
+                .with_parallel_levels(min_width)
+                .eval(option);
+            assert_eq!(parallel.greeks(), serial.greeks());
+            assert_eq!(parallel.extended_greeks(), serial.extended_greeks());
+        }
+
+        let option = BarrierOption::with_barrier(
+            EuropeanOption::new(OptionType::Call, 100.0, 0.5),
+            barrier(BarrierKind::DownAndIn, 90.0, Monitoring::Continuous),
+        );
+        let serial = model(ArrayBinomialTreeMap::new(300)).eval(option.clone());
+        let parallel = model(ArrayBinomialTreeMap::new(300))
+            .with_parallel_levels(1)
+            .eval(option);
+        assert_eq!(parallel.greeks(), serial.greeks());
+    }
+
+    #[test]
+    fn test_binomial_tree_american_put2() {
+        let tree_map = binomial_tree_map!(3);
+        let model: CoxRossRubenstein<StaticBinomialTreeMap> =
+            CoxRossRubenstein::new(tree_map, Spot(31.0), 3, Expiry(0.75), 0.3, 0.05, 0.05);
+        let option = AmericanOption::new(OptionType::Put, 30.0, 0.75);
+        let greeks = model.eval(option);
+        assert_reference_eq!(greeks.value().0, 2.8356347);
+        assert_reference_eq!(greeks.delta().0, -0.38601997);
+        //assert_eq!(val.risk_free_probability, 0.4626);
+    }
+
+    #[test]
+    fn test_binomial_tree_gamma() {
+        // Gamma of the first step against the closed form, for strikes in, at and out of
+        // the money
+        for option_type in [OptionType::Call, OptionType::Put] {
+            for strike in [90.0, 100.0, 110.0] {
+                let model: CoxRossRubenstein<ArrayBinomialTreeMap> = CoxRossRubenstein::new(
+                    ArrayBinomialTreeMap::new(500),
+                    Spot(100.0),
+                    500,
+                    Expiry(0.5),
+                    0.3,
+                    0.05,
+                    0.02,
+                );
+                let gamma = model
+                    .eval(EuropeanOption::new(option_type, strike, 0.5))
+                    .gamma();
+                let black =
+                    black_scholes::black_greeks(option_type, 100.0, strike, 0.3, 0.05, 0.02, 0.5);
+
+                assert!((gamma.0 - black.gamma).abs() < 5e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_binomial_tree_american_put2_gamma() {
+        let tree_map = DynamicBinomialTreeMap::new(3);
+        let model: CoxRossRubenstein<DynamicBinomialTreeMap> =
+            CoxRossRubenstein::new(tree_map, Spot(31.0), 3, Expiry(0.75), 0.3, 0.05, 0.05);
+        let option = AmericanOption::new(OptionType::Put, 30.0, 0.75);
+        let gamma = model.eval(option).gamma();
+        // The change of the deltas at the first step over the change of the node prices.
+        // Dividing by the option values at the nodes instead gave a negative gamma of -0.1371
+        assert!((gamma.0 - 0.05291727).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_binomial_tree_american_put3() {
+        let tree_map = binomial_tree_map!(3);
+        let model: CoxRossRubenstein<StaticBinomialTreeMap> =
+            CoxRossRubenstein::new(tree_map, Spot(60.0), 3, Expiry(3.0 / 12.0), 0.45, 0.1, 0.00);
+        let option = AmericanOption::new(OptionType::Put, 60.0, 3.0 / 12.0);
+        let greeks = model.eval(option);
+        assert_reference_eq!(greeks.value().0, 5.1627836);
+        assert_reference_eq!(greeks.delta().0, -0.43557432);
+        //println!("{:?}", greeks.model.tree_map.map);
     }
 
     #[test]
@@ -709,8 +1977,8 @@ V:49.4009        V:18.0290        V:0.0000         V:0.0000"#;
         );
         let option = AmericanOption::new(OptionType::Call, 420.0, 9.0 / 12.0);
         let greeks = model.eval(option);
-        assert_eq!(greeks.value(), Value(42.06769));
-        assert_eq!(greeks.delta(), Delta(0.48716724));
+        assert_reference_eq!(greeks.value().0, 42.06769);
+        assert_reference_eq!(greeks.delta().0, 0.48716724);
         //println!("{:?}", greeks.model.tree_map.map);
     }
 
@@ -721,14 +1989,681 @@ V:49.4009        V:18.0290        V:0.0000         V:0.0000"#;
             CoxRossRubenstein::new(tree_map, Spot(31.0), 100, Expiry(0.75), 0.3, 0.05, 0.05);
         let option = AmericanOption::new(OptionType::Put, 30.0, 0.75);
         let greeks = model.eval(option);
-        assert_eq!(greeks.value(), Value(2.6043036));
-        assert_eq!(greeks.delta(), Delta(-0.38875455));
+        assert_reference_eq!(greeks.value().0, 2.6043036);
+        assert_reference_eq!(greeks.delta().0, -0.38875455);
+    }
+
+    #[test]
+    fn test_binomial_tree_bermudan_put() {
+        fn value<T: OptionContract + Sync>(option: T) -> Value {
+            let model: CoxRossRubenstein<StaticBinomialTreeMap> = CoxRossRubenstein::new(
+                binomial_tree_map!(50),
+                Spot(100.0),
+                50,
+                Expiry(0.5),
+                0.3,
+                0.05,
+                0.0,
+            );
+            model.eval(option).value()
+        }
+        let bermudan = |exercise_times| {
+            value(BermudanOption::with_exercise_times(
+                OptionType::Put,
+                105.0,
+                0.5,
+                exercise_times,
+            ))
+        };
+        let american = value(AmericanOption::new(OptionType::Put, 105.0, 0.5));
+        let european = value(EuropeanOption::new(OptionType::Put, 105.0, 0.5));
+
+        // Exercisable on every level
+        assert_eq!(
+            bermudan((0..=50).map(|i| i as Float * 0.01).collect()),
+            american
+        );
+        // Exercisable at expiry only
+        assert_eq!(
+            value(BermudanOption::new(OptionType::Put, 105.0, 0.5)),
+            european
+        );
+
+        let quarterly = bermudan(vec![0.25]);
+        assert!(european.0 < quarterly.0 && quarterly.0 < american.0);
+        // Exercise times between levels move to the nearest level
+        assert_eq!(bermudan(vec![0.2501]), quarterly);
+    }
+
+    #[test]
+    fn test_binomial_tree_portfolio() {
+        let strikes = [90.0, 95.0, 100.0, 105.0, 110.0];
+        let put = |strike| AmericanOption::new(OptionType::Put, strike, 0.5);
+
+        let model: CoxRossRubenstein<StaticBinomialTreeMap> = CoxRossRubenstein::new(
+            binomial_tree_map!(50),
+            Spot(100.0),
+            50,
+            Expiry(0.5),
+            0.3,
+            0.05,
+            0.02,
+        );
+        let portfolio = model.eval_portfolio(strikes.map(put));
+        assert_eq!(portfolio.len(), strikes.len());
+
+        for (eval, strike) in portfolio.iter().zip(strikes) {
+            let single: CoxRossRubenstein<StaticBinomialTreeMap> = CoxRossRubenstein::new(
+                binomial_tree_map!(50),
+                Spot(100.0),
+                50,
+                Expiry(0.5),
+                0.3,
+                0.05,
+                0.02,
+            );
+            assert_eq!(eval.greeks(), single.eval(put(strike)).greeks());
+        }
+
+        // Centred on each strike
+        let model: LeisenReimer<StaticBinomialTreeMap> = LeisenReimer::new(
+            binomial_tree_map!(51),
+            Spot(100.0),
+            51,
+            Expiry(0.5),
+            0.3,
+            0.05,
+            0.02,
+        );
+        let portfolio = model.eval_portfolio(strikes.map(put));
+        for (eval, strike) in portfolio.iter().zip(strikes) {
+            let single: LeisenReimer<StaticBinomialTreeMap> = LeisenReimer::new(
+                binomial_tree_map!(51),
+                Spot(100.0),
+                51,
+                Expiry(0.5),
+                0.3,
+                0.05,
+                0.02,
+            );
+            assert_eq!(eval.value(), single.eval(put(strike)).value());
+        }
+    }
+
+    #[test]
+    fn test_binomial_tree_custom_option() {
+        fn value<T: OptionContract + Sync>(option: T) -> Float {
+            let model: CoxRossRubenstein<StaticBinomialTreeMap> = CoxRossRubenstein::new(
+                binomial_tree_map!(50),
+                Spot(100.0),
+                50,
+                Expiry(0.5),
+                0.3,
+                0.05,
+                0.0,
+            );
+            model.eval(option).value().0
+        }
+        let put = |price: Float| (105.0 - price).max(0.0);
+        let call = |strike: Float| move |price: Float| (price - strike).max(0.0);
+
+        assert_eq!(
+            value(CustomOption::new(put, Exercise::American, 0.5)),
+            value(AmericanOption::new(OptionType::Put, 105.0, 0.5))
+        );
+        assert_eq!(
+            value(CustomOption::new(put, Exercise::Bermudan(vec![0.25]), 0.5)),
+            value(BermudanOption::with_exercise_times(
+                OptionType::Put,
+                105.0,
+                0.5,
+                vec![0.25]
+            ))
+        );
+
+        let straddle = value(CustomOption::new(
+            |price: Float| (price - 105.0).abs(),
+            Exercise::European,
+            0.5,
+        ));
+        let european = |option_type| value(EuropeanOption::new(option_type, 105.0, 0.5));
+        assert!((straddle - european(OptionType::Call) - european(OptionType::Put)).abs() < 1e-4);
+
+        let capped_call = value(CustomOption::new(
+            |price: Float| (price - 95.0).clamp(0.0, 20.0),
+            Exercise::European,
+            0.5,
+        ));
+        let call_spread = value(CustomOption::new(call(95.0), Exercise::European, 0.5))
+            - value(CustomOption::new(call(115.0), Exercise::European, 0.5));
+        assert!((capped_call - call_spread).abs() < 1e-4);
+    }
+
+    fn barrier_value<T: OptionContract + Sync>(option: T, number_of_steps: usize) -> Float {
+        smoothed_barrier_value::<T, leaf_smoothing::None>(option, number_of_steps)
+    }
+
+    fn smoothed_barrier_value<T: OptionContract + Sync, V: leaf_smoothing::ValueAtLeaf>(
+        option: T,
+        number_of_steps: usize,
+    ) -> Float {
+        let model: CoxRossRubenstein<StaticBinomialTreeMap, V> = CoxRossRubenstein::new(
+            StaticBinomialTreeMap::with_capacity(number_of_steps),
+            Spot(100.0),
+            number_of_steps,
+            Expiry(0.5),
+            0.3,
+            0.05,
+            0.0,
+        );
+        model.eval(option).value().0
+    }
+
+    fn barrier(kind: BarrierKind, level: Float, monitoring: Monitoring) -> Barrier {
+        Barrier {
+            kind,
+            level,
+            rebate: 0.0,
+            monitoring,
+        }
+    }
+
+    #[test]
+    fn test_binomial_tree_down_and_out_call() {
+        let expected = crate::black_scholes::barrier_value(
+            OptionType::Call,
+            100.0,
+            100.0,
+            &barrier(BarrierKind::DownAndOut, 90.0, Monitoring::Continuous),
+            0.3,
+            0.05,
+            0.0,
+            0.5,
+        );
+
+        // The barrier correction keeps prices from zig-zagging with the number of steps
+        for number_of_steps in [60, 80, 100, 120] {
+            let option = BarrierOption::with_barrier(
+                EuropeanOption::new(OptionType::Call, 100.0, 0.5),
+                barrier(BarrierKind::DownAndOut, 90.0, Monitoring::Continuous),
+            );
+            assert!((barrier_value(option.clone(), number_of_steps) - expected).abs() < 5e-2);
+            // Leaf smoothing keeps converging with the closed-form values near the barrier
+            let smoothed =
+                smoothed_barrier_value::<_, leaf_smoothing::Black>(option.clone(), number_of_steps);
+            assert!((smoothed - expected).abs() < 5e-2);
+        }
+    }
+
+    #[test]
+    fn test_binomial_tree_barrier_in_out_parity() {
+        for (knock_in, knock_out, level) in [
+            (BarrierKind::DownAndIn, BarrierKind::DownAndOut, 90.0),
+            (BarrierKind::UpAndIn, BarrierKind::UpAndOut, 115.0),
+        ] {
+            for option_type in [OptionType::Call, OptionType::Put] {
+                let option = EuropeanOption::new(option_type, 100.0, 0.5);
+                let knocked_in = BarrierOption::with_barrier(
+                    option,
+                    barrier(knock_in, level, Monitoring::Continuous),
+                );
+                let knocked_out = BarrierOption::with_barrier(
+                    option,
+                    barrier(knock_out, level, Monitoring::Continuous),
+                );
+
+                let parity = barrier_value(knocked_in, 100) + barrier_value(knocked_out, 100);
+                assert!((parity - barrier_value(option, 100)).abs() < 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_binomial_tree_american_barrier_put() {
+        let option = AmericanOption::new(OptionType::Put, 100.0, 0.5);
+        let up_and_out = Barrier {
+            rebate: 2.0,
+            ..barrier(BarrierKind::UpAndOut, 110.0, Monitoring::Continuous)
+        };
+
+        let american = barrier_value(BarrierOption::with_barrier(option, up_and_out.clone()), 100);
+        let european = barrier_value(
+            BarrierOption::with_barrier(
+                EuropeanOption::new(OptionType::Put, 100.0, 0.5),
+                up_and_out.clone(),
+            ),
+            100,
+        );
+        assert!(american > european);
+        assert!(american < barrier_value(option, 100));
+
+        // Already knocked out or in at the spot
+        let knocked_out = Barrier {
+            level: 95.0,
+            ..up_and_out
+        };
+        assert_eq!(
+            barrier_value(
+                BarrierOption::with_barrier(option, knocked_out.clone()),
+                100
+            ),
+            2.0
+        );
+        let knocked_in = Barrier {
+            kind: BarrierKind::UpAndIn,
+            ..knocked_out
+        };
+        assert_eq!(
+            barrier_value(BarrierOption::with_barrier(option, knocked_in), 100),
+            barrier_value(option, 100)
+        );
+    }
+
+    #[test]
+    fn test_binomial_tree_discretely_monitored_barrier() {
+        let option = EuropeanOption::new(OptionType::Call, 100.0, 0.5);
+        let monthly = (1..=6).map(|month| month as Float / 12.0).collect();
+
+        let continuous = barrier_value(
+            BarrierOption::with_barrier(
+                option,
+                barrier(BarrierKind::DownAndOut, 90.0, Monitoring::Continuous),
+            ),
+            120,
+        );
+        let discrete = barrier_value(
+            BarrierOption::with_barrier(
+                option,
+                barrier(BarrierKind::DownAndOut, 90.0, Monitoring::Discrete(monthly)),
+            ),
+            120,
+        );
+
+        assert!(continuous < discrete && discrete < barrier_value(option, 120));
+    }
+
+    fn smoothed_value<T: OptionContract + Sync>(option: T, number_of_steps: usize) -> Float {
+        let model: CoxRossRubenstein<StaticBinomialTreeMap, BroadieDetemple> =
+            CoxRossRubenstein::new(
+                StaticBinomialTreeMap::with_capacity(number_of_steps),
+                Spot(100.0),
+                number_of_steps,
+                Expiry(0.5),
+                0.3,
+                0.05,
+                0.02,
+            );
+        model.eval(option).value().0
+    }
+
+    #[test]
+    fn test_binomial_tree_digital_options() {
+        use crate::black_scholes::{asset_or_nothing_value, cash_or_nothing_value};
+
+        for option_type in [OptionType::Call, OptionType::Put] {
+            let cash = cash_or_nothing_value(option_type, 100.0, 95.0, 0.3, 0.05, 0.02, 0.5);
+            let asset = asset_or_nothing_value(option_type, 100.0, 95.0, 0.3, 0.05, 0.02, 0.5);
+
+            for number_of_steps in [99, 100] {
+                let value = smoothed_value(
+                    CashOrNothingOption::with_cash(option_type, 95.0, 0.5, 10.0),
+                    number_of_steps,
+                );
+                assert!((value - 10.0 * cash).abs() < 1e-2);
+
+                let value = smoothed_value(
+                    AssetOrNothingOption::new(option_type, 95.0, 0.5),
+                    number_of_steps,
+                );
+                assert!((value - asset).abs() < 5e-2);
+            }
+        }
+    }
+
+    #[test]
+    fn test_binomial_tree_custom_option_smoothing() {
+        // Payoffs without a closed form are smoothed with the integrated payoff
+        let call = CustomOption::new(
+            |price: Float| (price - 95.0).max(0.0),
+            Exercise::American,
+            0.5,
+        );
+        let vanilla = smoothed_value(AmericanOption::new(OptionType::Call, 95.0, 0.5), 100);
+        assert!((smoothed_value(call, 100) - vanilla).abs() < 1e-3);
+
+        let power = CustomOption::new(|price: Float| price.powi(2), Exercise::European, 0.5);
+        let forward = 100.0 * ((0.05 - 0.02 + 0.3 * 0.3) * 0.5 as Float).exp();
+        let value = smoothed_value(power, 100);
+        assert!((value - 100.0 * forward * (-0.02 * 0.5 as Float).exp()).abs() < 1e-4 * value);
+    }
+
+    #[test]
+    fn test_binomial_tree_one_touch() {
+        for (option_type, strike) in [(OptionType::Call, 110.0), (OptionType::Put, 90.0)] {
+            let expected = crate::black_scholes::one_touch_value(
+                option_type,
+                100.0,
+                strike,
+                0.3,
+                0.05,
+                0.02,
+                0.5,
+            );
+            let value = smoothed_value(OneTouchOption::new(option_type, strike, 0.5), 100);
+
+            assert!((value - expected).abs() < 5e-2);
+        }
+    }
+
+    fn dividend_model(
+        cash_dividends: DividendSchedule,
+    ) -> CoxRossRubenstein<StaticBinomialTreeMap> {
+        CoxRossRubenstein::new(
+            binomial_tree_map!(100),
+            Spot(100.0),
+            100,
+            Expiry(0.5),
+            0.3,
+            0.05,
+            0.0,
+        )
+        .with_cash_dividends(cash_dividends)
+    }
+
+    #[test]
+    fn test_binomial_tree_cash_dividends_european() {
+        let dividend = CashDividend {
+            ex_date: 0.25,
+            amount: 2.0,
+        };
+        let eval = dividend_model(DividendSchedule::new(vec![dividend])).eval(EuropeanOption::new(
+            OptionType::Call,
+            95.0,
+            0.5,
+        ));
+
+        // The escrowed model prices Europeans with Black-Scholes on the spot net of dividends
+        let escrowed_spot = 100.0 - 2.0 * (-0.05 * 0.25 as Float).exp();
+        let black = crate::black_scholes::black_value(
+            OptionType::Call,
+            escrowed_spot,
+            95.0,
+            0.3,
+            0.05,
+            0.0,
+            0.5,
+        );
+        assert!((eval.value().0 - black).abs() < 5e-2);
+        // The extended tree sees the same dividends from two steps earlier
+        assert!((eval.extended_greeks().value.0 - eval.value().0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_binomial_tree_cash_dividends_early_exercise() {
+        // Exercising just before the ex-date beats holding the call through the dividend
+        let schedule = DividendSchedule::new(vec![CashDividend {
+            ex_date: 0.45,
+            amount: 5.0,
+        }]);
+        let american = dividend_model(schedule.clone())
+            .eval(AmericanOption::new(OptionType::Call, 95.0, 0.5))
+            .value();
+        let european = dividend_model(schedule)
+            .eval(EuropeanOption::new(OptionType::Call, 95.0, 0.5))
+            .value();
+        assert!(american.0 > european.0 + 0.5);
+
+        // Without dividends an American call is never exercised early
+        let american = dividend_model(DividendSchedule::default())
+            .eval(AmericanOption::new(OptionType::Call, 95.0, 0.5))
+            .value();
+        let european = dividend_model(DividendSchedule::default())
+            .eval(EuropeanOption::new(OptionType::Call, 95.0, 0.5))
+            .value();
+        assert!((american.0 - european.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_binomial_tree_cash_dividends_above_spot() {
+        let option = AmericanOption::new(OptionType::Call, 95.0, 0.5);
+        let schedule = |amount| {
+            DividendSchedule::new(vec![CashDividend {
+                ex_date: 0.25,
+                amount,
+            }])
+        };
+
+        // The escrowed dividend of 100 is worth less than the spot today
+        assert!(dividend_model(schedule(100.0)).try_eval(option).is_ok());
+        assert!(matches!(
+            dividend_model(schedule(110.0)).try_eval(option),
+            Err(Error::NonPositiveNetSpot(spot)) if spot < 0.0
+        ));
+    }
+
+    #[test]
+    fn test_binomial_tree_without_cash_dividends() {
+        let option = AmericanOption::new(OptionType::Put, 105.0, 0.5);
+        let model: CoxRossRubenstein<StaticBinomialTreeMap> = CoxRossRubenstein::new(
+            binomial_tree_map!(100),
+            Spot(100.0),
+            100,
+            Expiry(0.5),
+            0.3,
+            0.05,
+            0.0,
+        );
+
+        assert_eq!(
+            dividend_model(DividendSchedule::default())
+                .eval(option)
+                .greeks(),
+            model.eval(option).greeks()
+        );
+    }
+
+    #[test]
+    fn test_jarrow_rudd_european_call() {
+        let tree_map = binomial_tree_map!(2);
+        let model: JarrowRudd<StaticBinomialTreeMap> =
+            JarrowRudd::new(tree_map, Spot(100.0), 2, Expiry(0.5), 0.3, 0.05, 0.0);
+        let option = EuropeanOption::new(OptionType::Call, 95.0, 0.5);
+        let greeks = model.eval(option);
+        assert_reference_eq!(greeks.value().0, 12.392382);
+        assert_reference_eq!(greeks.delta().0, 0.6604057);
+    }
+
+    #[test]
+    fn test_jarrow_rudd_european_put_100steps() {
+        let tree_map = binomial_tree_map!(100);
+        let model: JarrowRudd<StaticBinomialTreeMap> =
+            JarrowRudd::new(tree_map, Spot(95.0), 100, Expiry(0.5), 0.3, 0.05, 0.0);
+        let option = EuropeanOption::new(OptionType::Put, 100.0, 0.5);
+        let value = model.eval(option).value().0;
+
+        let black =
+            crate::black_scholes::black_value(OptionType::Put, 95.0, 100.0, 0.3, 0.05, 0.0, 0.5);
+        assert!((value - black).abs() < 1e-2);
+    }
+
+    #[test]
+    fn test_jarrow_rudd_american_put2_100steps() {
+        let tree_map = binomial_tree_map!(100);
+        let model: JarrowRudd<StaticBinomialTreeMap> =
+            JarrowRudd::new(tree_map, Spot(31.0), 100, Expiry(0.75), 0.3, 0.05, 0.05);
+        let option = AmericanOption::new(OptionType::Put, 30.0, 0.75);
+        let greeks = model.eval(option);
+        assert_reference_eq!(greeks.value().0, 2.591935);
+        assert_reference_eq!(greeks.delta().0, -0.38920107);
+    }
+
+    fn leisen_reimer_european_error(steps: usize, option_type: OptionType) -> Float {
+        let tree_map = crate::binomial_tree_map::dynamic::DynamicBinomialTreeMap::new(steps);
+        let model: LeisenReimer<crate::binomial_tree_map::dynamic::DynamicBinomialTreeMap> =
+            LeisenReimer::new(tree_map, Spot(100.0), steps, Expiry(0.5), 0.3, 0.05, 0.02);
+        let value = model
+            .eval(EuropeanOption::new(option_type, 95.0, 0.5))
+            .value()
+            .0;
+
+        let black =
+            crate::black_scholes::black_value(option_type, 100.0, 95.0, 0.3, 0.05, 0.02, 0.5);
+        (value - black).abs()
+    }
+
+    #[test]
+    fn test_leisen_reimer_european_call() {
+        let tree_map = binomial_tree_map!(25);
+        let model: LeisenReimer<StaticBinomialTreeMap> =
+            LeisenReimer::new(tree_map, Spot(100.0), 25, Expiry(0.5), 0.3, 0.05, 0.0);
+        let option = EuropeanOption::new(OptionType::Call, 95.0, 0.5);
+        let greeks = model.eval(option);
+        assert_reference_eq!(greeks.value().0, 12.3275);
+        assert_reference_eq!(greeks.delta().0, 0.67674625);
+    }
+
+    #[test]
+    fn test_leisen_reimer_european_convergence() {
+        for option_type in [OptionType::Call, OptionType::Put] {
+            let errors = [5, 11, 23].map(|steps| leisen_reimer_european_error(steps, option_type));
+
+            // Second order convergence: doubling the steps should quarter the error
+            assert!(errors[0] / errors[1] > 3.0);
+            assert!(errors[1] / errors[2] > 3.0);
+            assert!(errors[2] < 1e-3);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "f64")]
+    fn test_leisen_reimer_european_convergence_f64() {
+        // Without the single precision noise floor the convergence holds at large step counts
+        for option_type in [OptionType::Call, OptionType::Put] {
+            let errors =
+                [51, 101, 201, 401].map(|steps| leisen_reimer_european_error(steps, option_type));
+            assert!(errors[0] / errors[1] > 3.0);
+            assert!(errors[1] / errors[2] > 3.0);
+            assert!(errors[2] / errors[3] > 3.0);
+            assert!(errors[3] < 1e-5);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "f64")]
+    fn test_binomial_tree_delta_gamma_f64() {
+        // Single precision rounding leaves delta off by about 1e-4 on thousands of steps
+        for option_type in [OptionType::Call, OptionType::Put] {
+            let model: CoxRossRubenstein<ArrayBinomialTreeMap, BroadieDetemple> =
+                CoxRossRubenstein::new(
+                    ArrayBinomialTreeMap::new(2000),
+                    Spot(100.0),
+                    2000,
+                    Expiry(0.5),
+                    0.3,
+                    0.05,
+                    0.02,
+                );
+            let greeks = model
+                .eval(EuropeanOption::new(option_type, 95.0, 0.5))
+                .greeks();
+            let black = black_scholes::black_greeks(option_type, 100.0, 95.0, 0.3, 0.05, 0.02, 0.5);
+
+            assert!((greeks.delta.0 - black.delta).abs() < 5e-6);
+            assert!((greeks.gamma.0 - black.gamma).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_leisen_reimer_even_steps() {
+        let model = |steps| {
+            LeisenReimer::<ArrayBinomialTreeMap>::try_new(
+                ArrayBinomialTreeMap::new(steps),
+                Spot(100.0),
+                steps,
+                Expiry(0.5),
+                0.3,
+                0.05,
+                0.02,
+            )
+        };
+        for steps in [10, 20, 40] {
+            assert!(matches!(model(steps), Err(Error::EvenSteps(n)) if n == steps));
+            assert!(model(steps + 1).is_ok());
+        }
+
+        // The next odd numbers of steps keep the second order convergence
+        for option_type in [OptionType::Call, OptionType::Put] {
+            let errors = [11, 21, 41].map(|steps| leisen_reimer_european_error(steps, option_type));
+            assert!(errors[0] / errors[1] > 3.0);
+            assert!(errors[1] / errors[2] > 3.0);
+        }
+    }
+
+    #[test]
+    fn test_leisen_reimer_american_put2_51steps() {
+        let tree_map = binomial_tree_map!(51);
+        let model: LeisenReimer<StaticBinomialTreeMap> =
+            LeisenReimer::new(tree_map, Spot(31.0), 51, Expiry(0.75), 0.3, 0.05, 0.05);
+        let option = AmericanOption::new(OptionType::Put, 30.0, 0.75);
+        let greeks = model.eval(option);
+        // The Peizer-Pratt inversion loses most of the digits of single precision
+        assert_reference_eq!(greeks.value().0, 2.595826, 1e-3);
+        assert_reference_eq!(greeks.delta().0, -0.38947815, 1e-3);
+    }
+
+    #[test]
+    fn test_tian_american_put2_100steps() {
+        let tree_map = binomial_tree_map!(100);
+        let model: Tian<StaticBinomialTreeMap> =
+            Tian::new(tree_map, Spot(31.0), 100, Expiry(0.75), 0.3, 0.05, 0.05);
+        let option = AmericanOption::new(OptionType::Put, 30.0, 0.75);
+        let greeks = model.eval(option);
+        assert_reference_eq!(greeks.value().0, 2.5935314);
+        assert_reference_eq!(greeks.delta().0, -0.3881266);
+    }
+
+    #[test]
+    fn test_binomial_tree_european_vega_rho_psi() {
+        for option_type in [OptionType::Call, OptionType::Put] {
+            let tree_map = binomial_tree_map!(100);
+            let model: CoxRossRubenstein<StaticBinomialTreeMap, BroadieDetemple> =
+                CoxRossRubenstein::new(tree_map, Spot(100.0), 100, Expiry(0.5), 0.3, 0.05, 0.02);
+            let greeks = model
+                .eval(EuropeanOption::new(option_type, 95.0, 0.5))
+                .full_greeks();
+
+            let black = black_scholes::black_greeks(option_type, 100.0, 95.0, 0.3, 0.05, 0.02, 0.5);
+            // Leaf smoothing keeps the bumped trees from oscillating against each other
+            assert!((greeks.vega.0 - black.vega).abs() < 3e-2);
+            assert!((greeks.rho.0 - black.rho).abs() < 6e-2);
+            assert!((greeks.psi.0 - black.psi).abs() < 6e-2);
+        }
+    }
+
+    #[test]
+    fn test_binomial_tree_forward_difference_bumps() {
+        let bumps = Bumps {
+            difference: FiniteDifference::Forward,
+            ..Default::default()
+        };
+        let tree_map = binomial_tree_map!(100);
+        let model: CoxRossRubenstein<StaticBinomialTreeMap, BroadieDetemple> =
+            CoxRossRubenstein::new(tree_map, Spot(100.0), 100, Expiry(0.5), 0.3, 0.05, 0.02)
+                .with_bumps(bumps);
+        let eval = model.eval(EuropeanOption::new(OptionType::Call, 95.0, 0.5));
+
+        let black =
+            black_scholes::black_greeks(OptionType::Call, 100.0, 95.0, 0.3, 0.05, 0.02, 0.5);
+        // Forward differences are first order in the bump
+        assert!((eval.vega().0 - black.vega).abs() < 0.1);
+        assert!((eval.rho().0 - black.rho).abs() < 0.1);
+        assert!((eval.psi().0 - black.psi).abs() < 0.1);
     }
 
     #[allow(dead_code)]
     // Estimate for rate of convergence
     // Senning, Jonathan R. "Computing and Estimating the Rate of Convergence https://www.math-cs.gordon.edu/courses/ma342/handouts/rate.pdf
-    fn rate_of_convergence(errors: [f32; 4]) -> f32 {
+    fn rate_of_convergence(errors: [Float; 4]) -> Float {
         ((errors[3] - errors[2]) / (errors[2] - errors[1]))
             .abs()
             .ln()
@@ -739,40 +2674,28 @@ V:49.4009        V:18.0290        V:0.0000         V:0.0000"#;
 
     // Mark S. Joshi, "The Convergence of Binomial Trees For Pricing the American Put"
     // https://fbe.unimelb.edu.au/__data/assets/pdf_file/0010/2591884/170.pdf
-    fn relative_error(tree_price: f32, true_price: f32, intrinsic_value: f32) -> f32 {
+    fn relative_error(tree_price: Float, true_price: Float, intrinsic_value: Float) -> Float {
         (tree_price - true_price) / (0.5 + true_price - intrinsic_value)
     }
 
-    fn eval_american_option_example(steps: usize) -> f32 {
-        if steps > 128 {
-            let tree_map = crate::binomial_tree_map::dynamic::DynamicBinomialTreeMap::new(steps);
-            let binom_tree: CoxRossRubenstein<
-                crate::binomial_tree_map::dynamic::DynamicBinomialTreeMap,
-                Black,
-            > = CoxRossRubenstein::new(tree_map, Spot(100.0), steps, Expiry(0.5), 0.3, 0.05, 0.0);
-
-            binom_tree
-                .eval(AmericanOption::new(OptionType::Call, 95.0, 0.5))
-                .value()
-                .0
-        } else {
-            eval_binomial_tree_with_steps!(
-                steps,
-                AmericanOption,
-                Call,
-                95.0,
-                100.0,
-                0.5,
-                0.3,
-                0.05,
-                0.0
-            )
-            .value()
-            .0
-        }
+    fn eval_american_option_example(steps: usize) -> Float {
+        eval_binomial_tree_with_steps!(
+            steps,
+            AmericanOption,
+            Call,
+            95.0,
+            100.0,
+            0.5,
+            0.3,
+            0.05,
+            0.0
+        )
+        .value()
+        .0
     }
 
-    fn eval_and_calculate_relative_error(steps: usize) -> f32 {
+    #[allow(clippy::excessive_precision)]
+    fn eval_and_calculate_relative_error(steps: usize) -> Float {
         let true_value = 12.32791655;
 
         let val = eval_american_option_example(steps);
@@ -794,7 +2717,7 @@ V:49.4009        V:18.0290        V:0.0000         V:0.0000"#;
         assert_eq!(eval_and_calculate_relative_error(127), -0.0003817296);
         assert_eq!(eval_and_calculate_relative_error(1001), 0.00057088915);*/
 
-        for i in vec![11, 51, 71, 101, 113, 127, 1001] {
+        for i in [11, 51, 71, 101, 113, 127, 1001] {
             println!("{}: {}", i, eval_and_calculate_relative_error(i))
         }
     }
@@ -0,0 +1,231 @@
+//! Richardson extrapolation of a tree and a tree with half the steps.
+//!
+//! With leaf smoothing, the error of the tree falls smoothly as `c / N` for N steps, so
+//! that `(N V_N - M V_M) / (N - M)` with `M = N / 2` cancels the leading term. This is the
+//! binomial Black-Scholes with Richardson extrapolation (BBSR) of Broadie and Detemple
+//! (1996) with `leaf_smoothing::BroadieDetemple`. Without smoothing, the odd-even
+//! oscillation of the Cox-Ross-Rubinstein tree breaks the assumption and the extrapolation
+//! can be worse than the tree itself.
+
+use crate::Float;
+use crate::binomial_tree_map::BinomialTreeStackBuilder;
+use crate::instruments::OptionContract;
+use crate::model::{
+    BinomialTreeModel, Delta, EvaluatedBinomialTreeModelImpl, Expiry, Gamma, Greeks, RollingLevels,
+    Spot, Theta, Value, border_truncation, lattice, leaf_smoothing,
+};
+
+/// Fewest steps of the tree, so that the tree with half the steps has the two steps
+/// of theta.
+const MIN_STEPS: usize = 4;
+
+impl<Stack, V, U, L: lattice::Lattice> BinomialTreeModel<Stack, V, U, L> {
+    /// Model of the same market and settings on `stack` with `number_of_steps` steps.
+    fn with_number_of_steps<S>(
+        &self,
+        stack: S,
+        number_of_steps: usize,
+    ) -> BinomialTreeModel<S, V, U, L> {
+        BinomialTreeModel::new(
+            stack,
+            Spot(self.spot.0),
+            number_of_steps,
+            Expiry(self.expiry.0),
+            self.params.volatility,
+            self.params.interest_rate,
+            self.params.dividends,
+        )
+        .with_bumps(self.bumps)
+        .with_cash_dividends(self.cash_dividends.clone())
+        .with_parallel_levels(self.parallel_width)
+    }
+
+    /// Steps of the tree with half the steps, rounded up to a number of steps the lattice
+    /// is defined for.
+    fn coarse_steps(&self) -> usize {
+        assert!(
+            self.number_of_steps >= MIN_STEPS,
+            "Richardson extrapolation needs at least {MIN_STEPS} steps"
+        );
+        (self.number_of_steps / 2..)
+            .find(|&steps| L::validate_steps(steps).is_ok())
+            .expect("Lattices are defined for every other number of steps")
+    }
+}
+
+#[allow(private_bounds)]
+impl<
+    Stack: BinomialTreeStackBuilder,
+    V: leaf_smoothing::ValueAtLeaf,
+    U: border_truncation::ValueAtBorder,
+    L: lattice::Lattice,
+> BinomialTreeModel<Stack, V, U, L>
+{
+    /// Value extrapolated from this tree and a tree with half the steps, see the
+    /// [module documentation](self).
+    pub fn richardson_value<T: OptionContract + Sync + Clone>(self, option: T) -> Value {
+        Value(self.richardson(option, |eval| eval.value().0, extrapolate))
+    }
+
+    /// Greeks extrapolated from this tree and a tree with half the steps, each greek as
+    /// the value in [`Self::richardson_value`].
+    pub fn richardson_greeks<T: OptionContract + Sync + Clone>(self, option: T) -> Greeks {
+        self.richardson(option, |eval| eval.greeks(), extrapolate_greeks)
+    }
+
+    fn richardson<T: OptionContract + Sync + Clone, R>(
+        self,
+        option: T,
+        result: impl Fn(&EvaluatedBinomialTreeModelImpl<Stack, V, U, L, T>) -> R,
+        extrapolate: impl Fn(R, R, Float) -> R,
+    ) -> R {
+        let coarse_steps = self.coarse_steps();
+        let coarse = self.with_number_of_steps(Stack::with_steps(coarse_steps), coarse_steps);
+        let weight = weight(self.number_of_steps, coarse_steps);
+
+        let fine = result(&self.eval(option.clone()));
+        let coarse = result(&coarse.eval(option));
+        extrapolate(fine, coarse, weight)
+    }
+}
+
+impl<V: leaf_smoothing::ValueAtLeaf, U: border_truncation::ValueAtBorder, L: lattice::Lattice>
+    BinomialTreeModel<RollingLevels, V, U, L>
+{
+    /// Value extrapolated from this tree and a tree with half the steps, both evaluated
+    /// level by level.
+    pub fn richardson_value<T: OptionContract + Sync + Clone>(self, option: T) -> Value {
+        let coarse_steps = self.coarse_steps();
+        let coarse = self.with_number_of_steps(RollingLevels, coarse_steps);
+        let weight = weight(self.number_of_steps, coarse_steps);
+
+        let fine = self.eval(option.clone()).value().0;
+        Value(extrapolate(fine, coarse.eval(option).value().0, weight))
+    }
+
+    /// Greeks extrapolated from this tree and a tree with half the steps, both evaluated
+    /// level by level.
+    pub fn richardson_greeks<T: OptionContract + Sync + Clone>(self, option: T) -> Greeks {
+        let coarse_steps = self.coarse_steps();
+        let coarse = self.with_number_of_steps(RollingLevels, coarse_steps);
+        let weight = weight(self.number_of_steps, coarse_steps);
+
+        let fine = self.eval(option.clone()).greeks();
+        extrapolate_greeks(fine, coarse.eval(option).greeks(), weight)
+    }
+}
+
+/// Weight of the fine tree on `steps` steps against the coarse tree on `coarse_steps`
+/// steps, `N / (N - M)`, 2 for an even number of steps.
+fn weight(steps: usize, coarse_steps: usize) -> Float {
+    steps as Float / (steps - coarse_steps) as Float
+}
+
+fn extrapolate(fine: Float, coarse: Float, weight: Float) -> Float {
+    weight * fine + (1.0 - weight) * coarse
+}
+
+fn extrapolate_greeks(fine: Greeks, coarse: Greeks, weight: Float) -> Greeks {
+    let extrapolate = |fine, coarse| extrapolate(fine, coarse, weight);
+
+    Greeks {
+        value: Value(extrapolate(fine.value.0, coarse.value.0)),
+        delta: Delta(extrapolate(fine.delta.0, coarse.delta.0)),
+        gamma: Gamma(extrapolate(fine.gamma.0, coarse.gamma.0)),
+        theta: Theta(extrapolate(fine.theta.0, coarse.theta.0)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binomial_tree_map::array::ArrayBinomialTreeMap;
+    use crate::black_scholes::black_greeks;
+    use crate::instruments::{AmericanOption, EuropeanOption, OptionType, StrikeContract};
+    use crate::model::{CoxRossRubenstein, LeisenReimer};
+    use pretty_assertions::assert_eq;
+
+    fn model(
+        steps: usize,
+    ) -> CoxRossRubenstein<ArrayBinomialTreeMap, leaf_smoothing::BroadieDetemple> {
+        CoxRossRubenstein::new(
+            ArrayBinomialTreeMap::new(steps),
+            Spot(100.0),
+            steps,
+            Expiry(0.5),
+            0.3,
+            0.05,
+            0.02,
+        )
+    }
+
+    fn rolling(steps: usize) -> CoxRossRubenstein<RollingLevels, leaf_smoothing::BroadieDetemple> {
+        CoxRossRubenstein::new(
+            RollingLevels,
+            Spot(100.0),
+            steps,
+            Expiry(0.5),
+            0.3,
+            0.05,
+            0.02,
+        )
+    }
+
+    #[test]
+    fn test_richardson_european() {
+        let option = EuropeanOption::new(OptionType::Put, 105.0, 0.5);
+        let greeks = model(100).richardson_greeks(option);
+        let black = black_greeks(OptionType::Put, 100.0, 105.0, 0.3, 0.05, 0.02, 0.5);
+
+        assert!((greeks.value.0 - black.value).abs() < 1e-3);
+        assert!((greeks.delta.0 - black.delta).abs() < 1e-4);
+        assert!((greeks.gamma.0 - black.gamma).abs() < 2e-5);
+        assert!((greeks.theta.0 - black.theta).abs() < 5e-3);
+
+        // The tree with half the steps of a Leisen-Reimer tree keeps an odd number of steps
+        let model: LeisenReimer<ArrayBinomialTreeMap> = LeisenReimer::new(
+            ArrayBinomialTreeMap::new(51),
+            Spot(100.0),
+            51,
+            Expiry(0.5),
+            0.3,
+            0.05,
+            0.02,
+        );
+        assert!((model.richardson_value(option).0 - black.value).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_richardson_american_put() {
+        let put = AmericanOption::new(OptionType::Put, 105.0, 0.5);
+        // Average of an even and odd number of steps of the tree without smoothing
+        let reference = |steps| {
+            let model: CoxRossRubenstein<ArrayBinomialTreeMap> = CoxRossRubenstein::new(
+                ArrayBinomialTreeMap::new(steps),
+                Spot(100.0),
+                steps,
+                Expiry(0.5),
+                0.3,
+                0.05,
+                0.02,
+            );
+            model.eval(put).value().0
+        };
+        let reference = (reference(1000) + reference(1001)) / 2.0;
+
+        let tree_error = (model(100).eval(put).value().0 - reference).abs();
+        let value = model(100).richardson_value(put);
+        assert!((value.0 - reference).abs() < tree_error / 3.0);
+
+        // Odd numbers of steps are extrapolated with the tree on the steps rounded down
+        let odd_value = model(101).richardson_value(put);
+        assert!((odd_value.0 - reference).abs() < tree_error / 3.0);
+
+        // The same extrapolation of the trees evaluated level by level
+        assert_eq!(rolling(100).richardson_value(put), value);
+        let greeks = rolling(100).richardson_greeks(put);
+        let tree_greeks = model(100).richardson_greeks(put);
+        assert_eq!(greeks.delta, tree_greeks.delta);
+        assert_eq!(greeks.gamma, tree_greeks.gamma);
+    }
+}
@@ -0,0 +1,317 @@
+use crate::Float;
+use crate::binomial_tree_map::nodes::{NodeIndex, NodeNameTrait};
+use crate::error::Error;
+use crate::instruments::OptionContract;
+use crate::model::induction::{Children, Induction, Level};
+use crate::model::{
+    BinomialTreeModel, Delta, Expiry, Gamma, Greeks, Psi, Rho, Spot, Theta, Value, Vega,
+    border_truncation, lattice, leaf_smoothing,
+};
+
+/// Stand-in for the node stack of a binomial tree model evaluated level by level.
+///
+/// Only the level being computed and the level after it are kept in memory, plus the first
+/// three levels for delta, gamma and theta, so memory grows with `N` instead of `N²`. The
+/// evaluated tree cannot be displayed and its nodes cannot be queried.
+///
+/// Barrier options are not supported, as the knock-in and layered barriers need several
+/// trees over the whole node stack.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct RollingLevels;
+
+impl<V: leaf_smoothing::ValueAtLeaf, U: border_truncation::ValueAtBorder, L: lattice::Lattice>
+    BinomialTreeModel<RollingLevels, V, U, L>
+{
+    /// Evaluates the option level by level.
+    ///
+    /// # Panics
+    ///
+    /// Panics for barrier options, see [`Self::try_eval`].
+    pub fn eval<T: OptionContract + Sync>(
+        mut self,
+        option: T,
+    ) -> EvaluatedRollingTreeModelImpl<V, U, L, T> {
+        assert!(
+            option.barrier().is_none(),
+            "Barrier options are priced on the full binomial tree"
+        );
+        self.set_strike(option.strike());
+        let first_levels = self.rolling_induction(&option);
+
+        EvaluatedRollingTreeModelImpl {
+            model: self,
+            first_levels,
+            option,
+        }
+    }
+
+    /// Evaluates the option after checking its strike, cash dividends and bump sizes, as
+    /// [`BinomialTreeModel::try_eval`] for the full tree, and that it has no barrier.
+    pub fn try_eval<T: OptionContract + Sync>(
+        self,
+        option: T,
+    ) -> Result<EvaluatedRollingTreeModelImpl<V, U, L, T>, Error> {
+        if option.barrier().is_some() {
+            return Err(Error::UnsupportedBarrier);
+        }
+        self.validate_tree_spot()?;
+        self.bumps.validate(self.params.volatility)?;
+        self.validate_strike(option.strike())?;
+        Ok(self.eval(option))
+    }
+
+    /// Backward induction keeping two levels at a time, as in the induction over the stack.
+    ///
+    /// Returns the values of the first three levels, indexed by their number of up steps.
+    fn rolling_induction<T: OptionContract + Sync>(&self, option: &T) -> [Vec<Option<Float>>; 3] {
+        let tree_spot = self.tree_spot();
+        let induction =
+            Induction::<T, V, U>::new(option, &self.params, tree_spot, self.expiry.0, None);
+
+        let mut first_levels: [Vec<Option<Float>>; 3] = Default::default();
+        let mut next_level: Vec<Option<Float>> = Vec::with_capacity(self.number_of_steps + 1);
+        let mut level: Vec<Option<Float>> = Vec::with_capacity(self.number_of_steps + 1);
+
+        for i in (0..=self.number_of_steps).rev() {
+            let dividends = self.dividends_at(i);
+            let node_level = Level {
+                time_to_expiry: self.expiry.0 - self.time_step * (i as Float),
+                time: self.time_at(i),
+                leaves: i == self.number_of_steps,
+                before_leaves: i + 1 == self.number_of_steps,
+            };
+
+            level.clear();
+            level.extend((0..=i).map(|j| {
+                let price = self.params.calculate_price(tree_spot, j, i - j) + dividends;
+                let children = Children::of(
+                    [
+                        next_level.get(j + 1).copied().flatten(),
+                        next_level.get(j).copied().flatten(),
+                    ],
+                    |[up_value, down_value]| self.calculate_node_value(up_value, down_value),
+                );
+                induction.node_value(&node_level, price, children, None)
+            }));
+
+            if i < first_levels.len() {
+                first_levels[i] = level.clone();
+            }
+            std::mem::swap(&mut level, &mut next_level);
+        }
+
+        first_levels
+    }
+}
+
+pub struct EvaluatedRollingTreeModelImpl<V, U, L, T> {
+    model: BinomialTreeModel<RollingLevels, V, U, L>,
+    first_levels: [Vec<Option<Float>>; 3],
+    option: T,
+}
+
+impl<
+    V: leaf_smoothing::ValueAtLeaf,
+    U: border_truncation::ValueAtBorder,
+    L: lattice::Lattice,
+    T: OptionContract + Sync,
+> EvaluatedRollingTreeModelImpl<V, U, L, T>
+{
+    fn node_value(&self, step: usize, ups: usize) -> Float {
+        self.first_levels[step][ups].expect("The first levels are never truncated")
+    }
+
+    /// Price of a node of the first levels, without the cash dividends, which cancel out in
+    /// the differences of prices at the same step. Multiplied out as the node prices of the
+    /// greeks of the full tree, which then agree to the last digit.
+    fn node_price(&self, step: usize, ups: usize) -> Float {
+        let params = &self.model.params;
+        NodeIndex { step, ups }.value(self.model.tree_spot(), params.u, params.d)
+    }
+
+    pub fn value(&self) -> Value {
+        Value(self.node_value(0, 0))
+    }
+
+    pub fn delta(&self) -> Delta {
+        Delta(
+            (self.node_value(1, 1) - self.node_value(1, 0))
+                / (self.node_price(1, 1) - self.node_price(1, 0)),
+        )
+    }
+
+    /// Gamma from the deltas of the second level, over the spread of the first level prices.
+    pub fn gamma(&self) -> Gamma {
+        let delta_u = (self.node_value(2, 2) - self.node_value(2, 1))
+            / (self.node_price(2, 2) - self.node_price(2, 1));
+        let delta_d = (self.node_value(2, 1) - self.node_value(2, 0))
+            / (self.node_price(2, 1) - self.node_price(2, 0));
+
+        Gamma((delta_u - delta_d) / (self.node_price(1, 1) - self.node_price(1, 0)))
+    }
+
+    /// Theta from the initial node and the node two steps later at the same price. The time
+    /// step is positive for models built with [`BinomialTreeModel::try_new`].
+    pub fn theta(&self) -> Theta {
+        Theta((self.node_value(2, 1) - self.node_value(0, 0)) / (2.0 * self.model.time_step))
+    }
+
+    /// Re-evaluates the option value with bumped market parameters.
+    fn revalue(&self, volatility: Float, interest_rate: Float, dividends: Float) -> Float {
+        let mut model: BinomialTreeModel<RollingLevels, V, U, L> = BinomialTreeModel::new(
+            RollingLevels,
+            Spot(self.model.spot.0),
+            self.model.number_of_steps,
+            Expiry(self.model.expiry.0),
+            volatility,
+            interest_rate,
+            dividends,
+        )
+        .with_cash_dividends(self.model.cash_dividends.clone());
+        model.set_strike(self.option.strike());
+
+        model.rolling_induction(&self.option)[0][0].expect("The initial node is never truncated")
+    }
+
+    pub fn vega(&self) -> Vega {
+        let params = &self.model.params;
+        Vega(self.model.bumps.difference.derivative(
+            params.volatility,
+            self.model.bumps.volatility,
+            self.value().0,
+            |volatility| self.revalue(volatility, params.interest_rate, params.dividends),
+        ))
+    }
+
+    pub fn rho(&self) -> Rho {
+        let params = &self.model.params;
+        Rho(self.model.bumps.difference.derivative(
+            params.interest_rate,
+            self.model.bumps.interest_rate,
+            self.value().0,
+            |interest_rate| self.revalue(params.volatility, interest_rate, params.dividends),
+        ))
+    }
+
+    pub fn psi(&self) -> Psi {
+        let params = &self.model.params;
+        Psi(self.model.bumps.difference.derivative(
+            params.dividends,
+            self.model.bumps.dividends,
+            self.value().0,
+            |dividends| self.revalue(params.volatility, params.interest_rate, dividends),
+        ))
+    }
+
+    /// Greeks read off the evaluated tree, without re-evaluating it.
+    pub fn greeks(&self) -> Greeks {
+        Greeks {
+            value: self.value(),
+            delta: self.delta(),
+            gamma: self.gamma(),
+            theta: self.theta(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binomial_tree_map::array::ArrayBinomialTreeMap;
+    use crate::black_scholes::black_value;
+    use crate::instruments::{
+        AmericanOption, Barrier, BarrierKind, BarrierOption, EuropeanOption, Monitoring,
+        OptionType, StrikeContract,
+    };
+    use crate::model::{CashDividend, CoxRossRubenstein, DividendSchedule};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_rolling_levels() {
+        let dividends = DividendSchedule::new(vec![CashDividend {
+            ex_date: 0.25,
+            amount: 2.0,
+        }]);
+        let option = AmericanOption::new(OptionType::Put, 105.0, 0.5);
+
+        let model: CoxRossRubenstein<ArrayBinomialTreeMap, leaf_smoothing::Black> =
+            CoxRossRubenstein::new(
+                ArrayBinomialTreeMap::new(100),
+                Spot(100.0),
+                100,
+                Expiry(0.5),
+                0.3,
+                0.05,
+                0.02,
+            )
+            .with_cash_dividends(dividends.clone());
+        let rolling: CoxRossRubenstein<RollingLevels, leaf_smoothing::Black> =
+            CoxRossRubenstein::new(
+                RollingLevels,
+                Spot(100.0),
+                100,
+                Expiry(0.5),
+                0.3,
+                0.05,
+                0.02,
+            )
+            .with_cash_dividends(dividends);
+
+        let eval = model.eval(option);
+        let rolling = rolling.eval(option);
+
+        assert_eq!(rolling.value(), eval.value());
+        assert_eq!(rolling.delta(), eval.delta());
+        assert_eq!(rolling.theta(), eval.theta());
+        assert_eq!(rolling.vega(), eval.vega());
+        assert_eq!(rolling.rho(), eval.rho());
+        assert_eq!(rolling.psi(), eval.psi());
+        assert_eq!(rolling.gamma(), eval.gamma());
+    }
+
+    #[test]
+    fn test_rolling_levels_barrier() {
+        let model: CoxRossRubenstein<RollingLevels> = CoxRossRubenstein::new(
+            RollingLevels,
+            Spot(100.0),
+            100,
+            Expiry(0.5),
+            0.3,
+            0.05,
+            0.02,
+        );
+        let option = BarrierOption::with_barrier(
+            EuropeanOption::new(OptionType::Call, 100.0, 0.5),
+            Barrier {
+                kind: BarrierKind::DownAndOut,
+                level: 90.0,
+                rebate: 0.0,
+                monitoring: Monitoring::Continuous,
+            },
+        );
+
+        assert!(matches!(
+            model.try_eval(option),
+            Err(Error::UnsupportedBarrier)
+        ));
+    }
+
+    #[test]
+    fn test_rolling_levels_many_steps() {
+        let model: CoxRossRubenstein<RollingLevels, leaf_smoothing::Black> = CoxRossRubenstein::new(
+            RollingLevels,
+            Spot(100.0),
+            5000,
+            Expiry(0.5),
+            0.3,
+            0.05,
+            0.02,
+        );
+        let value = model
+            .eval(EuropeanOption::new(OptionType::Put, 105.0, 0.5))
+            .value();
+
+        let black = black_value(OptionType::Put, 100.0, 105.0, 0.3, 0.05, 0.02, 0.5);
+        assert!((value.0 - black).abs() < 1e-2);
+    }
+}
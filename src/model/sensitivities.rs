@@ -0,0 +1,93 @@
+//! Bump-and-revalue settings for sensitivities that cannot be read off the tree.
+//!
+//! Vega, rho and psi are computed by re-evaluating the tree with bumped
+//! volatility, interest rate and dividend yield respectively.
+
+use crate::Float;
+use crate::error::{Error, positive};
+
+/// Finite difference scheme used when re-evaluating bumped trees.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FiniteDifference {
+    /// `(V(x + h) - V(x)) / h`, one extra evaluation per sensitivity
+    Forward,
+    /// `(V(x + h) - V(x - h)) / 2h`, two extra evaluations per sensitivity
+    Central,
+}
+
+impl FiniteDifference {
+    /// Approximates the derivative of `value_at` in `x` with bump size `h`.
+    ///
+    /// `value` is the already known value at `x`, which saves an evaluation
+    /// for forward differences.
+    pub(crate) fn derivative(
+        &self,
+        x: Float,
+        h: Float,
+        value: Float,
+        value_at: impl Fn(Float) -> Float,
+    ) -> Float {
+        match self {
+            FiniteDifference::Forward => (value_at(x + h) - value) / h,
+            FiniteDifference::Central => (value_at(x + h) - value_at(x - h)) / (2.0 * h),
+        }
+    }
+}
+
+/// Absolute bump sizes for the bump-and-revalue sensitivities.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Bumps {
+    /// Bump of the volatility, used for vega
+    pub volatility: Float,
+    /// Bump of the risk-free rate, used for rho
+    pub interest_rate: Float,
+    /// Bump of the continuous dividend yield, used for psi
+    pub dividends: Float,
+    /// Finite difference scheme
+    pub difference: FiniteDifference,
+}
+
+impl Bumps {
+    /// Checks that the bump sizes are positive, and that central differences do not bump
+    /// `volatility` down to zero or below.
+    pub(crate) fn validate(&self, volatility: Float) -> Result<(), Error> {
+        positive(self.volatility, Error::NonPositiveBump)?;
+        positive(self.interest_rate, Error::NonPositiveBump)?;
+        positive(self.dividends, Error::NonPositiveBump)?;
+        if self.difference == FiniteDifference::Central {
+            positive(volatility - self.volatility, Error::NonPositiveVolatility)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for Bumps {
+    /// Bumps sized to stay well above the rounding error of single precision trees.
+    fn default() -> Self {
+        Self {
+            volatility: 0.01,
+            interest_rate: 0.001,
+            dividends: 0.001,
+            difference: FiniteDifference::Central,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finite_difference() {
+        let square = |x: Float| x * x;
+
+        assert_eq!(
+            FiniteDifference::Forward.derivative(3.0, 0.5, square(3.0), square),
+            6.5
+        );
+        assert_eq!(
+            FiniteDifference::Central.derivative(3.0, 0.5, square(3.0), square),
+            6.0
+        );
+    }
+}
@@ -4,8 +4,9 @@
 /// binomial tree that would extend beyond the computational domain.
 /// Different strategies can decide whether to compute a value or exclude
 /// nodes from evaluation.
-use crate::black_scholes::black_value;
-use crate::instruments::OptionContract;
+use crate::Float;
+use crate::analytical::AmericanApproximation;
+use crate::instruments::{NodeTime, OptionContract};
 use crate::model::VolatilityParameters;
 
 /// Trait for different border truncation strategies.
@@ -15,7 +16,7 @@ use crate::model::VolatilityParameters;
 /// boundary cases - either by excluding them or by applying special pricing.
 pub trait ValueAtBorder {
     /// Create a new truncation strategy instance.
-    fn new(spot: f32, expiry: f32, volatility: f32, rate: f32, dividends: f32) -> Self;
+    fn new(spot: Float, expiry: Float, volatility: Float, rate: Float, dividends: Float) -> Self;
 
     /// Compute the option value at a border node, if it should be included.
     ///
@@ -26,6 +27,7 @@ pub trait ValueAtBorder {
     /// * `price` - The stock price at this node
     /// * `vol_params` - Volatility and rate parameters
     /// * `expiry` - Time remaining until expiration
+    /// * `time` - Time of the node's level
     ///
     /// # Returns
     ///
@@ -33,11 +35,12 @@ pub trait ValueAtBorder {
     fn value<U: OptionContract + Sync>(
         &self,
         option: &U,
-        value: f32,
-        price: f32,
+        value: Float,
+        price: Float,
         vol_params: &VolatilityParameters,
-        expiry: f32,
-    ) -> Option<f32>;
+        expiry: Float,
+        time: NodeTime,
+    ) -> Option<Float>;
 
     /// Returns whether this strategy is a non-trivial truncation (not no-op).
     fn not_none() -> bool;
@@ -48,19 +51,26 @@ pub trait ValueAtBorder {
 /// This strategy does not filter any nodes at the borders.
 /// All nodes in the tree are evaluated using intrinsic values.
 impl ValueAtBorder for None {
-    fn new(_spot: f32, _expiry: f32, _volatility: f32, _rate: f32, _dividends: f32) -> Self {
+    fn new(
+        _spot: Float,
+        _expiry: Float,
+        _volatility: Float,
+        _rate: Float,
+        _dividends: Float,
+    ) -> Self {
         Self {}
     }
 
     fn value<U: OptionContract + Sync>(
         &self,
         option: &U,
-        value: f32,
-        price: f32,
+        value: Float,
+        price: Float,
         _vol_params: &VolatilityParameters,
-        _expiry: f32,
-    ) -> Option<f32> {
-        Some(option.value(value, price))
+        _expiry: Float,
+        time: NodeTime,
+    ) -> Option<Float> {
+        Some(option.value(value, price, time))
     }
 
     fn not_none() -> bool {
@@ -76,8 +86,7 @@ impl ValueAtBorder for None {
 /// with high probability. This is more computationally efficient than
 /// evaluating all extreme nodes.
 impl ValueAtBorder for Black {
-    fn new(spot: f32, expiry: f32, volatility: f32, rate: f32, dividends: f32) -> Self {
-        const NUM_OF_STD: usize = 6;
+    fn new(spot: Float, expiry: Float, volatility: Float, rate: Float, dividends: Float) -> Self {
         Self {
             price_bounds: PriceBounds::new(spot, expiry, volatility, rate, dividends, NUM_OF_STD),
         }
@@ -86,25 +95,94 @@ impl ValueAtBorder for Black {
     fn value<U: OptionContract + Sync>(
         &self,
         option: &U,
-        _value: f32,
-        price: f32,
+        _value: Float,
+        price: Float,
         vol_params: &VolatilityParameters,
-        current_expiry: f32,
-    ) -> Option<f32> {
+        current_expiry: Float,
+        time: NodeTime,
+    ) -> Option<Float> {
         if self.price_bounds.is_out_of_range(price) {
             return Option::None;
         }
 
-        let black_value = black_value(
-            option.option_type(),
+        let black_value = option.black_value(
             price,
-            option.strike(),
             vol_params.volatility,
             vol_params.interest_rate,
             vol_params.dividends,
             current_expiry,
         );
-        Some(option.value(black_value, price))
+        Some(option.value(black_value, price, time))
+    }
+
+    fn not_none() -> bool {
+        true
+    }
+}
+
+/// Barone-Adesi and Whaley based truncation strategy.
+///
+/// Excludes the same nodes as [`Black`], valuing the border nodes of American contracts
+/// with the quadratic approximation of Barone-Adesi and Whaley.
+impl ValueAtBorder for BaroneAdesiWhaley {
+    fn new(spot: Float, expiry: Float, volatility: Float, rate: Float, dividends: Float) -> Self {
+        Self {
+            price_bounds: PriceBounds::new(spot, expiry, volatility, rate, dividends, NUM_OF_STD),
+        }
+    }
+
+    fn value<U: OptionContract + Sync>(
+        &self,
+        option: &U,
+        _value: Float,
+        price: Float,
+        vol_params: &VolatilityParameters,
+        current_expiry: Float,
+        time: NodeTime,
+    ) -> Option<Float> {
+        self.price_bounds.american_value(
+            AmericanApproximation::BaroneAdesiWhaley,
+            option,
+            price,
+            vol_params,
+            current_expiry,
+            time,
+        )
+    }
+
+    fn not_none() -> bool {
+        true
+    }
+}
+
+/// Bjerksund and Stensland based truncation strategy.
+///
+/// As [`BaroneAdesiWhaley`], with the two-boundary approximation of Bjerksund and
+/// Stensland (2002).
+impl ValueAtBorder for BjerksundStensland {
+    fn new(spot: Float, expiry: Float, volatility: Float, rate: Float, dividends: Float) -> Self {
+        Self {
+            price_bounds: PriceBounds::new(spot, expiry, volatility, rate, dividends, NUM_OF_STD),
+        }
+    }
+
+    fn value<U: OptionContract + Sync>(
+        &self,
+        option: &U,
+        _value: Float,
+        price: Float,
+        vol_params: &VolatilityParameters,
+        current_expiry: Float,
+        time: NodeTime,
+    ) -> Option<Float> {
+        self.price_bounds.american_value(
+            AmericanApproximation::BjerksundStensland,
+            option,
+            price,
+            vol_params,
+            current_expiry,
+            time,
+        )
     }
 
     fn not_none() -> bool {
@@ -120,13 +198,26 @@ pub struct Black {
     price_bounds: PriceBounds,
 }
 
+/// Marker type for Barone-Adesi and Whaley based truncation strategy.
+pub struct BaroneAdesiWhaley {
+    price_bounds: PriceBounds,
+}
+
+/// Marker type for Bjerksund and Stensland based truncation strategy.
+pub struct BjerksundStensland {
+    price_bounds: PriceBounds,
+}
+
+/// Standard deviations of the log price within which nodes are evaluated.
+const NUM_OF_STD: usize = 6;
+
 /// Price bounds for boundary checking.
 ///
 /// Calculates and stores the upper and lower price bounds within which
 /// tree nodes are evaluated, under the risk-neutral measure.
 struct PriceBounds {
-    lower_bound: f32,
-    upper_bound: f32,
+    lower_bound: Float,
+    upper_bound: Float,
 }
 
 impl PriceBounds {
@@ -135,18 +226,18 @@ impl PriceBounds {
     /// Using the lognormal distribution of stock prices under the risk-neutral measure,
     /// this calculates confidence bounds based on standard deviations.
     fn new(
-        spot: f32,
-        expiry: f32,
-        volatility: f32,
-        rate: f32,
-        dividends: f32,
+        spot: Float,
+        expiry: Float,
+        volatility: Float,
+        rate: Float,
+        dividends: Float,
         number_of_std: usize,
     ) -> PriceBounds {
         let mean_log = spot.ln() + (rate - dividends - 0.5 * volatility.powi(2) * expiry);
         let std_log = volatility * expiry.sqrt();
 
-        let lower_log = mean_log - (number_of_std as f32) * std_log;
-        let upper_log = mean_log + (number_of_std as f32) * std_log;
+        let lower_log = mean_log - (number_of_std as Float) * std_log;
+        let upper_log = mean_log + (number_of_std as Float) * std_log;
 
         let lower_price = lower_log.exp();
         let upper_price = upper_log.exp();
@@ -157,10 +248,35 @@ impl PriceBounds {
         }
     }
 
-    fn is_out_of_range(&self, price: f32) -> bool {
+    fn is_out_of_range(&self, price: Float) -> bool {
         let in_range = (self.lower_bound..=self.upper_bound).contains(&price);
         !in_range
     }
+
+    /// Value of a node within the bounds from an analytic American approximation.
+    fn american_value<U: OptionContract>(
+        &self,
+        approximation: AmericanApproximation,
+        option: &U,
+        price: Float,
+        vol_params: &VolatilityParameters,
+        current_expiry: Float,
+        time: NodeTime,
+    ) -> Option<Float> {
+        if self.is_out_of_range(price) {
+            return Option::None;
+        }
+
+        let american_value = option.american_value(
+            approximation,
+            price,
+            vol_params.volatility,
+            vol_params.interest_rate,
+            vol_params.dividends,
+            current_expiry,
+        );
+        Some(option.value(american_value, price, time))
+    }
 }
 
 #[cfg(test)]
@@ -171,12 +287,12 @@ mod tests {
     fn test_calculate_price_bounds() {
         let bounds = PriceBounds::new(100.0, 0.5, 0.3, 0.05, 0.0, 6);
 
-        assert_eq!(bounds.lower_bound, 28.78568);
-        assert_eq!(bounds.upper_bound, 367.03702);
+        assert_reference_eq!(bounds.lower_bound, 28.78568);
+        assert_reference_eq!(bounds.upper_bound, 367.03702);
 
         assert!(!bounds.is_out_of_range(100.0));
         assert!(bounds.is_out_of_range(0.0));
         assert!(bounds.is_out_of_range(400.0));
-        assert!(!bounds.is_out_of_range(367.03702));
+        assert!(!bounds.is_out_of_range(bounds.upper_bound));
     }
 }
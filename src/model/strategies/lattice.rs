@@ -0,0 +1,253 @@
+/// Lattice parametrisations for binomial trees.
+///
+/// This module defines how the up and down factors and the up probability
+/// of a recombining binomial tree are derived from the market parameters.
+/// All lattices share the same node storage and backward induction; only the
+/// parameters of each step differ.
+use crate::Float;
+use crate::error::Error;
+use crate::model::VolatilityParameters;
+
+/// Trait for different lattice parametrisations.
+///
+/// A lattice maps volatility, rates and the size of a time step onto the
+/// up factor `u`, down factor `d` and up probability `p` used by the tree.
+pub trait Lattice {
+    /// Compute the parameters of a single time step.
+    ///
+    /// # Arguments
+    ///
+    /// * `spot` - Current spot price
+    /// * `strike` - Strike of the option being evaluated
+    /// * `number_of_steps` - Number of steps in the tree
+    /// * `expiry` - Time to expiration (years)
+    /// * `volatility` - Volatility (annualized)
+    /// * `interest_rate` - Risk-free rate
+    /// * `dividends` - Continuous dividend yield
+    fn parameters(
+        spot: Float,
+        strike: Float,
+        number_of_steps: usize,
+        expiry: Float,
+        volatility: Float,
+        interest_rate: Float,
+        dividends: Float,
+    ) -> VolatilityParameters;
+
+    /// Checks that the lattice is defined for trees with `number_of_steps` steps.
+    fn validate_steps(_number_of_steps: usize) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Cox-Ross-Rubinstein lattice.
+///
+/// Uses `u = exp(σ√Δt)` and `d = 1/u`, with the risk-neutral up probability
+/// `p = (a - d) / (u - d)` where `a = exp((r - q)Δt)`.
+impl Lattice for CoxRossRubenstein {
+    fn parameters(
+        _spot: Float,
+        _strike: Float,
+        number_of_steps: usize,
+        expiry: Float,
+        volatility: Float,
+        interest_rate: Float,
+        dividends: Float,
+    ) -> VolatilityParameters {
+        let timestep = expiry / number_of_steps as Float;
+        VolatilityParameters::new(volatility, interest_rate, dividends, timestep)
+    }
+}
+
+/// Jarrow-Rudd (equal-probability) lattice.
+///
+/// Adjusts both factors by the risk-neutral drift,
+/// `u = exp((r - q - σ²/2)Δt + σ√Δt)` and `d = exp((r - q - σ²/2)Δt - σ√Δt)`,
+/// and uses an up probability of exactly one half.
+impl Lattice for JarrowRudd {
+    fn parameters(
+        _spot: Float,
+        _strike: Float,
+        number_of_steps: usize,
+        expiry: Float,
+        volatility: Float,
+        interest_rate: Float,
+        dividends: Float,
+    ) -> VolatilityParameters {
+        let timestep = expiry / number_of_steps as Float;
+        let drift = (interest_rate - dividends - 0.5 * volatility.powi(2)) * timestep;
+        let diffusion = volatility * timestep.sqrt();
+
+        VolatilityParameters::with_factors(
+            (drift + diffusion).exp(),
+            (drift - diffusion).exp(),
+            0.5,
+            volatility,
+            interest_rate,
+            dividends,
+        )
+    }
+}
+
+/// Tian (1993) third-moment-matching lattice.
+///
+/// Chooses `u`, `d` and `p` such that the first three moments of the binomial
+/// step match those of the lognormal distribution. With `a = exp((r - q)Δt)` and
+/// `v = exp(σ²Δt)`, `u, d = a·v/2 · (v + 1 ± √(v² + 2v - 3))`.
+impl Lattice for Tian {
+    fn parameters(
+        _spot: Float,
+        _strike: Float,
+        number_of_steps: usize,
+        expiry: Float,
+        volatility: Float,
+        interest_rate: Float,
+        dividends: Float,
+    ) -> VolatilityParameters {
+        let timestep = expiry / number_of_steps as Float;
+        let a = ((interest_rate - dividends) * timestep).exp();
+        let v = (volatility.powi(2) * timestep).exp();
+        let root = (v.powi(2) + 2.0 * v - 3.0).sqrt();
+
+        let u = 0.5 * a * v * (v + 1.0 + root);
+        let d = 0.5 * a * v * (v + 1.0 - root);
+
+        VolatilityParameters::with_factors(
+            u,
+            d,
+            (a - d) / (u - d),
+            volatility,
+            interest_rate,
+            dividends,
+        )
+    }
+}
+
+/// Leisen-Reimer lattice.
+///
+/// Centres the tree on the strike by inverting the Black-Scholes `d1` and `d2`
+/// with the Peizer-Pratt method 2 approximation of the binomial distribution.
+/// The inversion is only defined for an odd number of steps, which put the strike
+/// between the two middle leaves. This gives smooth, second-order convergence for
+/// European options.
+///
+/// # Panics
+///
+/// For an even number of steps, which `try_new` rejects with [`Error::EvenSteps`].
+impl Lattice for LeisenReimer {
+    fn parameters(
+        spot: Float,
+        strike: Float,
+        number_of_steps: usize,
+        expiry: Float,
+        volatility: Float,
+        interest_rate: Float,
+        dividends: Float,
+    ) -> VolatilityParameters {
+        assert!(
+            !number_of_steps.is_multiple_of(2),
+            "The Leisen-Reimer lattice needs an odd number of steps"
+        );
+        let timestep = expiry / number_of_steps as Float;
+
+        let vol_sqrt_expiry = volatility * expiry.sqrt();
+        let d1 = ((spot / strike).ln()
+            + (interest_rate - dividends + 0.5 * volatility.powi(2)) * expiry)
+            / vol_sqrt_expiry;
+        let d2 = d1 - vol_sqrt_expiry;
+
+        let a = ((interest_rate - dividends) * timestep).exp();
+        let p = peizer_pratt_inversion(d2, number_of_steps);
+        let p_bar = peizer_pratt_inversion(d1, number_of_steps);
+        let u = a * p_bar / p;
+        let d = (a - p * u) / (1.0 - p);
+
+        VolatilityParameters::with_factors(u, d, p, volatility, interest_rate, dividends)
+    }
+
+    fn validate_steps(number_of_steps: usize) -> Result<(), Error> {
+        if number_of_steps.is_multiple_of(2) {
+            Err(Error::EvenSteps(number_of_steps))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Peizer-Pratt method 2 inversion, `h(z, n)`, of the cumulative normal distribution.
+///
+/// Gives the probability `p` for which a binomial distribution with `n` (odd) steps
+/// approximates the standard normal probability `N(z)`.
+fn peizer_pratt_inversion(z: Float, number_of_steps: usize) -> Float {
+    let n = number_of_steps as Float;
+    let x = z / (n + 1.0 / 3.0 + 0.1 / (n + 1.0));
+
+    0.5 + z.signum() * 0.5 * (1.0 - (-x.powi(2) * (n + 1.0 / 6.0)).exp()).sqrt()
+}
+
+/// Marker type for the Cox-Ross-Rubinstein lattice.
+pub struct CoxRossRubenstein;
+
+/// Marker type for the Jarrow-Rudd lattice.
+pub struct JarrowRudd;
+
+/// Marker type for the Tian lattice.
+pub struct Tian;
+
+/// Marker type for the Leisen-Reimer lattice.
+pub struct LeisenReimer;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cox_ross_rubenstein_parameters() {
+        let params = CoxRossRubenstein::parameters(100.0, 95.0, 2, 0.5, 0.3, 0.05, 0.0);
+
+        assert_eq!(params.u * params.d, 1.0);
+        assert!(params.p() > 0.0 && params.p() < 1.0);
+    }
+
+    #[test]
+    fn test_jarrow_rudd_parameters() {
+        let params = JarrowRudd::parameters(100.0, 95.0, 2, 0.5, 0.3, 0.05, 0.0);
+
+        assert_eq!(params.p(), 0.5);
+        assert_reference_eq!(params.u, 1.1632874);
+        assert_reference_eq!(params.d, 0.8617845);
+    }
+
+    #[test]
+    fn test_tian_parameters() {
+        let params = Tian::parameters(100.0, 95.0, 2, 0.5, 0.3, 0.05, 0.0);
+        let a = ((0.05 as Float) * 0.25).exp();
+
+        assert_reference_eq!(params.u, 1.2040672);
+        assert_reference_eq!(params.d, 0.89073795);
+        assert!((params.p() * params.u + (1.0 - params.p()) * params.d - a).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_peizer_pratt_inversion() {
+        assert_eq!(peizer_pratt_inversion(0.0, 25), 0.5);
+        assert!(peizer_pratt_inversion(1.0, 25) > 0.5);
+        assert!(
+            (peizer_pratt_inversion(-1.0, 25) - (1.0 - peizer_pratt_inversion(1.0, 25))).abs()
+                < 1e-6
+        );
+    }
+
+    #[test]
+    fn test_leisen_reimer_parameters() {
+        let params = LeisenReimer::parameters(100.0, 95.0, 25, 0.5, 0.3, 0.05, 0.0);
+        let a = ((0.05 as Float) * 0.5 / 25.0).exp();
+
+        assert!(params.p() > 0.0 && params.p() < 1.0);
+        assert!((params.p() * params.u + (1.0 - params.p()) * params.d - a).abs() < 1e-6);
+
+        assert_eq!(LeisenReimer::validate_steps(25), Ok(()));
+        assert_eq!(LeisenReimer::validate_steps(24), Err(Error::EvenSteps(24)));
+        assert_eq!(CoxRossRubenstein::validate_steps(24), Ok(()));
+    }
+}
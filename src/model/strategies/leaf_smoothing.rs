@@ -3,8 +3,9 @@
 /// This module defines strategies for evaluating option values at the terminal nodes
 /// (leaves) of a binomial tree. Different strategies can provide different levels
 /// of accuracy or smoothing.
-use crate::black_scholes::black_value;
-use crate::instruments::OptionContract;
+use crate::Float;
+use crate::analytical::AmericanApproximation;
+use crate::instruments::{NodeTime, OptionContract};
 use crate::model::VolatilityParameters;
 
 /// Trait for different leaf node smoothing strategies.
@@ -21,12 +22,33 @@ pub trait ValueAtLeaf {
     /// * `price` - The stock price at this node
     /// * `vol_params` - Volatility and rate parameters
     /// * `expiry` - Time remaining until expiration
+    /// * `time` - Time of the leaf level
     fn value_at_leaf<U: OptionContract + Sync>(
         option: &U,
-        price: f32,
+        price: Float,
         vol_params: &VolatilityParameters,
-        expiry: f32,
-    ) -> f32;
+        expiry: Float,
+        time: NodeTime,
+    ) -> Float;
+
+    /// Compute the option value one time step before expiry.
+    ///
+    /// # Arguments
+    ///
+    /// * `option` - The option contract being evaluated
+    /// * `value` - The discounted expected value of the leaves
+    /// * `price` - The stock price at this node
+    /// * `vol_params` - Volatility and rate parameters
+    /// * `expiry` - Time remaining until expiration
+    /// * `time` - Time of the node's level
+    fn value_before_expiry<U: OptionContract + Sync>(
+        option: &U,
+        value: Float,
+        price: Float,
+        vol_params: &VolatilityParameters,
+        expiry: Float,
+        time: NodeTime,
+    ) -> Float;
 }
 
 /// No smoothing strategy - uses only intrinsic value.
@@ -37,12 +59,24 @@ pub trait ValueAtLeaf {
 impl ValueAtLeaf for None {
     fn value_at_leaf<U: OptionContract + Sync>(
         option: &U,
-        price: f32,
+        price: Float,
         _vol_params: &VolatilityParameters,
-        _expiry: f32,
-    ) -> f32 {
+        _expiry: Float,
+        _time: NodeTime,
+    ) -> Float {
         option.intrinsic_value(price)
     }
+
+    fn value_before_expiry<U: OptionContract + Sync>(
+        option: &U,
+        value: Float,
+        price: Float,
+        _vol_params: &VolatilityParameters,
+        _expiry: Float,
+        time: NodeTime,
+    ) -> Float {
+        option.value(value, price, time)
+    }
 }
 
 /// Black-Scholes smoothing strategy.
@@ -53,22 +87,156 @@ impl ValueAtLeaf for None {
 impl ValueAtLeaf for Black {
     fn value_at_leaf<U: OptionContract + Sync>(
         option: &U,
-        price: f32,
+        price: Float,
+        vol_params: &VolatilityParameters,
+        expiry: Float,
+        time: NodeTime,
+    ) -> Float {
+        let time_to_expiry = expiry; // There is one timestep left to expiry
+        let black_value = option.black_value(
+            price,
+            vol_params.volatility,
+            vol_params.interest_rate,
+            vol_params.dividends,
+            time_to_expiry,
+        );
+        option.value(black_value, price, time)
+    }
+
+    fn value_before_expiry<U: OptionContract + Sync>(
+        option: &U,
+        value: Float,
+        price: Float,
+        _vol_params: &VolatilityParameters,
+        _expiry: Float,
+        time: NodeTime,
+    ) -> Float {
+        option.value(value, price, time)
+    }
+}
+
+/// Broadie and Detemple smoothing strategy.
+///
+/// One time step before expiry, this strategy replaces the continuation value
+/// by the option's analytical European value (Broadie and Detemple, 1996). This
+/// smooths the payoff's kink, or its jump for digital options, and gives much better
+/// convergence with the number of steps.
+impl ValueAtLeaf for BroadieDetemple {
+    fn value_at_leaf<U: OptionContract + Sync>(
+        option: &U,
+        price: Float,
+        _vol_params: &VolatilityParameters,
+        _expiry: Float,
+        _time: NodeTime,
+    ) -> Float {
+        option.intrinsic_value(price)
+    }
+
+    fn value_before_expiry<U: OptionContract + Sync>(
+        option: &U,
+        _value: Float,
+        price: Float,
         vol_params: &VolatilityParameters,
-        expiry: f32,
-    ) -> f32 {
+        expiry: Float,
+        time: NodeTime,
+    ) -> Float {
         let time_to_expiry = expiry; // There is one timestep left to expiry
-        let black_value = black_value(
-            option.option_type(),
+        let black_value = option.black_value(
             price,
-            option.strike(),
             vol_params.volatility,
             vol_params.interest_rate,
             vol_params.dividends,
             time_to_expiry,
         );
-        option.value(black_value, price)
+        option.value(black_value, price, time)
+    }
+}
+
+/// Barone-Adesi and Whaley smoothing strategy.
+///
+/// As [`BroadieDetemple`], with the quadratic approximation of Barone-Adesi and Whaley for
+/// American contracts, which accounts for exercising during the last time step.
+impl ValueAtLeaf for BaroneAdesiWhaley {
+    fn value_at_leaf<U: OptionContract + Sync>(
+        option: &U,
+        price: Float,
+        _vol_params: &VolatilityParameters,
+        _expiry: Float,
+        _time: NodeTime,
+    ) -> Float {
+        option.intrinsic_value(price)
+    }
+
+    fn value_before_expiry<U: OptionContract + Sync>(
+        option: &U,
+        _value: Float,
+        price: Float,
+        vol_params: &VolatilityParameters,
+        expiry: Float,
+        time: NodeTime,
+    ) -> Float {
+        american_value_before_expiry(
+            AmericanApproximation::BaroneAdesiWhaley,
+            option,
+            price,
+            vol_params,
+            expiry,
+            time,
+        )
+    }
+}
+
+/// Bjerksund and Stensland smoothing strategy.
+///
+/// As [`BaroneAdesiWhaley`], with the two-boundary approximation of Bjerksund and
+/// Stensland (2002).
+impl ValueAtLeaf for BjerksundStensland {
+    fn value_at_leaf<U: OptionContract + Sync>(
+        option: &U,
+        price: Float,
+        _vol_params: &VolatilityParameters,
+        _expiry: Float,
+        _time: NodeTime,
+    ) -> Float {
+        option.intrinsic_value(price)
     }
+
+    fn value_before_expiry<U: OptionContract + Sync>(
+        option: &U,
+        _value: Float,
+        price: Float,
+        vol_params: &VolatilityParameters,
+        expiry: Float,
+        time: NodeTime,
+    ) -> Float {
+        american_value_before_expiry(
+            AmericanApproximation::BjerksundStensland,
+            option,
+            price,
+            vol_params,
+            expiry,
+            time,
+        )
+    }
+}
+
+fn american_value_before_expiry<U: OptionContract>(
+    approximation: AmericanApproximation,
+    option: &U,
+    price: Float,
+    vol_params: &VolatilityParameters,
+    time_to_expiry: Float,
+    time: NodeTime,
+) -> Float {
+    let american_value = option.american_value(
+        approximation,
+        price,
+        vol_params.volatility,
+        vol_params.interest_rate,
+        vol_params.dividends,
+        time_to_expiry,
+    );
+    option.value(american_value, price, time)
 }
 
 /// Marker type for no smoothing strategy.
@@ -76,3 +244,12 @@ pub struct None;
 
 /// Marker type for Black-Scholes smoothing strategy.
 pub struct Black;
+
+/// Marker type for Broadie and Detemple smoothing strategy.
+pub struct BroadieDetemple;
+
+/// Marker type for Barone-Adesi and Whaley smoothing strategy.
+pub struct BaroneAdesiWhaley;
+
+/// Marker type for Bjerksund and Stensland smoothing strategy.
+pub struct BjerksundStensland;
@@ -3,10 +3,13 @@
 //! This module provides strategy traits and implementations for:
 //! - **Leaf Smoothing** - How to value terminal nodes in the tree
 //! - **Border Truncation** - Which boundary nodes to include or exclude
+//! - **Lattice** - How the up/down factors and probabilities are parametrised
 
 pub mod border_truncation;
+pub mod lattice;
 pub mod leaf_smoothing;
 
 // Re-export commonly used items
 pub use border_truncation::ValueAtBorder;
+pub use lattice::Lattice;
 pub use leaf_smoothing::ValueAtLeaf;
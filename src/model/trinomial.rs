@@ -0,0 +1,1077 @@
+use crate::Float;
+use crate::binomial_tree_map::nodes::{NodeNameTrait, TrinomialNodeNameTrait};
+use crate::binomial_tree_map::{BinomialTreeMapImpl, BinomialTreeStackImpl};
+use crate::error::{Error, positive};
+use crate::instruments::{NodeTime, OptionContract};
+use crate::model::control_variate::European;
+use crate::model::induction::{Children, Induction, Level};
+use crate::model::{
+    Bumps, Delta, EvaluatedBinomialTree, Expiry, FullGreeks, Gamma, Greeks, Psi, Rho, Spot, Theta,
+    Value, Vega, VolatilityParameters, border_truncation, leaf_smoothing,
+};
+
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Fewest steps of a trinomial tree, whose greeks only look one step ahead.
+const MIN_STEPS: usize = 1;
+
+/// Stretch parameter giving equal probabilities for a driftless underlying,
+/// `λ = √(3/2)`, as suggested by Kamrad and Ritchken.
+pub const DEFAULT_STRETCH: Float = 1.224_744_9;
+
+/// Trinomial tree model using the Kamrad-Ritchken parametrisation.
+///
+/// Each step moves the price up by `u = exp(λσ√Δt)`, leaves it unchanged, or moves it
+/// down by `d = 1/u`. The stretch parameter `λ` controls the spacing of the lattice;
+/// `λ = 1` collapses the middle probability and recovers a binomial tree, while
+/// `λ = √3` corresponds to Boyle's original choice of spacing.
+pub struct TrinomialTreeModel<Stack, V = leaf_smoothing::None, U = border_truncation::None> {
+    stack: Stack,
+    params: TrinomialParameters,
+    spot: Spot,
+    expiry: Expiry,
+    number_of_steps: usize,
+    discount_factor: Float,
+    time_step: Float,
+    bumps: Bumps,
+    /// Whether the evaluation carries the tree of the European counterpart along
+    control_variate: bool,
+    _phantom_data: PhantomData<V>,
+    _phantom_data2: PhantomData<U>,
+}
+
+#[allow(private_bounds)]
+impl<
+    Stack: BinomialTreeStackImpl<
+        NodeNameContainerType: BinomialTreeMapImpl<NodeNameType: TrinomialNodeNameTrait>,
+    >,
+    V: leaf_smoothing::ValueAtLeaf,
+    U: border_truncation::ValueAtBorder,
+> TrinomialTreeModel<Stack, V, U>
+{
+    pub fn new(
+        stack: Stack,
+        initial_price: Spot,
+        number_of_steps: usize,
+        expiry: Expiry,
+        volatility: Float,
+        interest_rate: Float,
+        dividends: Float,
+    ) -> Self {
+        let time_step = expiry.0 / number_of_steps as Float;
+
+        Self {
+            stack,
+            params: TrinomialParameters::new(
+                volatility,
+                interest_rate,
+                dividends,
+                time_step,
+                DEFAULT_STRETCH,
+            ),
+            spot: initial_price,
+            expiry,
+            number_of_steps,
+            discount_factor: (-interest_rate * time_step).exp(),
+            time_step,
+            bumps: Bumps::default(),
+            control_variate: false,
+            _phantom_data: Default::default(),
+            _phantom_data2: Default::default(),
+        }
+    }
+
+    /// Model with validated inputs, see [`Self::new`].
+    ///
+    /// Rejects a non-positive spot, expiry or volatility, trees without steps, and lattices
+    /// whose probabilities lie outside (0, 1).
+    pub fn try_new(
+        stack: Stack,
+        initial_price: Spot,
+        number_of_steps: usize,
+        expiry: Expiry,
+        volatility: Float,
+        interest_rate: Float,
+        dividends: Float,
+    ) -> Result<Self, Error> {
+        positive(initial_price.0, Error::NonPositiveSpot)?;
+        positive(expiry.0, Error::NonPositiveExpiry)?;
+        positive(volatility, Error::NonPositiveVolatility)?;
+        if number_of_steps < MIN_STEPS {
+            return Err(Error::TooFewSteps {
+                steps: number_of_steps,
+                minimum: MIN_STEPS,
+            });
+        }
+
+        let model = Self::new(
+            stack,
+            initial_price,
+            number_of_steps,
+            expiry,
+            volatility,
+            interest_rate,
+            dividends,
+        );
+        model.params.validate()?;
+        Ok(model)
+    }
+
+    /// Sets the stretch parameter `λ` of the lattice. Must be at least one, which
+    /// [`Self::try_eval`] checks.
+    pub fn with_stretch(mut self, stretch: Float) -> Self {
+        self.params = TrinomialParameters::new(
+            self.params.vol_params.volatility,
+            self.params.vol_params.interest_rate,
+            self.params.vol_params.dividends,
+            self.time_step,
+            stretch,
+        );
+        self
+    }
+
+    /// Sets the bump sizes used for vega, rho and psi of the evaluated tree. They have to
+    /// be positive, which [`Self::try_eval`] checks.
+    pub fn with_bumps(mut self, bumps: Bumps) -> Self {
+        self.bumps = bumps;
+        self
+    }
+
+    /// Evaluates the European counterpart of the option in the same backward induction,
+    /// see [`BinomialTreeModel::with_control_variate`](crate::model::BinomialTreeModel::with_control_variate).
+    pub fn with_control_variate(mut self) -> Self {
+        self.control_variate = true;
+        self
+    }
+
+    /// Calculates the discounted node value in the trinomial tree.
+    ///
+    /// # Returns
+    /// The discounted expected value:
+    /// `(up_value * p_u + middle_value * p_m + down_value * p_d) * discount_factor`
+    fn calculate_node_value(
+        &self,
+        up_value: Float,
+        middle_value: Float,
+        down_value: Float,
+    ) -> Float {
+        (up_value * self.params.p_up
+            + middle_value * self.params.p_middle
+            + down_value * self.params.p_down)
+            * self.discount_factor
+    }
+
+    /// Evaluates the option on the tree.
+    ///
+    /// # Panics
+    ///
+    /// Panics for barrier options, see [`Self::try_eval`].
+    pub fn eval<T: OptionContract + Sync>(
+        self,
+        option: T,
+    ) -> EvaluatedTrinomialTreeModelImpl<Stack, V, U, T> {
+        assert!(
+            option.barrier().is_none(),
+            "Barrier options are priced on the binomial tree model"
+        );
+        let (tree_map, european) = self.backward_induction(&option, self.control_variate);
+
+        EvaluatedTrinomialTreeModelImpl {
+            model: self,
+            map: tree_map,
+            european,
+            option,
+        }
+    }
+
+    /// Evaluates the option after checking its strike, that the node stack has the steps
+    /// of the model, and the lattice and bump sizes set through the builders. Barrier
+    /// options, which are priced on the binomial tree model, are reported as an error
+    /// instead of panicking.
+    pub fn try_eval<T: OptionContract + Sync>(
+        self,
+        option: T,
+    ) -> Result<EvaluatedTrinomialTreeModelImpl<Stack, V, U, T>, Error> {
+        if option.barrier().is_some() {
+            return Err(Error::UnsupportedBarrier);
+        }
+        self.params.validate()?;
+        self.bumps.validate(self.params.vol_params.volatility)?;
+        let stack_steps = self.stack.iter().len().saturating_sub(1);
+        if stack_steps != self.number_of_steps {
+            return Err(Error::StackSteps {
+                stack_steps,
+                number_of_steps: self.number_of_steps,
+            });
+        }
+        if let Some(strike) = option.strike() {
+            positive(strike, Error::NonPositiveStrike)?;
+        }
+        Ok(self.eval(option))
+    }
+
+    /// Evaluates the option backwards from the leaves to the initial node, together with
+    /// the tree of its European counterpart if `with_european` is set.
+    fn backward_induction<T: OptionContract + Sync>(
+        &self,
+        option: &T,
+        with_european: bool,
+    ) -> (
+        <Stack as BinomialTreeStackImpl>::NodeNameContainerType,
+        Option<<Stack as BinomialTreeStackImpl>::NodeNameContainerType>,
+    ) {
+        let mut tree_map = <Stack as BinomialTreeStackImpl>::NodeNameContainerType::default();
+        let mut european_map =
+            with_european.then(<Stack as BinomialTreeStackImpl>::NodeNameContainerType::default);
+        let vol_params = &self.params.vol_params;
+        let induction =
+            Induction::<T, V, U>::new(option, vol_params, self.spot.0, self.expiry.0, None);
+        let european = European(option);
+        let european_induction = Induction::<European<T>, V, U>::new(
+            &european,
+            vol_params,
+            self.spot.0,
+            self.expiry.0,
+            None,
+        );
+
+        let last_level = self.stack.iter().len() - 1;
+        for (i, node_level) in self.stack.iter().enumerate().rev() {
+            let level = Level {
+                time_to_expiry: self.expiry.0 - self.time_step * (i as Float),
+                time: NodeTime {
+                    time: self.time_step * i as Float,
+                    time_step: self.time_step,
+                },
+                leaves: i == last_level,
+                before_leaves: i + 1 == last_level,
+            };
+
+            node_level.iter().rev().enumerate().for_each(|(j, node)| {
+                let children = |map: &<Stack as BinomialTreeStackImpl>::NodeNameContainerType| {
+                    let value = |node| map.value(&node);
+                    Children::of(
+                        [value(node.up()), value(node.middle()), value(node.down())],
+                        |[up_value, middle_value, down_value]| {
+                            self.calculate_node_value(up_value, middle_value, down_value)
+                        },
+                    )
+                };
+
+                let price = self
+                    .params
+                    .calculate_price(self.spot.0, j as isize - i as isize);
+
+                if let Some(option_value) =
+                    induction.node_value(&level, price, children(&tree_map), None)
+                {
+                    tree_map.set(node, option_value.into());
+                }
+                if let Some(european_map) = european_map.as_mut()
+                    && let Some(european_value) =
+                        european_induction.node_value(&level, price, children(european_map), None)
+                {
+                    european_map.set(node, european_value.into());
+                }
+            });
+        }
+
+        (tree_map, european_map)
+    }
+}
+
+#[allow(private_bounds)]
+pub struct EvaluatedTrinomialTreeModelImpl<
+    Stack: BinomialTreeStackImpl,
+    V: leaf_smoothing::ValueAtLeaf,
+    U: border_truncation::ValueAtBorder,
+    T: OptionContract,
+> {
+    model: TrinomialTreeModel<Stack, V, U>,
+    map: <Stack as BinomialTreeStackImpl>::NodeNameContainerType,
+    /// Tree of the European counterpart, carried along for the control variate
+    european: Option<<Stack as BinomialTreeStackImpl>::NodeNameContainerType>,
+    option: T,
+}
+
+impl<
+    Stack: BinomialTreeStackImpl,
+    V: leaf_smoothing::ValueAtLeaf,
+    U: border_truncation::ValueAtBorder,
+    T: OptionContract,
+> fmt::Display for EvaluatedTrinomialTreeModelImpl<Stack, V, U, T>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const GAP: usize = 2; // minimum spacing between sibling nodes
+
+        let levels = self.model.stack.iter().len();
+        if levels == 0 {
+            return writeln!(f, "<empty tree>");
+        }
+
+        let mut node_data: Vec<Vec<(String, String)>> = Vec::with_capacity(levels);
+        for (i, level) in self.model.stack.iter().enumerate() {
+            let row = level
+                .iter()
+                .rev()
+                .enumerate()
+                .map(|(j, node)| {
+                    let price = self
+                        .model
+                        .params
+                        .calculate_price(self.model.spot.0, j as isize - i as isize);
+                    let value = match self.map.value(node) {
+                        Some(value) => format!("V:{:.4}", value),
+                        None => "V:-".to_string(),
+                    };
+                    (format!("P:{:.2}", price), value)
+                })
+                .collect();
+            node_data.push(row);
+        }
+
+        let node_width = node_data
+            .iter()
+            .flat_map(|r| r.iter())
+            .map(|(p, v)| p.len().max(v.len()))
+            .max()
+            .unwrap_or(8)
+            + GAP;
+        let leaf_count = node_data[levels - 1].len();
+
+        // Every level is centred below the root; each level adds one node on either side
+        for row in node_data.iter() {
+            let indent = (leaf_count - row.len()) / 2 * node_width;
+            let prices: String = row
+                .iter()
+                .map(|(p, _)| format!("{:^node_width$}", p))
+                .collect();
+            let values: String = row
+                .iter()
+                .map(|(_, v)| format!("{:^node_width$}", v))
+                .collect();
+
+            writeln!(f, "{:indent$}{}", "", prices.trim_end())?;
+            writeln!(f, "{:indent$}{}", "", values.trim_end())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[allow(private_bounds)]
+impl<
+    Stack: BinomialTreeStackImpl<
+        NodeNameContainerType: BinomialTreeMapImpl<NodeNameType: TrinomialNodeNameTrait>,
+    >,
+    V: leaf_smoothing::ValueAtLeaf,
+    U: border_truncation::ValueAtBorder,
+    T: OptionContract + Sync,
+> EvaluatedTrinomialTreeModelImpl<Stack, V, U, T>
+{
+    fn initial_node(
+        &self,
+    ) -> <<Stack as BinomialTreeStackImpl>::NodeNameContainerType as BinomialTreeMapImpl>::NodeNameType
+    {
+        Default::default()
+    }
+
+    fn node_value(
+        &self,
+        node: &<<Stack as BinomialTreeStackImpl>::NodeNameContainerType as BinomialTreeMapImpl>::NodeNameType,
+    ) -> Float {
+        self.map.value(node).unwrap()
+    }
+
+    pub fn value(&self) -> Value {
+        Value(self.node_value(&self.initial_node()))
+    }
+
+    pub fn delta(&self) -> Delta {
+        self.first_step_greeks(&|node, _, _| self.node_value(node))
+            .0
+    }
+
+    /// Gamma read off the three nodes of the first time step.
+    pub fn gamma(&self) -> Gamma {
+        self.first_step_greeks(&|node, _, _| self.node_value(node))
+            .1
+    }
+
+    /// Theta read off the middle node of the first time step, which has the same price as
+    /// the root.
+    pub fn theta(&self) -> Theta {
+        self.first_step_greeks(&|node, _, _| self.node_value(node))
+            .2
+    }
+
+    /// Delta, gamma and theta of the node values `value(node, price, step)` of the root and
+    /// the first time step.
+    fn first_step_greeks(
+        &self,
+        value: &impl Fn(&<<Stack as BinomialTreeStackImpl>::NodeNameContainerType as BinomialTreeMapImpl>::NodeNameType, Float, usize) -> Float,
+    ) -> (Delta, Gamma, Theta) {
+        let initial_node = self.initial_node();
+        let price_u = self.model.params.calculate_price(self.model.spot.0, 1);
+        let price_m = self.model.spot.0;
+        let price_d = self.model.params.calculate_price(self.model.spot.0, -1);
+
+        let value_0 = value(&initial_node, price_m, 0);
+        let value_u = value(&initial_node.up(), price_u, 1);
+        let value_m = value(&initial_node.middle(), price_m, 1);
+        let value_d = value(&initial_node.down(), price_d, 1);
+
+        let delta_u = (value_u - value_m) / (price_u - price_m);
+        let delta_d = (value_m - value_d) / (price_m - price_d);
+
+        (
+            Delta((value_u - value_d) / (price_u - price_d)),
+            Gamma((delta_u - delta_d) / (0.5 * (price_u - price_d))),
+            Theta((value_m - value_0) / self.model.time_step),
+        )
+    }
+
+    /// Model with bumped market parameters, sharing the node stack.
+    fn bumped_model(
+        &self,
+        volatility: Float,
+        interest_rate: Float,
+        dividends: Float,
+    ) -> TrinomialTreeModel<&Stack, V, U> {
+        TrinomialTreeModel::new(
+            &self.model.stack,
+            Spot(self.model.spot.0),
+            self.model.number_of_steps,
+            Expiry(self.model.expiry.0),
+            volatility,
+            interest_rate,
+            dividends,
+        )
+        .with_stretch(self.model.params.stretch)
+    }
+
+    /// Re-evaluates the option value with bumped market parameters, sharing the node stack.
+    fn revalue(&self, volatility: Float, interest_rate: Float, dividends: Float) -> Float {
+        self.bumped_model(volatility, interest_rate, dividends)
+            .backward_induction(&self.option, false)
+            .0
+            .value(&self.initial_node())
+            .unwrap()
+    }
+
+    pub fn vega(&self) -> Vega {
+        let params = &self.model.params.vol_params;
+        Vega(self.model.bumps.difference.derivative(
+            params.volatility,
+            self.model.bumps.volatility,
+            self.value().0,
+            |volatility| self.revalue(volatility, params.interest_rate, params.dividends),
+        ))
+    }
+
+    pub fn rho(&self) -> Rho {
+        let params = &self.model.params.vol_params;
+        Rho(self.model.bumps.difference.derivative(
+            params.interest_rate,
+            self.model.bumps.interest_rate,
+            self.value().0,
+            |interest_rate| self.revalue(params.volatility, interest_rate, params.dividends),
+        ))
+    }
+
+    pub fn psi(&self) -> Psi {
+        let params = &self.model.params.vol_params;
+        Psi(self.model.bumps.difference.derivative(
+            params.dividends,
+            self.model.bumps.dividends,
+            self.value().0,
+            |dividends| self.revalue(params.volatility, params.interest_rate, dividends),
+        ))
+    }
+
+    /// Greeks read off the evaluated tree, without re-evaluating it.
+    pub fn greeks(&self) -> Greeks {
+        Greeks {
+            value: self.value(),
+            delta: self.delta(),
+            gamma: self.gamma(),
+            theta: self.theta(),
+        }
+    }
+
+    /// Value corrected with the European counterpart of the option as control variate,
+    /// see [`EvaluatedBinomialTreeModelImpl::control_variate_value`](crate::model::EvaluatedBinomialTreeModelImpl::control_variate_value).
+    pub fn control_variate_value(&self) -> Value {
+        self.with_european(|european| {
+            Value(self.corrected_value(european, &self.initial_node(), self.model.spot.0, 0))
+        })
+    }
+
+    /// Greeks corrected with the European counterpart of the option as control variate.
+    pub fn control_variate_greeks(&self) -> Greeks {
+        self.with_european(|european| {
+            let value = self.corrected_value(european, &self.initial_node(), self.model.spot.0, 0);
+            let (delta, gamma, theta) = self.first_step_greeks(&|node, price, step| {
+                self.corrected_value(european, node, price, step)
+            });
+
+            Greeks {
+                value: Value(value),
+                delta,
+                gamma,
+                theta,
+            }
+        })
+    }
+
+    /// Corrected greeks together with vega, rho and psi of the corrected value, see
+    /// [`Self::control_variate_greeks`].
+    pub fn control_variate_full_greeks(&self) -> FullGreeks {
+        let greeks = self.control_variate_greeks();
+        let params = &self.model.params.vol_params;
+        let bumps = &self.model.bumps;
+        let derivative = |x, h, value_at: &dyn Fn(Float) -> Float| {
+            bumps.difference.derivative(x, h, greeks.value.0, value_at)
+        };
+        let vega = Vega(derivative(
+            params.volatility,
+            bumps.volatility,
+            &|volatility| {
+                self.revalue_control_variate(volatility, params.interest_rate, params.dividends)
+            },
+        ));
+        let rho = Rho(derivative(
+            params.interest_rate,
+            bumps.interest_rate,
+            &|rate| self.revalue_control_variate(params.volatility, rate, params.dividends),
+        ));
+        let psi = Psi(derivative(
+            params.dividends,
+            bumps.dividends,
+            &|dividends| {
+                self.revalue_control_variate(params.volatility, params.interest_rate, dividends)
+            },
+        ));
+        FullGreeks::new(greeks, vega, rho, psi)
+    }
+
+    /// Runs `f` on the tree of the European counterpart, evaluating it unless the model
+    /// carried it along.
+    fn with_european<R>(
+        &self,
+        f: impl FnOnce(&<Stack as BinomialTreeStackImpl>::NodeNameContainerType) -> R,
+    ) -> R {
+        match &self.european {
+            Some(european) => f(european),
+            None => f(&self
+                .model
+                .backward_induction(&European(&self.option), false)
+                .0),
+        }
+    }
+
+    /// Value of `node` at `price` on `step`, corrected with the `european` tree.
+    fn corrected_value(
+        &self,
+        european: &<Stack as BinomialTreeStackImpl>::NodeNameContainerType,
+        node: &<<Stack as BinomialTreeStackImpl>::NodeNameContainerType as BinomialTreeMapImpl>::NodeNameType,
+        price: Float,
+        step: usize,
+    ) -> Float {
+        let params = &self.model.params.vol_params;
+        let black_value = European(&self.option).black_value(
+            price,
+            params.volatility,
+            params.interest_rate,
+            params.dividends,
+            self.model.expiry.0 - self.model.time_step * step as Float,
+        );
+
+        self.node_value(node) - european.value(node).unwrap() + black_value
+    }
+
+    /// Control-variate value with bumped market parameters.
+    fn revalue_control_variate(
+        &self,
+        volatility: Float,
+        interest_rate: Float,
+        dividends: Float,
+    ) -> Float {
+        let (tree_map, european) = self
+            .bumped_model(volatility, interest_rate, dividends)
+            .backward_induction(&self.option, true);
+        let initial_node = self.initial_node();
+
+        tree_map.value(&initial_node).unwrap() - european.unwrap().value(&initial_node).unwrap()
+            + European(&self.option).black_value(
+                self.model.spot.0,
+                volatility,
+                interest_rate,
+                dividends,
+                self.model.expiry.0,
+            )
+    }
+}
+
+impl<
+    Stack: BinomialTreeStackImpl<
+        NodeNameContainerType: BinomialTreeMapImpl<NodeNameType: TrinomialNodeNameTrait>,
+    >,
+    V: leaf_smoothing::ValueAtLeaf,
+    U: border_truncation::ValueAtBorder,
+    T: OptionContract + Sync,
+> EvaluatedBinomialTree for EvaluatedTrinomialTreeModelImpl<Stack, V, U, T>
+{
+    fn value(&self) -> Value {
+        EvaluatedTrinomialTreeModelImpl::value(self)
+    }
+
+    fn delta(&self) -> Delta {
+        EvaluatedTrinomialTreeModelImpl::delta(self)
+    }
+
+    fn gamma(&self) -> Gamma {
+        EvaluatedTrinomialTreeModelImpl::gamma(self)
+    }
+
+    fn theta(&self) -> Theta {
+        EvaluatedTrinomialTreeModelImpl::theta(self)
+    }
+
+    fn vega(&self) -> Vega {
+        EvaluatedTrinomialTreeModelImpl::vega(self)
+    }
+
+    fn rho(&self) -> Rho {
+        EvaluatedTrinomialTreeModelImpl::rho(self)
+    }
+
+    fn psi(&self) -> Psi {
+        EvaluatedTrinomialTreeModelImpl::psi(self)
+    }
+
+    fn greeks(&self) -> Greeks {
+        EvaluatedTrinomialTreeModelImpl::greeks(self)
+    }
+
+    fn control_variate_value(&self) -> Value {
+        EvaluatedTrinomialTreeModelImpl::control_variate_value(self)
+    }
+
+    fn control_variate_greeks(&self) -> Greeks {
+        EvaluatedTrinomialTreeModelImpl::control_variate_greeks(self)
+    }
+
+    fn control_variate_full_greeks(&self) -> FullGreeks {
+        EvaluatedTrinomialTreeModelImpl::control_variate_full_greeks(self)
+    }
+}
+
+/// Parameters of a single step in the Kamrad-Ritchken trinomial lattice.
+#[derive(Copy, Clone)]
+pub struct TrinomialParameters {
+    vol_params: VolatilityParameters,
+    p_up: Float,
+    p_middle: Float,
+    p_down: Float,
+    stretch: Float,
+}
+
+impl TrinomialParameters {
+    pub fn new(
+        volatility: Float,
+        interest_rate: Float,
+        dividends: Float,
+        timestep: Float,
+        stretch: Float,
+    ) -> TrinomialParameters {
+        let dx = stretch * volatility * timestep.sqrt();
+        let u = dx.exp();
+        let drift = (interest_rate - dividends - 0.5 * volatility.powi(2)) * timestep.sqrt();
+
+        let p_center = 1.0 / (2.0 * stretch.powi(2));
+        let p_drift = drift / (2.0 * stretch * volatility);
+
+        TrinomialParameters {
+            vol_params: VolatilityParameters::with_factors(
+                u,
+                1.0 / u,
+                p_center + p_drift,
+                volatility,
+                interest_rate,
+                dividends,
+            ),
+            p_up: p_center + p_drift,
+            p_middle: 1.0 - 1.0 / stretch.powi(2),
+            p_down: p_center - p_drift,
+            stretch,
+        }
+    }
+
+    /// Checks that the stretch is at least one, which keeps the middle probability
+    /// non-negative, and that the probabilities admit no arbitrage, which fails for too few
+    /// steps as for the binomial lattices.
+    pub(crate) fn validate(&self) -> Result<(), Error> {
+        if self.stretch.is_nan() || self.stretch < 1.0 {
+            return Err(Error::StretchBelowOne(self.stretch));
+        }
+        for probability in [self.p_up, self.p_down] {
+            if !(probability > 0.0 && probability < 1.0) {
+                return Err(Error::Arbitrage { probability });
+            }
+        }
+        Ok(())
+    }
+
+    /// Calculates the asset price `net_ups` up moves (negative for down moves) from the spot.
+    pub(crate) fn calculate_price(&self, spot_price: Float, net_ups: isize) -> Float {
+        if net_ups >= 0 {
+            self.vol_params
+                .calculate_price(spot_price, net_ups as usize, 0)
+        } else {
+            self.vol_params
+                .calculate_price(spot_price, 0, net_ups.unsigned_abs())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binomial_tree_map::array::ArrayBinomialTreeMap;
+    use crate::binomial_tree_map::r#static::StaticBinomialTreeMap;
+    use crate::binomial_tree_map::trinomial::TrinomialTreeMap;
+    use crate::black_scholes::black_value;
+    use crate::instruments::{
+        AmericanOption, Barrier, BarrierKind, BarrierOption, EuropeanOption, Monitoring,
+        OptionType, StrikeContract,
+    };
+    use crate::model::CoxRossRubenstein;
+    #[cfg(test)]
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_trinomial_parameters() {
+        let params = TrinomialParameters::new(0.3, 0.05, 0.0, 0.01, DEFAULT_STRETCH);
+
+        assert!((params.p_up + params.p_middle + params.p_down - 1.0).abs() < 1e-6);
+        assert!((params.p_middle - 1.0 / 3.0).abs() < 1e-6);
+        assert!(params.p_up > params.p_down);
+
+        let params = TrinomialParameters::new(0.3, 0.05, 0.0, 0.01, 1.0);
+        assert_eq!(params.p_middle, 0.0);
+    }
+
+    #[test]
+    fn test_trinomial_tree_display() {
+        let model: TrinomialTreeModel<TrinomialTreeMap> = TrinomialTreeModel::new(
+            TrinomialTreeMap::new(2),
+            Spot(100.0),
+            2,
+            Expiry(0.5),
+            0.3,
+            0.05,
+            0.0,
+        );
+        let eval = model.eval(EuropeanOption::new(OptionType::Call, 95.0, 0.5));
+
+        let expected = r#"                       P:100.00
+                       V:12.5989
+             P:83.22   P:100.00   P:120.17
+            V:1.6628   V:10.0153  V:26.3423
+  P:69.25    P:83.22   P:100.00   P:120.17   P:144.40
+ V:0.0000   V:0.0000   V:5.0000   V:25.1669  V:49.4009"#;
+
+        pretty_assertions::assert_eq!(format!("{}", eval).trim_end(), expected);
+    }
+
+    #[test]
+    fn test_trinomial_tree_european_call() {
+        let model: TrinomialTreeModel<TrinomialTreeMap> = TrinomialTreeModel::new(
+            TrinomialTreeMap::new(100),
+            Spot(100.0),
+            100,
+            Expiry(0.5),
+            0.3,
+            0.05,
+            0.0,
+        );
+        let eval = model.eval(EuropeanOption::new(OptionType::Call, 95.0, 0.5));
+
+        let black = black_value(OptionType::Call, 100.0, 95.0, 0.3, 0.05, 0.0, 0.5);
+        assert!((eval.value().0 - black).abs() < 1e-2);
+        assert_reference_eq!(eval.value().0, 12.324249);
+    }
+
+    #[test]
+    fn test_trinomial_tree_try_new() {
+        fn model(
+            stack_steps: usize,
+            number_of_steps: usize,
+            volatility: Float,
+        ) -> Result<TrinomialTreeModel<TrinomialTreeMap>, Error> {
+            TrinomialTreeModel::try_new(
+                TrinomialTreeMap::new(stack_steps),
+                Spot(100.0),
+                number_of_steps,
+                Expiry(0.5),
+                volatility,
+                0.5,
+                0.0,
+            )
+        }
+
+        assert!(matches!(
+            model(0, 0, 0.3),
+            Err(Error::TooFewSteps {
+                steps: 0,
+                minimum: 1
+            })
+        ));
+        assert!(matches!(
+            model(10, 10, -0.3),
+            Err(Error::NonPositiveVolatility(-0.3))
+        ));
+        // The drift of a step outweighs its volatility
+        assert!(matches!(
+            model(1, 1, 0.01),
+            Err(Error::Arbitrage { probability }) if probability > 1.0
+        ));
+
+        let option = EuropeanOption::new(OptionType::Put, 105.0, 0.5);
+        assert!(matches!(
+            model(10, 20, 0.3).unwrap().try_eval(option),
+            Err(Error::StackSteps {
+                stack_steps: 10,
+                number_of_steps: 20
+            })
+        ));
+        assert_eq!(
+            model(20, 20, 0.3)
+                .unwrap()
+                .try_eval(option)
+                .unwrap()
+                .value(),
+            model(20, 20, 0.3).unwrap().eval(option).value()
+        );
+        // A stretch below one gives a negative middle probability
+        assert!(matches!(
+            model(20, 20, 0.3)
+                .unwrap()
+                .with_stretch(0.9)
+                .try_eval(option),
+            Err(Error::StretchBelowOne(0.9))
+        ));
+        assert!(
+            model(20, 20, 0.3)
+                .unwrap()
+                .with_stretch(1.0)
+                .try_eval(option)
+                .is_ok()
+        );
+        let bumps = Bumps {
+            dividends: -0.001,
+            ..Default::default()
+        };
+        assert!(matches!(
+            model(20, 20, 0.3)
+                .unwrap()
+                .with_bumps(bumps)
+                .try_eval(option),
+            Err(Error::NonPositiveBump(-0.001))
+        ));
+    }
+
+    #[test]
+    fn test_trinomial_tree_barrier() {
+        let model: TrinomialTreeModel<TrinomialTreeMap> = TrinomialTreeModel::new(
+            TrinomialTreeMap::new(10),
+            Spot(100.0),
+            10,
+            Expiry(0.5),
+            0.3,
+            0.05,
+            0.0,
+        );
+        let option = BarrierOption::with_barrier(
+            EuropeanOption::new(OptionType::Call, 95.0, 0.5),
+            Barrier {
+                kind: BarrierKind::UpAndOut,
+                level: 120.0,
+                rebate: 0.0,
+                monitoring: Monitoring::Continuous,
+            },
+        );
+
+        assert!(matches!(
+            model.try_eval(option),
+            Err(Error::UnsupportedBarrier)
+        ));
+    }
+
+    #[test]
+    fn test_trinomial_tree_american_put2_100steps() {
+        let model: TrinomialTreeModel<TrinomialTreeMap> = TrinomialTreeModel::new(
+            TrinomialTreeMap::new(100),
+            Spot(31.0),
+            100,
+            Expiry(0.75),
+            0.3,
+            0.05,
+            0.05,
+        );
+        let eval = model.eval(AmericanOption::new(OptionType::Put, 30.0, 0.75));
+
+        let greeks = eval.full_greeks();
+        assert_reference_eq!(greeks.value.0, 2.5965493);
+        assert_reference_eq!(greeks.delta.0, -0.38870984);
+        assert_reference_eq!(greeks.gamma.0, 0.047374785);
+        assert_reference_eq!(greeks.theta.0, -1.9191425);
+        // The small bumps of the revaluations magnify the rounding of the values
+        assert_reference_eq!(greeks.vega.0, 9.999955, 1e-3);
+        assert_reference_eq!(greeks.rho.0, -9.28247, 1e-3);
+        assert_reference_eq!(greeks.psi.0, 7.7863927, 1e-3);
+    }
+
+    fn eval_trinomial_american_put(spot: Float, steps: usize) -> FullGreeks {
+        let model: TrinomialTreeModel<TrinomialTreeMap> = TrinomialTreeModel::new(
+            TrinomialTreeMap::new(steps),
+            Spot(spot),
+            steps,
+            Expiry(0.5),
+            0.3,
+            0.05,
+            0.0,
+        );
+        model
+            .eval(AmericanOption::new(OptionType::Put, 95.0, 0.5))
+            .full_greeks()
+    }
+
+    #[test]
+    fn test_trinomial_tree_greeks_against_binomial() {
+        let trinomial = eval_trinomial_american_put(100.0, 200);
+
+        let binomial: CoxRossRubenstein<StaticBinomialTreeMap> = CoxRossRubenstein::new(
+            crate::binomial_tree_map!(128),
+            Spot(100.0),
+            128,
+            Expiry(0.5),
+            0.3,
+            0.05,
+            0.0,
+        );
+        let binomial = binomial
+            .eval(AmericanOption::new(OptionType::Put, 95.0, 0.5))
+            .full_greeks();
+
+        assert!((trinomial.value.0 - binomial.value.0).abs() < 1e-2);
+        assert!((trinomial.delta.0 - binomial.delta.0).abs() < 1e-2);
+        assert!((trinomial.theta.0 - binomial.theta.0).abs() < 1e-1);
+        assert!((trinomial.vega.0 - binomial.vega.0).abs() < 0.5);
+        assert!((trinomial.rho.0 - binomial.rho.0).abs() < 0.5);
+        assert!((trinomial.psi.0 - binomial.psi.0).abs() < 0.5);
+
+        // Gamma against a finite difference of the tree values in the spot
+        let h = 1.0;
+        let up = eval_trinomial_american_put(100.0 + h, 200).value.0;
+        let down = eval_trinomial_american_put(100.0 - h, 200).value.0;
+        let gamma = (up - 2.0 * trinomial.value.0 + down) / h.powi(2);
+        assert!((trinomial.gamma.0 - gamma).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_trinomial_tree_control_variate() {
+        let put = AmericanOption::new(OptionType::Put, 105.0, 0.5);
+        let binomial = |steps| {
+            let model: CoxRossRubenstein<ArrayBinomialTreeMap> = CoxRossRubenstein::new(
+                ArrayBinomialTreeMap::new(steps),
+                Spot(100.0),
+                steps,
+                Expiry(0.5),
+                0.3,
+                0.05,
+                0.02,
+            );
+            model.eval(put).value().0
+        };
+        let reference = (binomial(1000) + binomial(1001)) / 2.0;
+
+        let (mut tree_error, mut control_variate_error) = (0.0, 0.0);
+        for steps in 50..=55 {
+            let model: TrinomialTreeModel<TrinomialTreeMap> = TrinomialTreeModel::new(
+                TrinomialTreeMap::new(steps),
+                Spot(100.0),
+                steps,
+                Expiry(0.5),
+                0.3,
+                0.05,
+                0.02,
+            );
+            let eval = model.eval(put);
+            tree_error += (eval.value().0 - reference).abs();
+            control_variate_error += (eval.control_variate_value().0 - reference).abs();
+        }
+        assert!(control_variate_error < tree_error / 3.0);
+
+        // European contracts get their Black-Scholes value
+        let model: TrinomialTreeModel<TrinomialTreeMap> = TrinomialTreeModel::new(
+            TrinomialTreeMap::new(50),
+            Spot(100.0),
+            50,
+            Expiry(0.5),
+            0.3,
+            0.05,
+            0.02,
+        );
+        let eval = model.eval(EuropeanOption::new(OptionType::Put, 105.0, 0.5));
+        assert_eq!(
+            eval.control_variate_value().0,
+            black_value(OptionType::Put, 100.0, 105.0, 0.3, 0.05, 0.02, 0.5)
+        );
+        // Carrying the counterpart along the induction gives the same tree as evaluating it
+        let model = || -> TrinomialTreeModel<TrinomialTreeMap> {
+            TrinomialTreeModel::new(
+                TrinomialTreeMap::new(50),
+                Spot(100.0),
+                50,
+                Expiry(0.5),
+                0.3,
+                0.05,
+                0.02,
+            )
+        };
+        let eval = model().eval(put);
+        let carried = model().with_control_variate().eval(put);
+        assert_eq!(
+            carried.control_variate_greeks(),
+            eval.control_variate_greeks()
+        );
+        assert_eq!(
+            carried.control_variate_full_greeks(),
+            eval.control_variate_full_greeks()
+        );
+    }
+
+    #[test]
+    fn test_trinomial_tree_unit_stretch() {
+        // With λ = 1 the middle branch vanishes and the tree is binomial with u = exp(σ√Δt)
+        let trinomial: TrinomialTreeModel<TrinomialTreeMap> = TrinomialTreeModel::new(
+            TrinomialTreeMap::new(50),
+            Spot(100.0),
+            50,
+            Expiry(0.5),
+            0.3,
+            0.05,
+            0.0,
+        )
+        .with_stretch(1.0);
+        let value = trinomial
+            .eval(EuropeanOption::new(OptionType::Call, 95.0, 0.5))
+            .value();
+
+        let black = black_value(OptionType::Call, 100.0, 95.0, 0.3, 0.05, 0.0, 0.5);
+        assert!((value.0 - black).abs() < 5e-2);
+    }
+}
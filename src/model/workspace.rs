@@ -0,0 +1,238 @@
+use crate::binomial_tree_map::{
+    BinomialTreeMapImpl, BinomialTreeStackBuilder, BinomialTreeStackImpl,
+};
+use hashbrown::HashMap;
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::fmt;
+
+thread_local! {
+    /// Workspaces of the current thread, one per stack type.
+    static WORKSPACES: RefCell<HashMap<TypeId, Box<dyn Any>>> = RefCell::new(HashMap::new());
+}
+
+/// Node value maps kept between evaluations of trees on `Stack`, so that batches of
+/// evaluations reuse the memory of the maps instead of allocating new ones for every tree.
+///
+/// A workspace is moved into the tree evaluated with
+/// [`BinomialTreeModel::eval_in`](crate::model::BinomialTreeModel::eval_in), which also
+/// re-evaluates the bumped trees of vega, rho and psi with its maps, and
+/// [`EvaluatedBinomialTreeModelImpl::recycle`](crate::model::EvaluatedBinomialTreeModelImpl::recycle)
+/// gives the maps back for the next evaluation, together with the node stack of the tree.
+#[allow(private_bounds)]
+pub struct Workspace<Stack: BinomialTreeStackImpl> {
+    pub(crate) maps: MapPool<<Stack as BinomialTreeStackImpl>::NodeNameContainerType>,
+    /// Node stacks kept for reuse, by their number of steps
+    pub(crate) stacks: HashMap<usize, Stack>,
+}
+
+#[allow(private_bounds)]
+impl<Stack: BinomialTreeStackImpl> Workspace<Stack> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of maps kept for reuse.
+    pub fn len(&self) -> usize {
+        self.maps.maps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.maps.maps.is_empty()
+    }
+
+    /// Node stack with `number_of_steps` steps, reusing a kept one if there is any.
+    pub(crate) fn take_stack(&mut self, number_of_steps: usize) -> Stack
+    where
+        Stack: BinomialTreeStackBuilder,
+    {
+        self.stacks
+            .remove(&number_of_steps)
+            .unwrap_or_else(|| Stack::with_steps(number_of_steps))
+    }
+
+    /// Keeps `stack` for reuse by trees with the same number of steps.
+    pub(crate) fn give_stack(&mut self, stack: Stack) {
+        let number_of_steps = stack.iter().len().saturating_sub(1);
+        self.stacks.insert(number_of_steps, stack);
+    }
+
+    /// Runs `f` with the workspace of the current thread, e.g. in the closures of rayon
+    /// iterators, where each worker thread then reuses its own maps.
+    pub fn with_thread_local<R>(f: impl FnOnce(&mut Self) -> R) -> R
+    where
+        Stack: 'static,
+    {
+        // Taken out while `f` runs, so that nested calls start from a workspace of their own
+        let mut workspace = WORKSPACES
+            .with_borrow_mut(|workspaces| workspaces.remove(&TypeId::of::<Self>()))
+            .map(|workspace| {
+                *workspace
+                    .downcast::<Self>()
+                    .expect("Workspaces are keyed by their type")
+            })
+            .unwrap_or_default();
+
+        let result = f(&mut workspace);
+
+        WORKSPACES.with_borrow_mut(|workspaces| {
+            workspaces.insert(TypeId::of::<Self>(), Box::new(workspace))
+        });
+        result
+    }
+}
+
+#[allow(private_bounds)]
+impl<Stack: BinomialTreeStackImpl> Default for Workspace<Stack> {
+    fn default() -> Self {
+        Self {
+            maps: MapPool::default(),
+            stacks: HashMap::new(),
+        }
+    }
+}
+
+#[allow(private_bounds)]
+impl<Stack: BinomialTreeStackImpl> fmt::Debug for Workspace<Stack> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Workspace")
+            .field("maps", &self.maps)
+            .field("stacks", &self.stacks.keys())
+            .finish()
+    }
+}
+
+/// Empty maps for the trees of an evaluation.
+#[derive(Debug)]
+pub(crate) struct MapPool<Map> {
+    maps: Vec<Map>,
+}
+
+impl<Map> Default for MapPool<Map> {
+    fn default() -> Self {
+        Self { maps: Vec::new() }
+    }
+}
+
+impl<Map: BinomialTreeMapImpl + Default> MapPool<Map> {
+    /// An empty map, reusing a kept one if there is any.
+    pub(crate) fn take(&mut self) -> Map {
+        self.maps.pop().unwrap_or_default()
+    }
+
+    /// Clears `map` and keeps it for reuse.
+    pub(crate) fn give(&mut self, mut map: Map) {
+        map.clear();
+        self.maps.push(map);
+    }
+
+    pub(crate) fn append(&mut self, mut other: Self) {
+        self.maps.append(&mut other.maps);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Float;
+    use crate::binomial_tree_map::r#static::StaticBinomialTreeMap;
+    use crate::instruments::{
+        AmericanOption, Barrier, BarrierKind, BarrierOption, EuropeanOption, Monitoring,
+        OptionType, StrikeContract,
+    };
+    use crate::model::{CoxRossRubenstein, EvaluatedBinomialTree, Expiry, Spot, leaf_smoothing};
+    use pretty_assertions::assert_eq;
+    use rayon::prelude::*;
+
+    fn model(
+        number_of_steps: usize,
+    ) -> CoxRossRubenstein<StaticBinomialTreeMap, leaf_smoothing::Black> {
+        CoxRossRubenstein::new(
+            StaticBinomialTreeMap::with_capacity(number_of_steps),
+            Spot(100.0),
+            number_of_steps,
+            Expiry(0.5),
+            0.3,
+            0.05,
+            0.02,
+        )
+    }
+
+    #[test]
+    fn test_workspace_reuse() {
+        let mut workspace = Workspace::new();
+        let option = AmericanOption::new(OptionType::Put, 105.0, 0.5);
+
+        let eval = model(100).eval_in(option, &mut workspace);
+        let greeks = eval.full_greeks();
+        eval.recycle(&mut workspace);
+        // The evaluated tree and the trees of vega, rho and psi share two maps
+        assert_eq!(workspace.len(), 2);
+        // The node stack is kept for the next tree with as many steps
+        let stack = workspace.take_stack(100);
+        assert!(workspace.stacks.is_empty());
+        workspace.give_stack(stack);
+        assert_eq!(workspace.stacks.keys().collect::<Vec<_>>(), vec![&100]);
+
+        // Reused maps give the same values as new ones, also for fewer steps
+        for number_of_steps in [100, 50] {
+            let eval = model(number_of_steps).eval_in(option, &mut workspace);
+            assert_eq!(
+                eval.full_greeks(),
+                model(number_of_steps).eval(option).full_greeks()
+            );
+            eval.recycle(&mut workspace);
+        }
+        assert_eq!(model(100).eval(option).full_greeks(), greeks);
+
+        // Knock-in barriers interpolate between two knock-in trees, which both need the
+        // knocked-in tree, so the bumped trees need four maps besides the evaluated one
+        let option = BarrierOption::with_barrier(
+            EuropeanOption::new(OptionType::Call, 100.0, 0.5),
+            Barrier {
+                kind: BarrierKind::DownAndIn,
+                level: 90.0,
+                rebate: 0.0,
+                monitoring: Monitoring::Continuous,
+            },
+        );
+        let eval = model(100).eval_in(option.clone(), &mut workspace);
+        assert_eq!(
+            eval.full_greeks(),
+            model(100).eval(option.clone()).full_greeks()
+        );
+        eval.recycle(&mut workspace);
+        assert_eq!(workspace.len(), 5);
+    }
+
+    #[test]
+    fn test_thread_local_workspace() {
+        let strikes: Vec<_> = (0..32).map(|k| 90.0 + k as Float).collect();
+
+        let eval_greeks = |strike: Float| {
+            Workspace::<StaticBinomialTreeMap>::with_thread_local(|workspace| {
+                let option = AmericanOption::new(OptionType::Call, strike, 0.5);
+                let eval = model(100).eval_in(option, workspace);
+                let greeks = eval.full_greeks();
+                eval.recycle(workspace);
+                greeks
+            })
+        };
+        let greeks: Vec<_> = strikes
+            .par_iter()
+            .map(|strike| eval_greeks(*strike))
+            .collect();
+
+        for (strike, greeks) in strikes.iter().zip(greeks) {
+            let option = AmericanOption::new(OptionType::Call, *strike, 0.5);
+            assert_eq!(greeks, model(100).eval(option).full_greeks());
+        }
+
+        // The maps stay with the thread between calls
+        eval_greeks(100.0);
+        assert_eq!(
+            Workspace::<StaticBinomialTreeMap>::with_thread_local(|workspace| workspace.len()),
+            2
+        );
+    }
+}